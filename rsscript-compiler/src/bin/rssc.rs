@@ -0,0 +1,127 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use rsscript_compiler::{
+    build::{self, Error},
+    diagnostic::Diagnostic,
+    Builder,
+};
+
+const USAGE: &str = "usage:
+    rssc check <file>...          parse and lower scripts, reporting any error
+    rssc emit <file>              print the Rust code generated for a script
+    rssc emit <dir> -o <out-dir>  compile a directory of scripts into <out-dir>
+    rssc ast <file>               print the parsed syntax tree of a script";
+
+enum Command {
+    Check(Vec<PathBuf>),
+    Emit(PathBuf, Option<PathBuf>),
+    Ast(PathBuf),
+}
+
+impl Command {
+    fn parse(mut args: impl Iterator<Item = String>) -> Option<Self> {
+        let command = args.next()?;
+        let args: Vec<_> = args.collect();
+
+        match (command.as_str(), args.as_slice()) {
+            ("check", [_, ..]) => Some(Self::Check(args.iter().map(PathBuf::from).collect())),
+            ("emit", [path]) => Some(Self::Emit(path.into(), None)),
+            ("emit", [path, flag, out_dir]) if flag == "-o" || flag == "--out-dir" => {
+                Some(Self::Emit(path.into(), Some(out_dir.into())))
+            }
+            ("ast", [path]) => Some(Self::Ast(path.into())),
+            _ => None,
+        }
+    }
+}
+
+// Failures are already reported by the time they reach `main`.
+struct Failed;
+
+fn read(path: &Path) -> Result<String, Failed> {
+    fs::read_to_string(path).map_err(|err| {
+        eprintln!("error: couldn't read `{}`: {}", path.display(), err);
+        Failed
+    })
+}
+
+fn report(diagnostics: &[Diagnostic], source: impl Fn(&Diagnostic) -> String) -> Failed {
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic.render(&source(diagnostic)));
+    }
+    eprintln!(
+        "error: could not compile due to {} previous error(s)",
+        diagnostics.len()
+    );
+    Failed
+}
+
+fn compile(path: &Path) -> Result<proc_macro2::TokenStream, Failed> {
+    let source = read(path)?;
+    build::compile(&path.display().to_string(), &source)
+        .map_err(|diagnostics| report(&diagnostics, |_| source.clone()))
+}
+
+fn run(command: Command) -> Result<(), Failed> {
+    match command {
+        Command::Check(paths) => {
+            let mut failed = false;
+            for path in paths {
+                failed |= compile(&path).is_err();
+            }
+            if failed {
+                return Err(Failed);
+            }
+        }
+        Command::Emit(path, None) => println!("{}", compile(&path)?),
+        Command::Emit(dir, Some(out_dir)) => {
+            let built = Builder::new(&dir)
+                .out_dir(out_dir)
+                .rerun_if_changed(false)
+                .build();
+
+            match built {
+                Ok(root) => println!("{}", root.display()),
+                Err(Error::Io(path, err)) => {
+                    eprintln!("error: {}: {}", path.display(), err);
+                    return Err(Failed);
+                }
+                Err(Error::Script(diagnostics)) => {
+                    let source = |diagnostic: &Diagnostic| {
+                        fs::read_to_string(dir.join(&diagnostic.file)).unwrap_or_default()
+                    };
+                    return Err(report(&diagnostics, source));
+                }
+            }
+        }
+        Command::Ast(path) => {
+            let source = read(&path)?;
+            let name = path.display().to_string();
+            match build::parse(&source) {
+                Ok(file) => println!("{:#?}", file),
+                Err(err) => {
+                    let diagnostics = Diagnostic::from_error(&name, err);
+                    return Err(report(&diagnostics, |_| source.clone()));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let Some(command) = Command::parse(std::env::args().skip(1)) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+
+    match run(command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failed) => ExitCode::FAILURE,
+    }
+}
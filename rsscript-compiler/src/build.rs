@@ -0,0 +1,600 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{self, Debug, Display},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{spanned::Spanned, Ident};
+
+use crate::{
+    diagnostic::Diagnostic,
+    encode::{scope, throws::Throwing, ToRustCode},
+    parser::{expr::template, item::ImportName, File, Item},
+};
+
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Script(Vec<Diagnostic>),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            Self::Script(diagnostics) => {
+                for diagnostic in diagnostics {
+                    writeln!(f, "{}", diagnostic)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// `build.rs` scripts usually unwrap the result, so the diagnostics are shown as they are.
+impl Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::error::Error for Error {}
+
+// Parses the source of a script file, which unlike macro input may contain backtick templates.
+pub fn parse(source: &str) -> syn::Result<File> {
+    syn::parse_str(&template::desugar(source))
+}
+
+// Parses and lowers a single script, without resolving its imports.
+pub fn compile(name: &str, source: &str) -> Result<TokenStream, Vec<Diagnostic>> {
+    let file = parse(source).map_err(|err| Diagnostic::from_error(name, err))?;
+    let tokens = file.into_rust_token_stream();
+
+    let diagnostics = Diagnostic::from_tokens(name, &tokens);
+    if diagnostics.is_empty() {
+        Ok(tokens)
+    } else {
+        Err(diagnostics)
+    }
+}
+
+struct Script {
+    path: PathBuf,
+    name: String,
+    module: Vec<String>,
+    file: File,
+}
+
+impl Script {
+    fn exports(&self) -> impl Iterator<Item = &Ident> {
+        self.file.items.iter().flat_map(Item::exports)
+    }
+}
+
+// Compiles every `.rss` file below a directory into Rust modules, to be used from `build.rs`:
+//
+//     rsscript_compiler::Builder::new("scripts").build().unwrap();
+//
+// and then included in a module, whose names are visible to the scripts:
+//
+//     mod scripts {
+//         use rsscript::types::number::Number;
+//
+//         include!(concat!(env!("OUT_DIR"), "/rsscript.rs"));
+//     }
+pub struct Builder {
+    src_dir: PathBuf,
+    out_dir: Option<PathBuf>,
+    name: String,
+    rerun_if_changed: bool,
+}
+
+impl Builder {
+    pub fn new(src_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            src_dir: src_dir.into(),
+            out_dir: None,
+            name: "rsscript".to_string(),
+            rerun_if_changed: true,
+        }
+    }
+
+    // Defaults to `OUT_DIR`.
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    // The name of the generated root file and of the directory holding the script modules.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    // Whether to print `cargo:rerun-if-changed` for the scripts, which only makes sense in
+    // `build.rs`.
+    pub fn rerun_if_changed(mut self, rerun_if_changed: bool) -> Self {
+        self.rerun_if_changed = rerun_if_changed;
+        self
+    }
+
+    // Returns the path of the root file.
+    pub fn build(&self) -> Result<PathBuf, Error> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or_else(|| {
+                    Error::Io(
+                        PathBuf::from("OUT_DIR"),
+                        io::Error::new(io::ErrorKind::NotFound, "environment variable is not set"),
+                    )
+                })?,
+        };
+
+        if self.rerun_if_changed {
+            println!("cargo:rerun-if-changed={}", self.src_dir.display());
+        }
+
+        let mut paths = Vec::new();
+        walk(&self.src_dir, &mut paths)?;
+
+        let mut diagnostics = Vec::new();
+        let mut scripts = Vec::new();
+        for path in paths {
+            if self.rerun_if_changed {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+
+            let relative = path.strip_prefix(&self.src_dir).unwrap();
+            let name = relative.display().to_string();
+            let source = fs::read_to_string(&path).map_err(|err| Error::Io(path.clone(), err))?;
+
+            let module: Vec<String> = relative
+                .with_extension("")
+                .iter()
+                .map(|segment| segment.to_string_lossy().into_owned())
+                .collect();
+            if let Some(segment) = module
+                .iter()
+                .find(|segment| syn::parse_str::<Ident>(segment).is_err())
+            {
+                diagnostics.push(Diagnostic {
+                    file: name,
+                    line: 1,
+                    column: 1,
+                    message: format!("`{}` is not a valid module name", segment),
+                });
+                continue;
+            }
+
+            match parse(&source) {
+                Ok(file) => scripts.push(Script {
+                    path,
+                    name,
+                    module,
+                    file,
+                }),
+                Err(err) => diagnostics.extend(Diagnostic::from_error(&name, err)),
+            }
+        }
+
+        let visible = throwing(&scripts);
+        let mut outputs = Vec::new();
+        for script in &scripts {
+            let interfaces = match imported_interfaces(&scripts, script) {
+                Ok(interfaces) => interfaces,
+                Err(errors) => {
+                    diagnostics.extend(errors);
+                    continue;
+                }
+            };
+
+            let tokens = imported_throwing(&scripts, script, &visible).scope(|| {
+                scope::with_interfaces(interfaces, || script.file.to_rust_token_stream())
+            });
+            let errors = Diagnostic::from_tokens(&script.name, &tokens);
+            if errors.is_empty() {
+                outputs.push((script, tokens));
+            } else {
+                diagnostics.extend(errors);
+            }
+        }
+
+        if !diagnostics.is_empty() {
+            return Err(Error::Script(diagnostics));
+        }
+
+        let mut root = Module::default();
+        for (script, tokens) in outputs {
+            let out_path = out_dir
+                .join(&self.name)
+                .join(script.path.strip_prefix(&self.src_dir).unwrap())
+                .with_extension("rs");
+            let code = quote! {
+                #[allow(unused_imports)]
+                use super::*;
+
+                #tokens
+            };
+
+            fs::create_dir_all(out_path.parent().unwrap())
+                .map_err(|err| Error::Io(out_path.clone(), err))?;
+            fs::write(&out_path, code.to_string())
+                .map_err(|err| Error::Io(out_path.clone(), err))?;
+
+            root.insert(&script.module, out_path);
+        }
+
+        let root_path = out_dir.join(&self.name).with_extension("rs");
+        fs::write(&root_path, root.to_rust_token_stream().to_string())
+            .map_err(|err| Error::Io(root_path.clone(), err))?;
+
+        Ok(root_path)
+    }
+}
+
+fn walk(dir: &Path, paths: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let mut entries = fs::read_dir(dir)
+        .and_then(|entries| {
+            entries
+                .map(|entry| entry.map(|entry| entry.path()))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|err| Error::Io(dir.to_path_buf(), err))?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            walk(&path, paths)?;
+        } else if path.extension().is_some_and(|extension| extension == "rss") {
+            paths.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+// Checks the imports of `script` against the exports of the scripts they name, and returns the
+// interfaces they bring into scope.
+fn imported_interfaces(
+    scripts: &[Script],
+    script: &Script,
+) -> Result<Vec<scope::Interface>, Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    let mut interfaces = Vec::new();
+
+    for item in &script.file.items {
+        let Item::Import(import) = item else {
+            continue;
+        };
+
+        let Some(target) = resolve(scripts, &script.module, &import.path) else {
+            let path = &import.path;
+            diagnostics.push(Diagnostic::new(
+                &script.name,
+                import.path.span(),
+                format!("cannot find a script at `{}`", quote!(#path)),
+            ));
+            continue;
+        };
+        let exports: HashSet<_> = target.exports().collect();
+
+        for name in &import.items {
+            let (ident, local) = match (name.ident(), name.local_ident()) {
+                (Some(ident), Some(local)) => (ident, local),
+                _ => {
+                    interfaces.extend(
+                        exports
+                            .iter()
+                            .filter_map(|&ident| find_interface(scripts, target, ident, ident)),
+                    );
+                    continue;
+                }
+            };
+
+            if !exports.contains(ident) {
+                diagnostics.push(Diagnostic::new(
+                    &script.name,
+                    ident.span(),
+                    format!("`{}` is not exported by `{}`", ident, target.name),
+                ));
+                continue;
+            }
+            interfaces.extend(find_interface(scripts, target, ident, local));
+        }
+    }
+
+    if diagnostics.is_empty() {
+        Ok(interfaces)
+    } else {
+        Err(diagnostics)
+    }
+}
+
+// Finds which of the functions and methods visible in each script throw, imported ones included.
+// Scripts can import from each other both ways, so this is repeated until nothing changes.
+fn throwing(scripts: &[Script]) -> Vec<Throwing> {
+    let mut visible = vec![Throwing::default(); scripts.len()];
+    loop {
+        let next: Vec<_> = scripts
+            .iter()
+            .map(|script| {
+                let mut throwing = imported_throwing(scripts, script, &visible);
+                let declared = throwing.clone().scope(|| Throwing::of(&script.file.items));
+                throwing.merge(declared);
+                throwing
+            })
+            .collect();
+        if next == visible {
+            return visible;
+        }
+        visible = next;
+    }
+}
+
+// Imported functions are renamed like the import, along with the methods and constructor of
+// imported classes and the functions of imported interfaces.
+fn imported_throwing(scripts: &[Script], script: &Script, visible: &[Throwing]) -> Throwing {
+    let mut throwing = Throwing::default();
+
+    for item in &script.file.items {
+        let Item::Import(import) = item else {
+            continue;
+        };
+        let Some(target) = resolve(scripts, &script.module, &import.path) else {
+            continue;
+        };
+        let index = scripts
+            .iter()
+            .position(|script| std::ptr::eq(script, target))
+            .unwrap();
+        let exports: HashSet<_> = target.exports().map(ToString::to_string).collect();
+
+        let mut imported = Throwing::default();
+        for (name, &throws) in &visible[index].fns {
+            let (head, rest) = match name.split_once('.') {
+                Some((head, rest)) => (head, Some(rest)),
+                None => (name.as_str(), None),
+            };
+            if !exports.contains(head) {
+                continue;
+            }
+
+            for name in &import.items {
+                let local = match (name.ident(), name.local_ident()) {
+                    (Some(ident), Some(local)) if ident == head => local.to_string(),
+                    (Some(_), Some(_)) => continue,
+                    _ => head.to_string(),
+                };
+                let local = match rest {
+                    Some(rest) => format!("{}.{}", local, rest),
+                    None => local,
+                };
+                imported.fns.insert(local, throws);
+            }
+        }
+        throwing.merge(imported);
+    }
+
+    throwing
+}
+
+// Import paths are relative to the directory of the importing script.
+fn resolve<'a>(scripts: &'a [Script], module: &[String], path: &syn::Path) -> Option<&'a Script> {
+    let mut target = module[..module.len() - 1].to_vec();
+
+    for segment in &path.segments {
+        if segment.ident == "super" {
+            target.pop()?;
+        } else {
+            target.push(segment.ident.to_string());
+        }
+    }
+
+    scripts.iter().find(|script| script.module == target)
+}
+
+// Follows re-exports until the interface named `ident` is found, renaming it to `local`.
+fn find_interface(
+    scripts: &[Script],
+    script: &Script,
+    ident: &Ident,
+    local: &Ident,
+) -> Option<scope::Interface> {
+    fn find(
+        scripts: &[Script],
+        script: &Script,
+        ident: &Ident,
+        seen: &mut Vec<String>,
+    ) -> Option<scope::Interface> {
+        if seen.contains(&script.name) {
+            return None;
+        }
+        seen.push(script.name.clone());
+
+        script.file.items.iter().find_map(|item| match item {
+            Item::Interface(item) if item.ident == *ident => Some(scope::Interface::new(item)),
+            Item::Import(import) => {
+                let name = import
+                    .items
+                    .iter()
+                    .find(|name| name.local_ident() == Some(ident))?;
+                let target = resolve(scripts, &script.module, &import.path)?;
+                match name {
+                    ImportName::Glob(_) => None,
+                    name => find(scripts, target, name.ident()?, seen),
+                }
+            }
+            _ => None,
+        })
+    }
+
+    let mut interface = find(scripts, script, ident, &mut Vec::new())?;
+    interface.ident = local.clone();
+    Some(interface)
+}
+
+#[derive(Default)]
+struct Module {
+    scripts: BTreeMap<String, PathBuf>,
+    modules: BTreeMap<String, Module>,
+}
+
+impl Module {
+    fn insert(&mut self, module: &[String], path: PathBuf) {
+        match module {
+            [name] => {
+                self.scripts.insert(name.clone(), path);
+            }
+            [dir, rest @ ..] => self
+                .modules
+                .entry(dir.clone())
+                .or_default()
+                .insert(rest, path),
+            [] => {}
+        }
+    }
+}
+
+impl ToRustCode for Module {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        for (name, path) in &self.scripts {
+            let ident = format_ident!("{}", name, span = Span::call_site());
+            let path = path.display().to_string();
+            tokens.extend(quote! {
+                #[path = #path]
+                pub mod #ident;
+            });
+        }
+
+        for (name, module) in &self.modules {
+            let ident = format_ident!("{}", name, span = Span::call_site());
+            let module = module.to_rust_token_stream();
+            tokens.extend(quote! {
+                pub mod #ident {
+                    #[allow(unused_imports)]
+                    use super::*;
+
+                    #module
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn scripts(name: &str, files: &[(&str, &str)]) -> (PathBuf, PathBuf) {
+        let root = std::env::temp_dir().join(format!("rsscript-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let src_dir = root.join("src");
+        for (path, source) in files {
+            let path = src_dir.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, source).unwrap();
+        }
+
+        (src_dir, root.join("out"))
+    }
+
+    #[test]
+    fn build_with_imports() {
+        let (src_dir, out_dir) = scripts(
+            "imports",
+            &[
+                ("shapes/shape.rss", "export interface Shape { type Unit; }"),
+                (
+                    "square.rss",
+                    "import { Shape } from shapes::shape
+                    export class Square implements Shape { type Unit = Number; }",
+                ),
+            ],
+        );
+
+        let root = Builder::new(&src_dir).out_dir(&out_dir).build().unwrap();
+
+        let root = fs::read_to_string(root).unwrap();
+        assert!(root.contains("pub mod square ;"));
+        assert!(root.contains("pub mod shapes {"));
+        let square = fs::read_to_string(out_dir.join("rsscript/square.rs")).unwrap();
+        assert!(square.contains(
+            &quote!(
+                use super::shapes::shape::{Shape};
+            )
+            .to_string()
+        ));
+        assert!(square.contains(&quote!(impl Shape for Square { type Unit = Number; }).to_string()));
+
+        fs::remove_dir_all(out_dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn imported_throwing_functions() {
+        let (src_dir, out_dir) = scripts(
+            "throwing",
+            &[
+                (
+                    "checks.rss",
+                    "export function check(value: Number): Number {
+                        if (value < 0) { throw RangeError(\"negative\"); }
+                        value
+                    }
+                    export class Checked {
+                        constructor(value: Number) { check(value); }
+                    }",
+                ),
+                (
+                    "uses.rss",
+                    "import { check as verify, Checked } from checks
+                    function twice(value: Number): Number { verify(value) * 2 }
+                    function make(): Number { new Checked(1); 1 }",
+                ),
+            ],
+        );
+
+        Builder::new(&src_dir).out_dir(&out_dir).build().unwrap();
+
+        let uses = fs::read_to_string(out_dir.join("rsscript/uses.rs")).unwrap();
+        let result = quote!(-> ::std::result::Result<Number, ::rsscript::types::error::Error>);
+        assert!(uses.contains(&quote!(fn twice(value: Number) #result).to_string()));
+        assert!(uses.contains(&quote!(fn make() #result).to_string()));
+
+        fs::remove_dir_all(out_dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn import_errors() {
+        let (src_dir, out_dir) = scripts(
+            "import-errors",
+            &[
+                ("a.rss", "interface Hidden {}"),
+                (
+                    "b.rss",
+                    "import { Hidden } from a\nimport { X } from missing",
+                ),
+            ],
+        );
+
+        let Err(Error::Script(diagnostics)) = Builder::new(&src_dir).out_dir(&out_dir).build()
+        else {
+            panic!("the imports should not resolve");
+        };
+        let diagnostics: Vec<_> = diagnostics.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            diagnostics,
+            [
+                "b.rss:1:10: `Hidden` is not exported by `a.rss`",
+                "b.rss:2:19: cannot find a script at `missing`",
+            ]
+        );
+
+        fs::remove_dir_all(out_dir.parent().unwrap()).unwrap();
+    }
+}
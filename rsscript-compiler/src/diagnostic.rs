@@ -0,0 +1,108 @@
+use std::fmt::{self, Display};
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use syn::LitStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    // Line and column are only meaningful for spans produced outside of a procedural macro, or
+    // while the fallback implementation of proc-macro2 is forced.
+    pub fn new(file: &str, span: Span, message: impl Display) -> Self {
+        let start = span.start();
+
+        Self {
+            file: file.to_string(),
+            line: start.line,
+            column: start.column + 1,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn from_error(file: &str, error: syn::Error) -> Vec<Self> {
+        error
+            .into_iter()
+            .map(|error| Self::new(file, error.span(), error))
+            .collect()
+    }
+
+    // Collects the `compile_error!`s emitted while lowering a script.
+    pub fn from_tokens(file: &str, tokens: &TokenStream) -> Vec<Self> {
+        let mut diagnostics = Vec::new();
+        let tokens: Vec<_> = tokens.clone().into_iter().collect();
+
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                TokenTree::Ident(ident) if ident == "compile_error" => {
+                    let Some(TokenTree::Group(group)) = tokens.get(i + 2) else {
+                        continue;
+                    };
+                    if let Ok(message) = syn::parse2::<LitStr>(group.stream()) {
+                        diagnostics.push(Self::new(file, ident.span(), message.value()));
+                    }
+                }
+                TokenTree::Group(group) => {
+                    diagnostics.extend(Self::from_tokens(file, &group.stream()));
+                }
+                _ => {}
+            }
+        }
+
+        diagnostics
+    }
+
+    // Shows the message along with the offending line of `source`, like rustc does.
+    pub fn render(&self, source: &str) -> String {
+        let mut rendered = format!(
+            "error: {}\n --> {}:{}:{}\n",
+            self.message, self.file, self.line, self.column
+        );
+
+        if let Some(line) = source.lines().nth(self.line.wrapping_sub(1)) {
+            let number = self.line.to_string();
+            let gutter = " ".repeat(number.len());
+            rendered.push_str(&format!(
+                "{gutter} |\n{number} | {line}\n{gutter} | {}^\n",
+                " ".repeat(self.column.saturating_sub(1))
+            ));
+        }
+
+        rendered
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file, self.line, self.column, self.message
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let diagnostic = Diagnostic {
+            file: "main.rss".to_string(),
+            line: 2,
+            column: 7,
+            message: "oops".to_string(),
+        };
+
+        assert_eq!(
+            diagnostic.render("let a = 1;\nclass {}\n"),
+            "error: oops\n --> main.rss:2:7\n  |\n2 | class {}\n  |       ^\n"
+        );
+    }
+}
@@ -1,4 +1,14 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Delimiter, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{punctuated::Punctuated, Ident};
+
+pub mod scope;
+
+pub mod context;
+
+pub mod narrow;
+
+pub mod throws;
 
 pub trait ToRustCode {
     fn to_rust_code(&self, tokens: &mut TokenStream);
@@ -26,3 +36,77 @@ impl<T: ToRustCode> ToRustCode for Option<T> {
         }
     }
 }
+
+impl<T: ToRustCode + ?Sized> ToRustCode for Box<T> {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        (**self).to_rust_code(tokens);
+    }
+}
+
+impl<T: ToRustCode + ?Sized> ToRustCode for &T {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        (**self).to_rust_code(tokens);
+    }
+}
+
+impl<T: ToRustCode> ToRustCode for [T] {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        for item in self {
+            item.to_rust_code(tokens);
+        }
+    }
+}
+
+impl<T: ToRustCode> ToRustCode for Vec<T> {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.as_slice().to_rust_code(tokens);
+    }
+}
+
+impl<T: ToRustCode, P: ToTokens> ToRustCode for Punctuated<T, P> {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        for pair in self.pairs() {
+            pair.value().to_rust_code(tokens);
+            pair.punct().to_tokens(tokens);
+        }
+    }
+}
+
+pub fn runtime() -> TokenStream {
+    quote!(::rsscript::types)
+}
+
+pub fn unsupported(span: Span, what: &str) -> TokenStream {
+    syn::Error::new(span, format!("{} cannot be lowered to Rust yet", what)).to_compile_error()
+}
+
+pub fn private_ident(name: &str) -> Ident {
+    Ident::new(&format!("__rsscript_{}", name), Span::mixed_site())
+}
+
+// Opens up a lowered block holding a single expression, whose braces `unused_braces` warns about
+// where a function body puts it, after statements or as an argument of `Ok`. Parentheses around
+// the expression, which `unused_parens` warns about there, are removed too.
+pub fn unbraced(block: TokenStream) -> TokenStream {
+    let Ok(syn::Block { stmts, .. }) = syn::parse2(block.clone()) else {
+        return block;
+    };
+    let inner = single_group(&block, Delimiter::Brace).unwrap();
+    match &stmts[..] {
+        [syn::Stmt::Expr(syn::Expr::Paren(_), None)] => {
+            single_group(&inner, Delimiter::Parenthesis).unwrap()
+        }
+        [syn::Stmt::Expr(_, None)] => inner,
+        _ => block,
+    }
+}
+
+fn single_group(tokens: &TokenStream, delimiter: Delimiter) -> Option<TokenStream> {
+    let mut trees = tokens.clone().into_iter();
+    match (trees.next(), trees.next()) {
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == delimiter => {
+            Some(group.stream())
+        }
+        _ => None,
+    }
+}
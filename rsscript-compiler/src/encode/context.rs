@@ -0,0 +1,353 @@
+use std::cell::RefCell;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::{Ident, Lifetime};
+
+use crate::encode;
+
+// Where a thrown error goes from the code being lowered.
+#[derive(Clone)]
+pub enum ThrowTarget {
+    // A `try` or `catch` block, lowered to a labeled block evaluating to a `Result`.
+    Label(Lifetime),
+    // The enclosing function, which returns a `Result`.
+    Return,
+    // Nowhere, so throwing is an error with the message.
+    Nowhere(&'static str),
+    // Nowhere either, from the body of a generator function, which can `yield` but not return a
+    // value.
+    Generator,
+}
+
+// What a `break` or `continue` can leave.
+#[derive(Clone, Copy, PartialEq)]
+pub enum JumpKind {
+    Loop,
+    Switch,
+    // Any other labeled statement, which only a labeled `break` can leave.
+    Block,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Jump {
+    Break,
+    Continue,
+}
+
+// A `return`, `break` or `continue` out of a `try` statement with a `finally` block, which is
+// taken once `finally` has run.
+pub enum Exit {
+    Return(Span),
+    Jump(Jump, Span, Option<Ident>),
+}
+
+enum FrameKind {
+    Throw(ThrowTarget),
+    // The `try` and `catch` blocks of a statement with a `finally` block, which exits break out
+    // of with a completion.
+    Finally {
+        lifetime: Lifetime,
+        exits: Vec<Exit>,
+    },
+    Jump {
+        kind: JumpKind,
+        label: Option<String>,
+        lifetime: Lifetime,
+        // Whether it is lowered to a Rust loop, which an unlabeled `break` or `continue` leaves.
+        rust_loop: bool,
+    },
+    // Generated Rust loops or labeled blocks that script jumps cross.
+    Opaque,
+}
+
+struct Frame {
+    kind: FrameKind,
+    // Whether an error was thrown to it, or whether its label was jumped to.
+    used: bool,
+}
+
+thread_local! {
+    static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+struct FrameGuard;
+
+impl Drop for FrameGuard {
+    fn drop(&mut self) {
+        FRAMES.with(|frames| frames.borrow_mut().pop());
+    }
+}
+
+fn with_frame<R>(kind: FrameKind, f: impl FnOnce() -> R) -> (R, bool) {
+    FRAMES.with(|frames| frames.borrow_mut().push(Frame { kind, used: false }));
+    let guard = FrameGuard;
+    let result = f();
+    let used = FRAMES.with(|frames| frames.borrow().last().unwrap().used);
+    drop(guard);
+
+    (result, used)
+}
+
+fn private_lifetime(name: &str) -> Lifetime {
+    let depth = FRAMES.with(|frames| frames.borrow().len());
+    Lifetime {
+        apostrophe: Span::mixed_site(),
+        ident: encode::private_ident(&format!("{}{}", name, depth)),
+    }
+}
+
+// Lowers `f` with errors thrown in it going to `target`. Also returns whether any error was
+// thrown to it, which is how functions are found to need a `Result`.
+pub fn with_throw_target<R>(target: ThrowTarget, f: impl FnOnce() -> R) -> (R, bool) {
+    with_frame(FrameKind::Throw(target), f)
+}
+
+// Labels of nested `try` blocks must not shadow each other.
+pub fn try_label() -> Lifetime {
+    private_lifetime("try")
+}
+
+// Sends `error`, an expression of the runtime `Error` type, to the current throw target. Outside
+// of any function, in the statements of a script, the error ends the program like an uncaught
+// exception.
+pub fn propagate(span: Span, error: TokenStream) -> TokenStream {
+    let target = FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        let frame = frames
+            .iter_mut()
+            .rev()
+            .find(|frame| matches!(frame.kind, FrameKind::Throw(_)))?;
+        frame.used = true;
+        match &frame.kind {
+            FrameKind::Throw(target) => Some(target.clone()),
+            _ => None,
+        }
+    });
+
+    match target {
+        Some(ThrowTarget::Label(label)) => {
+            quote!(break #label ::std::result::Result::Err(#error))
+        }
+        Some(ThrowTarget::Return) => quote!(return ::std::result::Result::Err(#error)),
+        Some(ThrowTarget::Nowhere(message)) => syn::Error::new(span, message).to_compile_error(),
+        Some(ThrowTarget::Generator) => syn::Error::new(
+            span,
+            "errors cannot be thrown out of a generator, catch them in its body",
+        )
+        .to_compile_error(),
+        None => quote!(::std::panic!("Uncaught {}", #error)),
+    }
+}
+
+// Lowers `f`, the `try` and `catch` blocks of a statement with a `finally` block, which is
+// labeled `lifetime`. Returns the exits out of them, which `Completion::Jump` numbers by their
+// position.
+pub fn with_finally<R>(lifetime: Lifetime, f: impl FnOnce() -> R) -> (R, Vec<Exit>) {
+    let kind = FrameKind::Finally {
+        lifetime,
+        exits: Vec::new(),
+    };
+    FRAMES.with(|frames| frames.borrow_mut().push(Frame { kind, used: false }));
+    let guard = FrameGuard;
+    let result = f();
+    let exits = FRAMES.with(
+        |frames| match &mut frames.borrow_mut().last_mut().unwrap().kind {
+            FrameKind::Finally { exits, .. } => std::mem::take(exits),
+            _ => unreachable!(),
+        },
+    );
+    drop(guard);
+
+    (result, exits)
+}
+
+// Labels of nested `finally` blocks must not shadow each other.
+pub fn finally_label() -> Lifetime {
+    private_lifetime("finally")
+}
+
+// Breaks out of the innermost `finally` frame the current function has with `exit`, or returns
+// `None` if there is none.
+fn leave_finally(exit: Exit, value: Option<TokenStream>) -> Option<TokenStream> {
+    let runtime = encode::runtime();
+    FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        for frame in frames.iter_mut().rev() {
+            match &mut frame.kind {
+                FrameKind::Finally { lifetime, exits } => {
+                    let completion = match exit {
+                        Exit::Return(_) => {
+                            if !exits.iter().any(|exit| matches!(exit, Exit::Return(_))) {
+                                exits.push(exit);
+                            }
+                            let value = value.unwrap_or_else(|| quote!(()));
+                            quote!(#runtime::error::Completion::Return(#value))
+                        }
+                        Exit::Jump(..) => {
+                            let index = exits.len();
+                            exits.push(exit);
+                            quote!(#runtime::error::Completion::Jump(#index))
+                        }
+                    };
+                    return Some(quote!(break #lifetime #completion));
+                }
+                FrameKind::Throw(ThrowTarget::Label(_)) => {}
+                FrameKind::Throw(_) => return None,
+                FrameKind::Jump { .. } | FrameKind::Opaque => {}
+            }
+        }
+        None
+    })
+}
+
+// Lowers `f`, the body of a statement that `break` or `continue` can leave. Returns the Rust
+// label to put on the statement if a jump needs it.
+pub fn with_jump_target<R>(
+    kind: JumpKind,
+    label: Option<&Ident>,
+    rust_loop: bool,
+    f: impl FnOnce() -> R,
+) -> (R, Option<Lifetime>) {
+    let lifetime = private_lifetime("label");
+    let kind = FrameKind::Jump {
+        kind,
+        label: label.map(ToString::to_string),
+        lifetime: lifetime.clone(),
+        rust_loop,
+    };
+    let (result, used) = with_frame(kind, f);
+
+    (result, used.then_some(lifetime))
+}
+
+// Lowers `f` inside a generated Rust loop or labeled block, which is not a target of its own.
+pub fn with_opaque<R>(f: impl FnOnce() -> R) -> R {
+    with_frame(FrameKind::Opaque, f).0
+}
+
+// Lowers `break` or `continue` to the statement it leaves, which must be in the same function.
+pub fn jump(jump: Jump, span: Span, label: Option<&Ident>) -> TokenStream {
+    let keyword = match jump {
+        Jump::Break => quote_spanned!(span=> break),
+        Jump::Continue => quote_spanned!(span=> continue),
+    };
+
+    let result = FRAMES.with(|frames| {
+        let mut frames = frames.borrow_mut();
+        let mut crossed = false;
+
+        for frame in frames.iter_mut().rev() {
+            match &frame.kind {
+                FrameKind::Finally { .. } => return Ok(Target::Finally),
+                FrameKind::Throw(ThrowTarget::Label(_)) | FrameKind::Opaque => crossed = true,
+                FrameKind::Throw(_) => break,
+                FrameKind::Jump {
+                    kind,
+                    label: frame_label,
+                    lifetime,
+                    rust_loop,
+                } => {
+                    let found = match label {
+                        Some(label) => frame_label.as_deref() == Some(&label.to_string()),
+                        None => match jump {
+                            Jump::Break => *kind != JumpKind::Block,
+                            Jump::Continue => *kind == JumpKind::Loop,
+                        },
+                    };
+                    if !found {
+                        crossed |= *rust_loop || *kind == JumpKind::Block;
+                        continue;
+                    }
+                    if jump == Jump::Continue && *kind != JumpKind::Loop {
+                        return Err("`continue` can only jump to a loop".to_string());
+                    }
+                    if *rust_loop && !crossed {
+                        return Ok(Target::Nearest);
+                    }
+                    let lifetime = lifetime.clone();
+                    frame.used = true;
+                    return Ok(Target::Label(lifetime));
+                }
+            }
+        }
+
+        Err(match label {
+            Some(label) => format!("use of undeclared label `{}`", label),
+            None if jump == Jump::Continue => "`continue` outside of a loop".to_string(),
+            None => "`break` outside of a loop or `switch`".to_string(),
+        })
+    });
+
+    match result {
+        Ok(Target::Nearest) => keyword,
+        Ok(Target::Label(lifetime)) => quote!(#keyword #lifetime),
+        Ok(Target::Finally) => leave_finally(Exit::Jump(jump, span, label.cloned()), None).unwrap(),
+        Err(message) => syn::Error::new(span, message).to_compile_error(),
+    }
+}
+
+enum Target {
+    // The Rust loop the jump is in.
+    Nearest,
+    Label(Lifetime),
+    // A `finally` block to run first.
+    Finally,
+}
+
+// The target of the function being lowered, which `try` blocks in it do not change.
+fn function_target() -> Option<ThrowTarget> {
+    FRAMES.with(|frames| {
+        frames
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|frame| match &frame.kind {
+                FrameKind::Throw(ThrowTarget::Label(_)) => None,
+                FrameKind::Throw(target) => Some(target.clone()),
+                _ => None,
+            })
+    })
+}
+
+// Whether the code being lowered is in the body of a generator function, and not in a function
+// nested in it.
+pub fn in_generator() -> bool {
+    matches!(function_target(), Some(ThrowTarget::Generator))
+}
+
+// Lowers `return`, wrapping the value in `Ok` in a function that returns a `Result`. Inside a
+// `try` statement with a `finally` block, the value is kept until `finally` has run.
+pub fn return_value(span: Span, value: Option<TokenStream>) -> TokenStream {
+    let target = function_target();
+    let return_token = quote_spanned!(span=> return);
+
+    let generator = matches!(target, Some(ThrowTarget::Generator));
+    if !(generator && value.is_some()) {
+        if let Some(code) = leave_finally(Exit::Return(span), value.clone()) {
+            return code;
+        }
+    }
+
+    match target {
+        Some(ThrowTarget::Return) => {
+            let value = value.unwrap_or_else(|| quote!(()));
+            quote!(#return_token ::std::result::Result::Ok(#value))
+        }
+        Some(ThrowTarget::Generator) if value.is_some() => {
+            syn::Error::new(span, "a generator cannot return a value").to_compile_error()
+        }
+        Some(_) => quote!(#return_token #value),
+        None => syn::Error::new(span, "`return` outside of a function").to_compile_error(),
+    }
+}
+
+// Lowers the exit taken once the `finally` block it crossed has run, with `value` the value
+// returned, if any.
+pub fn resume(exit: &Exit, value: TokenStream) -> TokenStream {
+    match exit {
+        Exit::Return(span) if in_generator() => return_value(*span, None),
+        Exit::Return(span) => return_value(*span, Some(value)),
+        Exit::Jump(kind, span, label) => jump(*kind, *span, label.as_ref()),
+    }
+}
@@ -0,0 +1,324 @@
+use std::cell::RefCell;
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Lit, Member};
+
+use crate::{
+    encode::{
+        self,
+        scope::{self, Union, Variant, VariantKind},
+    },
+    parser::{
+        expr::{BinOp, Expr, ExprBinary, ExprField, ExprLit, ExprPath, ExprUnary, UnOp},
+        ty,
+    },
+};
+
+// A variable of a union type known to hold one variant, whose contents are bound by the `match`
+// it was narrowed with.
+#[derive(Clone)]
+struct Narrowed {
+    var: String,
+    tag: Option<Ident>,
+    variant: Variant,
+}
+
+thread_local! {
+    static NARROWED: RefCell<Vec<Narrowed>> = const { RefCell::new(Vec::new()) };
+}
+
+struct NarrowedGuard;
+
+impl Drop for NarrowedGuard {
+    fn drop(&mut self) {
+        NARROWED.with(|narrowed| narrowed.borrow_mut().pop());
+    }
+}
+
+fn with_narrowed<R>(var: &Ident, union: &Union, variant: usize, f: impl FnOnce() -> R) -> R {
+    NARROWED.with(|narrowed| {
+        narrowed.borrow_mut().push(Narrowed {
+            var: var.to_string(),
+            tag: union.tag.clone(),
+            variant: union.variants[variant].clone(),
+        })
+    });
+    let _guard = NarrowedGuard;
+    f()
+}
+
+fn narrowed(var: &Ident) -> Option<Narrowed> {
+    NARROWED.with(|narrowed| {
+        narrowed
+            .borrow()
+            .iter()
+            .rev()
+            .find(|narrowed| *var == narrowed.var)
+            .cloned()
+    })
+}
+
+// The name bound to the payload of a narrowed variable, or to one of its fields.
+pub fn binding(var: &Ident, field: Option<&Ident>) -> Ident {
+    match field {
+        Some(field) => encode::private_ident(&format!("narrowed_{}_{}", var, field)),
+        None => encode::private_ident(&format!("narrowed_{}", var)),
+    }
+}
+
+fn path_ident(expr: &Expr) -> Option<&Ident> {
+    match expr {
+        Expr::Path(ExprPath { path }) => path.get_ident(),
+        Expr::Paren(expr) => path_ident(&expr.expr),
+        _ => None,
+    }
+}
+
+// A read of a narrowed variable goes to what it is bound to. In place of an operand, it is the
+// bound value itself, so that methods can borrow it and fields can be assigned. Anything else
+// gets a copy, since the binding only borrows the variable.
+pub fn read(var: &Ident, field: Option<&Member>, place: bool) -> Option<TokenStream> {
+    let narrowed = narrowed(var)?;
+    let binding = match (&narrowed.variant.kind, field) {
+        (VariantKind::Type(_), None) => binding(var, None),
+        (VariantKind::Object { tag, fields }, Some(Member::Named(field))) => {
+            if narrowed.tag.as_ref() == Some(field) {
+                return tag.as_ref().map(ty::lit_code);
+            }
+            fields.iter().find(|(ident, _)| ident == field)?;
+            binding(var, Some(field))
+        }
+        _ => return None,
+    };
+
+    if place {
+        Some(quote!((*#binding)))
+    } else {
+        Some(quote!(::std::clone::Clone::clone(&*#binding)))
+    }
+}
+
+// Whether `var` narrowed to a variant is known to be in the variants given by `matches`.
+fn narrowed_match(var: &Ident, union: &Union, matches: &[bool]) -> Option<bool> {
+    let narrowed = narrowed(var)?;
+    let i = union
+        .variants
+        .iter()
+        .position(|variant| variant.ident == narrowed.variant.ident)?;
+    Some(matches[i])
+}
+
+// A condition on a variable of a union type that holds for some of its variants.
+pub struct Narrowing<'a> {
+    pub var: &'a Ident,
+    pub union: Union,
+    pub matches: Vec<bool>,
+}
+
+impl<'a> Narrowing<'a> {
+    // Comparisons of the discriminant or `typeof` with a literal, `instanceof` and `in`, possibly
+    // negated.
+    pub fn of(cond: &'a Expr) -> Option<Self> {
+        match cond {
+            Expr::Paren(expr) => Self::of(&expr.expr),
+            Expr::Unary(ExprUnary {
+                op: UnOp::Not(_),
+                expr,
+            }) => Self::of(expr).map(Self::negate),
+            Expr::Binary(binary) => Self::of_binary(binary),
+            _ => None,
+        }
+    }
+
+    pub fn of_binary(binary: &'a ExprBinary) -> Option<Self> {
+        let ExprBinary { left, op, right } = binary;
+        let negated = match op {
+            BinOp::StrictEq(..) | BinOp::Rust(syn::BinOp::Eq(_)) => false,
+            BinOp::StrictNe(..) | BinOp::Rust(syn::BinOp::Ne(_)) => true,
+            BinOp::InstanceOf(_) => {
+                let var = path_ident(left)?;
+                let class = path_ident(right)?;
+                return Self::new(var, |_, variant| variant.is_instance_of(class));
+            }
+            BinOp::In(_) => {
+                let Expr::Lit(ExprLit {
+                    lit: Lit::Str(name),
+                }) = &**left
+                else {
+                    return None;
+                };
+                let var = path_ident(right)?;
+                return Self::new(var, |_, variant| variant.has_field(&name.value()));
+            }
+            _ => return None,
+        };
+
+        let narrowing =
+            Self::of_comparison(left, right).or_else(|| Self::of_comparison(right, left))?;
+        Some(if negated {
+            narrowing.negate()
+        } else {
+            narrowing
+        })
+    }
+
+    fn of_comparison(subject: &'a Expr, value: &Expr) -> Option<Self> {
+        let Expr::Lit(ExprLit { lit }) = value else {
+            return None;
+        };
+
+        match subject {
+            Expr::Field(ExprField {
+                base,
+                optional: None,
+                member: Member::Named(member),
+                ..
+            }) => {
+                let var = path_ident(base)?;
+                let union = scope::variable_union(var)?;
+                if union.tag.as_ref() != Some(member) {
+                    return None;
+                }
+                Self::new(
+                    var,
+                    |_, variant| matches!(&variant.kind, VariantKind::Object { tag: Some(tag), .. } if ty::same_lit(tag, lit)),
+                )
+            }
+            Expr::Unary(ExprUnary {
+                op: UnOp::Typeof(_),
+                expr,
+            }) => {
+                let Lit::Str(lit) = lit else {
+                    return None;
+                };
+                let var = path_ident(expr)?;
+                let union = scope::variable_union(var)?;
+                // Only when every variant has a known `typeof` can the check be done by variant.
+                if union
+                    .variants
+                    .iter()
+                    .any(|variant| union.type_of(variant).is_none())
+                {
+                    return None;
+                }
+                Self::new(var, |union, variant| {
+                    union.type_of(variant) == Some(lit.value().as_str())
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn new(var: &'a Ident, f: impl Fn(&Union, &Variant) -> bool) -> Option<Self> {
+        let union = scope::variable_union(var)?;
+        let matches = union
+            .variants
+            .iter()
+            .map(|variant| f(&union, variant))
+            .collect();
+        Some(Self {
+            var,
+            union,
+            matches,
+        })
+    }
+
+    fn negate(mut self) -> Self {
+        for matches in &mut self.matches {
+            *matches = !*matches;
+        }
+        self
+    }
+
+    // Whether the variable is already narrowed, so that the condition is known while lowering.
+    pub fn known(&self) -> Option<bool> {
+        narrowed_match(self.var, &self.union, &self.matches)
+    }
+
+    fn variants(&self, matching: bool) -> Vec<usize> {
+        (0..self.matches.len())
+            .filter(|i| self.matches[*i] == matching)
+            .collect()
+    }
+
+    // The condition as a boolean expression, for where it does not narrow anything.
+    pub fn to_cond_code(&self) -> TokenStream {
+        if let Some(known) = self.known() {
+            return quote!(#known);
+        }
+
+        let var = self.var;
+        let union = &self.union.ident;
+        let patterns: Vec<TokenStream> = self
+            .variants(true)
+            .into_iter()
+            .map(|i| self.union.variants[i].pattern(union, None))
+            .collect();
+        if patterns.is_empty() {
+            quote!(false)
+        } else {
+            quote!(::std::matches!(&#var, #(#patterns)|*))
+        }
+    }
+
+    // Lowers `then` and `otherwise` into a `match` on the variable, narrowing it in either body if
+    // it can only be one variant there.
+    pub fn to_if_code(
+        &self,
+        then: impl FnOnce() -> TokenStream,
+        otherwise: Option<impl FnOnce() -> TokenStream>,
+    ) -> TokenStream {
+        let mut arms: Vec<Arm> = vec![(self.variants(true), Box::new(then))];
+        if let Some(otherwise) = otherwise {
+            arms.push((self.variants(false), Box::new(otherwise)));
+        }
+        match_code(self.var, &self.union, arms)
+    }
+}
+
+pub type Arm<'a> = (Vec<usize>, Box<dyn FnOnce() -> TokenStream + 'a>);
+
+// Lowers a `match` on a variable of a union type, in which every arm is lowered with the variable
+// narrowed if it matches a single variant. Arms take the variants no earlier arm took, and those
+// left without a body do nothing.
+pub fn match_code(var: &Ident, union: &Union, arms: Vec<Arm>) -> TokenStream {
+    let mut taken = vec![false; union.variants.len()];
+    let mut code = TokenStream::new();
+
+    for (variants, body) in arms {
+        let variants: Vec<usize> = variants.into_iter().filter(|i| !taken[*i]).collect();
+        for i in &variants {
+            taken[*i] = true;
+        }
+
+        let (pattern, body) = match variants.as_slice() {
+            [] => continue,
+            [i] => {
+                let pattern = union.variants[*i].pattern(&union.ident, Some(var));
+                (
+                    // The arm may not read everything it binds.
+                    quote!(#[allow(unused_variables)] #pattern),
+                    with_narrowed(var, union, *i, body),
+                )
+            }
+            variants => {
+                let patterns = variants
+                    .iter()
+                    .map(|i| union.variants[*i].pattern(&union.ident, None));
+                (quote!(#(#patterns)|*), body())
+            }
+        };
+        code.extend(quote!(#pattern => { #body }));
+    }
+
+    if taken.contains(&false) {
+        code.extend(quote!(_ => {}));
+    }
+    quote!(match &mut #var { #code })
+}
+
+// Whether `var` is narrowed, in which case it is not narrowed again.
+pub fn is_narrowed(var: &Ident) -> bool {
+    narrowed(var).is_some()
+}
@@ -0,0 +1,728 @@
+use std::{cell::RefCell, collections::HashMap, thread::LocalKey};
+
+use proc_macro2::{Group, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{parse::Parse, GenericArgument, Ident, Lit, Path, PathArguments, Signature, Type};
+
+use crate::{
+    encode::narrow,
+    parser::{
+        generics::GenericsParam,
+        item::{
+            class::ItemClass,
+            interface::{InterfaceItem, ItemInterface},
+        },
+    },
+};
+
+// Which accessors a member has, known as `Class.member` after the class declaring it.
+#[derive(Clone, Copy, Default)]
+pub struct Accessor {
+    pub getter: bool,
+    pub setter: bool,
+    pub is_static: bool,
+}
+
+#[derive(Clone, PartialEq)]
+pub enum EnumValue {
+    Number(f64),
+    String(String),
+}
+
+#[derive(Clone)]
+pub struct Enum {
+    pub is_const: bool,
+    pub members: Vec<(String, EnumValue)>,
+}
+
+impl Enum {
+    pub fn member(&self, name: &str) -> Option<&EnumValue> {
+        self.members
+            .iter()
+            .find_map(|(member, value)| (member == name).then_some(value))
+    }
+}
+
+#[derive(Clone)]
+pub enum VariantKind {
+    Null,
+    Undefined,
+    Lit(Lit),
+    Type(Type),
+    Object {
+        tag: Option<Lit>,
+        fields: Vec<(Ident, Type)>,
+    },
+}
+
+#[derive(Clone)]
+pub struct Variant {
+    pub ident: Ident,
+    pub kind: VariantKind,
+}
+
+impl Variant {
+    // What `typeof` gives for the variant, when it does not depend on the value.
+    pub fn type_of(&self) -> Option<&'static str> {
+        match &self.kind {
+            VariantKind::Null | VariantKind::Object { .. } => Some("object"),
+            VariantKind::Undefined => Some("undefined"),
+            VariantKind::Lit(Lit::Str(_)) => Some("string"),
+            VariantKind::Lit(Lit::Bool(_)) => Some("boolean"),
+            VariantKind::Lit(_) => Some("number"),
+            VariantKind::Type(Type::Path(ty)) => {
+                let segment = ty.path.segments.last()?;
+                match segment.ident.to_string().as_str() {
+                    "Number" | "f64" => Some("number"),
+                    "JsString" | "String" => Some("string"),
+                    "bool" => Some("boolean"),
+                    "Option" => None,
+                    _ => Some("object"),
+                }
+            }
+            VariantKind::Type(_) => Some("object"),
+        }
+    }
+
+    // Whether `x instanceof Class` holds for the variant.
+    pub fn is_instance_of(&self, class: &Ident) -> bool {
+        match &self.kind {
+            VariantKind::Type(Type::Path(ty)) => ty
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == *class),
+            _ => false,
+        }
+    }
+
+    pub fn has_field(&self, name: &str) -> bool {
+        match &self.kind {
+            VariantKind::Object { fields, .. } => fields.iter().any(|(ident, _)| ident == name),
+            _ => false,
+        }
+    }
+
+    // The pattern matching the variant. With `var`, it also binds what `var` is narrowed to.
+    pub fn pattern(&self, union: &Ident, var: Option<&Ident>) -> TokenStream {
+        let name = &self.ident;
+        match (&self.kind, var) {
+            (VariantKind::Type(_), Some(var)) => {
+                let binding = narrow::binding(var, None);
+                quote!(#union::#name(#binding))
+            }
+            (VariantKind::Type(_), None) => quote!(#union::#name(..)),
+            (VariantKind::Object { fields, .. }, Some(var)) => {
+                let idents = fields.iter().map(|(ident, _)| ident);
+                let bindings = fields
+                    .iter()
+                    .map(|(ident, _)| narrow::binding(var, Some(ident)));
+                quote!(#union::#name { #(#idents: #bindings),* })
+            }
+            (VariantKind::Object { .. }, None) => quote!(#union::#name { .. }),
+            _ => quote!(#union::#name),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Union {
+    pub ident: Ident,
+    // The discriminant of a discriminated union.
+    pub tag: Option<Ident>,
+    pub params: Vec<Ident>,
+    pub variants: Vec<Variant>,
+}
+
+impl Union {
+    // A member of a type parameter may be anything, so its `typeof` is only known at runtime.
+    pub fn type_of(&self, variant: &Variant) -> Option<&'static str> {
+        match &variant.kind {
+            VariantKind::Type(Type::Path(ty))
+                if ty
+                    .path
+                    .get_ident()
+                    .is_some_and(|ident| self.params.contains(ident)) =>
+            {
+                None
+            }
+            _ => variant.type_of(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct InterfaceFn {
+    pub ident: Ident,
+    pub inputs: Vec<Type>,
+    pub output: Option<Type>,
+    pub has_default: bool,
+}
+
+#[derive(Clone)]
+pub struct InterfaceType {
+    pub ident: Ident,
+    pub default: Option<Type>,
+}
+
+#[derive(Clone)]
+pub struct Interface {
+    pub ident: Ident,
+    pub params: Vec<(Ident, Option<Type>)>,
+    pub fns: Vec<InterfaceFn>,
+    pub types: Vec<InterfaceType>,
+}
+
+impl Interface {
+    pub fn new(item: &ItemInterface) -> Self {
+        let mut fns = Vec::new();
+        let mut types = Vec::new();
+
+        for item in &item.items {
+            match item {
+                InterfaceItem::Function(item) => fns.push(InterfaceFn {
+                    ident: item.ident.clone(),
+                    inputs: item.inputs.rust_types(),
+                    output: item.output.as_ref().map(|output| output.ty.clone()),
+                    has_default: item.default.is_some(),
+                }),
+                InterfaceItem::Type(item) => types.push(InterfaceType {
+                    ident: item.ident.clone(),
+                    default: item.default.as_ref().map(|(_, ty)| ty.clone()),
+                }),
+            }
+        }
+
+        let params = item
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                GenericsParam::Type(param) => Some((param.ident.clone(), param.default.clone())),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            ident: item.ident.clone(),
+            params,
+            fns,
+            types,
+        }
+    }
+
+    // Substitutes the generic arguments written in `path` (e.g. `Baz<Number>`) for the
+    // interface's type parameters.
+    pub fn instantiate(&self, path: &Path) -> Self {
+        let substitutions = substitutions(&self.params, path);
+
+        let mut interface = self.clone();
+        for f in &mut interface.fns {
+            for input in &mut f.inputs {
+                *input = substitute(input, &substitutions);
+            }
+            if let Some(output) = &mut f.output {
+                *output = substitute(output, &substitutions);
+            }
+        }
+        for t in &mut interface.types {
+            if let Some(default) = &mut t.default {
+                *default = substitute(default, &substitutions);
+            }
+        }
+        interface
+    }
+}
+
+// The generic arguments written in `path` for each of `params`, or their defaults.
+fn substitutions(params: &[(Ident, Option<Type>)], path: &Path) -> HashMap<String, TokenStream> {
+    let args: Vec<&Type> = match &path.segments.last().unwrap().arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    params
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (ident, default))| {
+            let ty = args.get(i).copied().or(default.as_ref())?;
+            Some((ident.to_string(), ty.to_token_stream()))
+        })
+        .collect()
+}
+
+fn substitute<T: ToTokens + Parse + Clone>(
+    item: &T,
+    substitutions: &HashMap<String, TokenStream>,
+) -> T {
+    fn walk(tokens: TokenStream, substitutions: &HashMap<String, TokenStream>) -> TokenStream {
+        tokens
+            .into_iter()
+            .flat_map(|token| match token {
+                TokenTree::Ident(ident) => match substitutions.get(&ident.to_string()) {
+                    Some(ty) => ty.clone(),
+                    None => TokenTree::Ident(ident).into(),
+                },
+                TokenTree::Group(group) => {
+                    let mut new =
+                        Group::new(group.delimiter(), walk(group.stream(), substitutions));
+                    new.set_span(group.span());
+                    TokenTree::Group(new).into()
+                }
+                token => token.into(),
+            })
+            .collect()
+    }
+
+    if substitutions.is_empty() {
+        return item.clone();
+    }
+    syn::parse2(walk(item.to_token_stream(), substitutions)).unwrap_or_else(|_| item.clone())
+}
+
+// Where errors come out of a function: from calling it, or, for an async function, from awaiting
+// what calling it gives.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ThrowsOn {
+    #[default]
+    Never,
+    Call,
+    Await,
+}
+
+// The methods a class lets its subclasses override, which make up the trait named after it that
+// the class and its subclasses implement. Its type parameters are kept to instantiate it with the
+// generic arguments a subclass extends it with.
+//
+// The names of its instance members tell which class a member accessed on an object of the class
+// is declared by, and the types of its members what class the object they give is of: the type of
+// a field, the value of a getter or what a method returns.
+#[derive(Clone)]
+pub struct Class {
+    pub ident: Ident,
+    pub params: Vec<(Ident, Option<Type>)>,
+    pub super_class: Option<Path>,
+    pub interfaces: Vec<Ident>,
+    pub methods: Vec<Signature>,
+    pub members: Vec<String>,
+    pub types: Vec<(String, Type)>,
+}
+
+impl Class {
+    pub fn new(item: &ItemClass) -> Self {
+        let params = item
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                GenericsParam::Type(param) => Some((param.ident.clone(), param.default.clone())),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            ident: item.ident.clone(),
+            params,
+            super_class: item.super_class().ok().flatten().cloned(),
+            interfaces: item
+                .implements
+                .iter()
+                .flat_map(|(_, paths)| paths)
+                .map(|path| path.segments.last().unwrap().ident.clone())
+                .collect(),
+            methods: item.overridable_methods(),
+            members: item.members(),
+            types: item.member_types(),
+        }
+    }
+
+    pub fn instantiate(&self, path: &Path) -> Self {
+        let substitutions = substitutions(&self.params, path);
+
+        let mut class = self.clone();
+        if let Some(super_class) = &mut class.super_class {
+            *super_class = substitute(super_class, &substitutions);
+        }
+        for method in &mut class.methods {
+            *method = substitute(method, &substitutions);
+        }
+        class
+    }
+}
+
+type Scopes<T> = RefCell<Vec<HashMap<String, T>>>;
+
+thread_local! {
+    static INTERFACES: Scopes<Interface> = const { RefCell::new(Vec::new()) };
+    static CLASSES: Scopes<Class> = const { RefCell::new(Vec::new()) };
+    static SIGNATURES: Scopes<Vec<ParamKind>> = const { RefCell::new(Vec::new()) };
+    static THROWING_FNS: Scopes<ThrowsOn> = const { RefCell::new(Vec::new()) };
+    static ACCESSORS: Scopes<Accessor> = const { RefCell::new(Vec::new()) };
+    static ENUMS: Scopes<Enum> = const { RefCell::new(Vec::new()) };
+    static UNIONS: Scopes<Union> = const { RefCell::new(Vec::new()) };
+    static VARIABLES: Scopes<Option<Type>> = const { RefCell::new(Vec::new()) };
+    static FIELD_PARAMS: Scopes<bool> = const { RefCell::new(Vec::new()) };
+    static THIS: Scopes<Option<Ident>> = const { RefCell::new(Vec::new()) };
+}
+
+struct ScopeGuard<T: 'static>(&'static LocalKey<Scopes<T>>);
+
+impl<T> Drop for ScopeGuard<T> {
+    fn drop(&mut self) {
+        self.0.with(|scopes| scopes.borrow_mut().pop());
+    }
+}
+
+// Makes `interfaces` visible to the classes lowered inside `f`, so that they can tell which of
+// their members belong to which implemented interface.
+pub fn with_interfaces<R>(
+    interfaces: impl IntoIterator<Item = Interface>,
+    f: impl FnOnce() -> R,
+) -> R {
+    let scope = interfaces
+        .into_iter()
+        .map(|interface| (interface.ident.to_string(), interface))
+        .collect();
+    INTERFACES.with(|scopes| scopes.borrow_mut().push(scope));
+
+    let _guard = ScopeGuard(&INTERFACES);
+    f()
+}
+
+pub fn interface(ident: &Ident) -> Option<Interface> {
+    INTERFACES.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.to_string()).cloned())
+    })
+}
+
+// Makes `classes` visible to the classes lowered inside `f`, so that a subclass can implement the
+// traits of the classes it extends.
+pub fn with_classes<R>(classes: impl IntoIterator<Item = Class>, f: impl FnOnce() -> R) -> R {
+    let scope = classes
+        .into_iter()
+        .map(|class| (class.ident.to_string(), class))
+        .collect();
+    CLASSES.with(|scopes| scopes.borrow_mut().push(scope));
+
+    let _guard = ScopeGuard(&CLASSES);
+    f()
+}
+
+pub fn class(ident: &Ident) -> Option<Class> {
+    CLASSES.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.to_string()).cloned())
+    })
+}
+
+// The class or interface declaring the instance member `name` of objects of type `ident`, which
+// may be the type itself, one of the interfaces it implements, or a class it extends. A type that
+// is not in scope, like an imported class, is taken to declare it.
+pub fn owner(ident: &Ident, name: &str) -> Option<Ident> {
+    let mut seen = Vec::new();
+    let mut next = ident.clone();
+    loop {
+        if seen.contains(&next) {
+            return None;
+        }
+        let Some(class) = class(&next) else {
+            return match interface(&next) {
+                Some(interface) => interface
+                    .fns
+                    .iter()
+                    .any(|f| f.ident == name)
+                    .then_some(next),
+                None => Some(next),
+            };
+        };
+        if class.members.iter().any(|member| member == name) {
+            return Some(next);
+        }
+        let declares = |ident: &&Ident| {
+            interface(ident).is_some_and(|interface| interface.fns.iter().any(|f| f.ident == name))
+        };
+        if let Some(ident) = class.interfaces.iter().find(declares) {
+            return Some(ident.clone());
+        }
+        seen.push(next);
+        next = class.super_class?.segments.last().unwrap().ident.clone();
+    }
+}
+
+// The type of the member `name` of the class or interface `ident`.
+pub fn member_type(ident: &Ident, name: &str) -> Option<Type> {
+    if let Some(class) = class(ident) {
+        let (_, ty) = class.types.into_iter().find(|(member, _)| member == name)?;
+        return Some(ty);
+    }
+    interface(ident)?
+        .fns
+        .into_iter()
+        .find(|f| f.ident == name)?
+        .output
+}
+
+// How a member is known to the scope, after the class or interface declaring it for objects of
+// type `ident`.
+pub fn member_key(ident: &Ident, name: &str) -> Option<String> {
+    Some(format!("{}.{}", owner(ident, name)?, name))
+}
+
+// The class or other type named by a type annotation, looking through `Option`.
+pub fn type_class(ty: &Type) -> Option<Ident> {
+    let Type::Path(ty) = ty else {
+        return None;
+    };
+    let [segment] = ty.path.segments.iter().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == "Option" => {
+            match args.args.first()? {
+                GenericArgument::Type(ty) => type_class(ty),
+                _ => None,
+            }
+        }
+        _ => Some(segment.ident.clone()),
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParamKind {
+    Required,
+    Optional,
+    Rest,
+}
+
+// Makes the parameters of the functions and methods taking optional or rest parameters visible to
+// the calls lowered inside `f`, so that missing arguments can be padded and the remaining ones
+// collected. Methods are known as `Class.name` after the class or interface declaring them, and a
+// constructor as `Class.constructor`.
+pub fn with_signatures<R>(
+    signatures: impl IntoIterator<Item = (String, Vec<ParamKind>)>,
+    f: impl FnOnce() -> R,
+) -> R {
+    let scope = signatures.into_iter().collect();
+    SIGNATURES.with(|scopes| scopes.borrow_mut().push(scope));
+
+    let _guard = ScopeGuard(&SIGNATURES);
+    f()
+}
+
+pub fn signature(name: &str) -> Option<Vec<ParamKind>> {
+    SIGNATURES.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+    })
+}
+
+// Makes the functions declared in a scope visible to the calls lowered inside `f`, along with
+// whether they return a `Result`, so that errors they return are thrown on. Methods are known as
+// `Class.name` after the class or interface declaring them, and constructors as
+// `Class.constructor`.
+pub fn with_throwing_fns<R>(
+    fns: impl IntoIterator<Item = (String, ThrowsOn)>,
+    f: impl FnOnce() -> R,
+) -> R {
+    let scope = fns.into_iter().collect();
+    THROWING_FNS.with(|scopes| scopes.borrow_mut().push(scope));
+
+    let _guard = ScopeGuard(&THROWING_FNS);
+    f()
+}
+
+pub fn throws_on(name: &str) -> ThrowsOn {
+    THROWING_FNS.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+            .unwrap_or_default()
+    })
+}
+
+pub fn is_throwing_fn(name: &str) -> bool {
+    throws_on(name) == ThrowsOn::Call
+}
+
+// Makes the accessors of the classes in scope visible to the member accesses lowered inside `f`.
+// A getter and a setter of the same member are declared separately, so they are merged.
+pub fn with_accessors<R>(
+    accessors: impl IntoIterator<Item = (String, Accessor)>,
+    f: impl FnOnce() -> R,
+) -> R {
+    let mut scope: HashMap<String, Accessor> = HashMap::new();
+    for (name, accessor) in accessors {
+        let entry = scope.entry(name).or_default();
+        entry.getter |= accessor.getter;
+        entry.setter |= accessor.setter;
+        entry.is_static |= accessor.is_static;
+    }
+    ACCESSORS.with(|scopes| scopes.borrow_mut().push(scope));
+
+    let _guard = ScopeGuard(&ACCESSORS);
+    f()
+}
+
+pub fn accessor(name: &str) -> Option<Accessor> {
+    ACCESSORS.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    })
+}
+
+// Makes the enums in scope visible to the expressions lowered inside `f`, so that member accesses
+// and reverse mappings can be rewritten, and members of `const` enums inlined.
+pub fn with_enums<R>(enums: impl IntoIterator<Item = (Ident, Enum)>, f: impl FnOnce() -> R) -> R {
+    let scope = enums
+        .into_iter()
+        .map(|(ident, item)| (ident.to_string(), item))
+        .collect();
+    ENUMS.with(|scopes| scopes.borrow_mut().push(scope));
+
+    let _guard = ScopeGuard(&ENUMS);
+    f()
+}
+
+pub fn enumeration(ident: &Ident) -> Option<Enum> {
+    ENUMS.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.to_string()).cloned())
+    })
+}
+
+// Makes the union types in scope visible to the code lowered inside `f`, so that variables of
+// these types can be narrowed.
+pub fn with_unions<R>(unions: impl IntoIterator<Item = Union>, f: impl FnOnce() -> R) -> R {
+    let scope = unions
+        .into_iter()
+        .map(|union| (union.ident.to_string(), union))
+        .collect();
+    UNIONS.with(|scopes| scopes.borrow_mut().push(scope));
+
+    let _guard = ScopeGuard(&UNIONS);
+    f()
+}
+
+pub fn union(ident: &Ident) -> Option<Union> {
+    UNIONS.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.to_string()).cloned())
+    })
+}
+
+// The union a type annotation names, ignoring its generic arguments.
+pub fn union_of(ty: &Type) -> Option<Union> {
+    let Type::Path(ty) = ty else {
+        return None;
+    };
+    match ty.path.segments.iter().collect::<Vec<_>>().as_slice() {
+        [segment] => union(&segment.ident),
+        _ => None,
+    }
+}
+
+// Makes the variables declared in a scope visible to the code lowered inside `f`, along with their
+// type when it is known, so that variables of union types can be narrowed and members of objects
+// found in the class declaring them. Variables of unknown type hide those of outer scopes.
+pub fn with_variables<R>(
+    variables: impl IntoIterator<Item = (Ident, Option<Type>)>,
+    f: impl FnOnce() -> R,
+) -> R {
+    let scope = variables
+        .into_iter()
+        .map(|(ident, ty)| (ident.to_string(), ty))
+        .collect();
+    VARIABLES.with(|scopes| scopes.borrow_mut().push(scope));
+
+    let _guard = ScopeGuard(&VARIABLES);
+    f()
+}
+
+pub fn variable_type(ident: &Ident) -> Option<Type> {
+    VARIABLES.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.to_string()).cloned())
+    })?
+}
+
+pub fn variable_union(ident: &Ident) -> Option<Union> {
+    union_of(&variable_type(ident)?)
+}
+
+// Parameter properties are moved into their fields, so the constructor reads and assigns the
+// parameters there. Locals of the same name declared in the body hide them again.
+pub fn with_field_params<R>(
+    params: impl IntoIterator<Item = (Ident, bool)>,
+    f: impl FnOnce() -> R,
+) -> R {
+    let scope = params
+        .into_iter()
+        .map(|(ident, is_field)| (ident.to_string(), is_field))
+        .collect();
+    FIELD_PARAMS.with(|scopes| scopes.borrow_mut().push(scope));
+
+    let _guard = ScopeGuard(&FIELD_PARAMS);
+    f()
+}
+
+pub fn is_field_param(ident: &Ident) -> bool {
+    FIELD_PARAMS.with(|scopes| {
+        scopes
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&ident.to_string()).copied())
+            .unwrap_or(false)
+    })
+}
+
+// The methods and accessors of an object literal take the object as an argument, which `this`
+// lowered inside `f` names instead of `self`, or `self` again for `None`.
+pub fn with_this<R>(ident: Option<Ident>, f: impl FnOnce() -> R) -> R {
+    THIS.with(|scopes| {
+        let scope = HashMap::from([("this".to_string(), ident)]);
+        scopes.borrow_mut().push(scope)
+    });
+
+    let _guard = ScopeGuard(&THIS);
+    f()
+}
+
+pub fn this() -> Option<Ident> {
+    THIS.with(|scopes| {
+        scopes
+            .borrow()
+            .last()
+            .and_then(|scope| scope.get("this").cloned())
+    })?
+}
@@ -0,0 +1,625 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use syn::{parse_quote, Ident};
+
+use crate::{
+    encode::scope::{self, ThrowsOn},
+    parser::{
+        expr::{
+            object::{ObjectField, ObjectStmt},
+            ExprArrowFunction, ExprFunction,
+        },
+        item::{
+            class::{ClassStmtValue, ItemClass},
+            interface::{InterfaceItem, ItemInterface},
+            FnArgs, ItemFunction, Local,
+        },
+        pat::Pattern,
+        stmt::{Block, ForArgs, Stmt, StmtValue},
+        Expr, Item,
+    },
+};
+
+// Whether errors can escape a function, found by looking for `throw` statements and calls that
+// are not caught in its body, without lowering it. A function that calls others declared further
+// out throws if one of them does, which is only known once their scope is lowered. Calls name
+// functions as the scope knows them, and methods after the class declaring them.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Throws {
+    always: bool,
+    calls: BTreeSet<String>,
+    // The async functions awaited, which throw what their body does.
+    awaits: BTreeSet<String>,
+}
+
+impl Throws {
+    fn merge(&mut self, other: &Throws) {
+        self.always |= other.always;
+        self.calls.extend(other.calls.iter().cloned());
+        self.awaits.extend(other.awaits.iter().cloned());
+    }
+}
+
+// A function, method or local closure, whose errors come out of calling it, or of awaiting what
+// calling it gives when it is async.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Callee {
+    throws: Throws,
+    is_async: bool,
+}
+
+impl Callee {
+    fn new(throws: Throws) -> Self {
+        Self {
+            throws,
+            is_async: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Walk {
+    // The functions and methods declared in the enclosing blocks, innermost last.
+    scopes: Vec<HashMap<String, Callee>>,
+    // The class whose constructor `super(...)` calls.
+    super_class: Option<String>,
+    throws: Throws,
+}
+
+impl Walk {
+    // Finds what the functions and methods among `items` throw, with calls between them followed.
+    // A method has the signature of the methods it overrides or implements, so they throw if any
+    // of them does.
+    fn items(&mut self, items: &[&Item]) -> HashMap<String, Callee> {
+        let mut callees: HashMap<String, Callee> = HashMap::new();
+        for item in items {
+            let found = match item {
+                Item::Function(item) => vec![(item.ident.to_string(), self.function(item))],
+                Item::Class(item) => self.class(item),
+                Item::Interface(item) => self.interface(item),
+                Item::Local(Local {
+                    pat: Pattern::Ident(ident),
+                    init: Some(init),
+                    ..
+                }) => self
+                    .closure(&init.expr)
+                    .map(|callee| (ident.to_string(), callee))
+                    .into_iter()
+                    .collect(),
+                _ => Vec::new(),
+            };
+            for (name, callee) in found {
+                let entry = callees.entry(name).or_default();
+                entry.throws.merge(&callee.throws);
+                entry.is_async |= callee.is_async;
+            }
+        }
+
+        for item in items {
+            let Item::Class(item) = item else {
+                continue;
+            };
+            for (method, overridden) in overrides(item) {
+                if let Some(callee) = callees.get_mut(&overridden) {
+                    callee.throws.calls.insert(method.clone());
+                }
+                let callee = callees.entry(method).or_default();
+                callee.throws.calls.insert(overridden);
+            }
+        }
+
+        loop {
+            let next: HashMap<_, _> = callees
+                .iter()
+                .map(|(name, callee)| {
+                    let mut next = callee.clone();
+                    let calls = callee.throws.calls.iter().map(|call| (call, false));
+                    let awaits = callee.throws.awaits.iter().map(|call| (call, true));
+                    for (call, awaited) in calls.chain(awaits) {
+                        match callees.get(call) {
+                            Some(callee) if callee.is_async == awaited => {
+                                next.throws.merge(&callee.throws)
+                            }
+                            _ => {}
+                        }
+                    }
+                    (name.clone(), next)
+                })
+                .collect();
+            if next == callees {
+                break;
+            }
+            callees = next;
+        }
+
+        let declared: Vec<_> = callees.keys().cloned().collect();
+        for callee in callees.values_mut() {
+            for name in &declared {
+                callee.throws.calls.remove(name);
+                callee.throws.awaits.remove(name);
+            }
+        }
+        callees
+    }
+
+    // Methods are known as `Class.name`, and the constructor as `Class.constructor`, which is what
+    // `new` calls. Accessors and generators never throw.
+    fn class(&mut self, item: &ItemClass) -> Vec<(String, Callee)> {
+        let class = &item.ident;
+        let outer = std::mem::replace(
+            &mut self.super_class,
+            item.super_class()
+                .ok()
+                .flatten()
+                .map(|path| path.segments.last().unwrap().ident.to_string()),
+        );
+
+        let this = (Ident::new("this", class.span()), Some(parse_quote!(#class)));
+        let mut found = Vec::new();
+        scope::with_variables([this], || {
+            for stmt in &item.stmts {
+                let ClassStmtValue::Method(method) = &stmt.stmt else {
+                    continue;
+                };
+                let throws = match method.method.star_token {
+                    Some(_) => Throws::default(),
+                    None => self.body(&method.method.inputs, &method.method.body),
+                };
+                let name = format!("{}.{}", class, method.method.ident.ident);
+                found.push((name, Callee::new(throws)));
+            }
+        });
+        // The implicit constructor of a subclass runs the one of its base class.
+        if let (None, Some(super_class)) = (item.constructor(), &self.super_class) {
+            let mut throws = Throws::default();
+            throws.calls.insert(format!("{}.constructor", super_class));
+            found.push((format!("{}.constructor", class), Callee::new(throws)));
+        }
+
+        self.super_class = outer;
+        found
+    }
+
+    // Functions of an interface are known as `Interface.name`, whether or not they have a default.
+    fn interface(&mut self, item: &ItemInterface) -> Vec<(String, Callee)> {
+        let ident = &item.ident;
+        let this = (Ident::new("this", ident.span()), Some(parse_quote!(#ident)));
+        scope::with_variables([this], || {
+            item.items
+                .iter()
+                .filter_map(|item| match item {
+                    InterfaceItem::Function(f) => {
+                        let throws = match &f.default {
+                            Some(body) => self.body(&f.inputs, body),
+                            None => Throws::default(),
+                        };
+                        Some((format!("{}.{}", ident, f.ident), Callee::new(throws)))
+                    }
+                    InterfaceItem::Type(_) => None,
+                })
+                .collect()
+        })
+    }
+
+    // Errors cannot be thrown out of generators, which report them where they are thrown.
+    fn function(&mut self, item: &ItemFunction) -> Callee {
+        if item.star_token.is_some() {
+            return Callee::default();
+        }
+        Callee {
+            throws: self.body(&item.inputs, &item.body),
+            is_async: item.async_token.is_some(),
+        }
+    }
+
+    // Function expressions and arrow functions, which are called like the local they are bound to.
+    fn closure(&mut self, expr: &Expr) -> Option<Callee> {
+        match expr {
+            Expr::Function(expr) => Some(self.function_expr(expr)),
+            Expr::ArrowFunction(expr) => Some(Callee::new(self.arrow_function(expr))),
+            Expr::Paren(expr) => self.closure(&expr.expr),
+            _ => None,
+        }
+    }
+
+    fn function_expr(&mut self, expr: &ExprFunction) -> Callee {
+        if expr.star_token.is_some() {
+            return Callee::default();
+        }
+        Callee {
+            throws: self.body(&expr.inputs, &expr.body),
+            is_async: expr.async_token.is_some(),
+        }
+    }
+
+    fn arrow_function(&mut self, expr: &ExprArrowFunction) -> Throws {
+        let outer = std::mem::take(&mut self.throws);
+        for pat in &expr.inputs.inputs {
+            self.pattern(pat);
+        }
+        scope::with_variables(expr.inputs.variables(), || self.stmt(&expr.body.stmt));
+        std::mem::replace(&mut self.throws, outer)
+    }
+
+    fn body(&mut self, inputs: &FnArgs, body: &Block) -> Throws {
+        let outer = std::mem::take(&mut self.throws);
+        for param in &inputs.params {
+            if let Some((_, default)) = &param.default {
+                self.expr(default);
+            }
+            self.pattern(&param.pat);
+        }
+        scope::with_variables(inputs.variables(), || self.block(body));
+        std::mem::replace(&mut self.throws, outer)
+    }
+
+    // What `f` finds is caught, so it is left out.
+    fn caught(&mut self, f: impl FnOnce(&mut Self)) {
+        let outer = std::mem::take(&mut self.throws);
+        f(self);
+        self.throws = outer;
+    }
+
+    // Calling an async function only gives what awaiting it throws.
+    fn call(&mut self, name: String, awaited: bool) {
+        match self.scopes.iter().rev().find_map(|scope| scope.get(&name)) {
+            Some(callee) if callee.is_async == awaited => {
+                let throws = callee.throws.clone();
+                self.throws.merge(&throws);
+            }
+            Some(_) => {}
+            None if awaited => {
+                self.throws.awaits.insert(name);
+            }
+            None => {
+                self.throws.calls.insert(name);
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        self.stmts(&block.stmts);
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) {
+        let items: Vec<_> = stmts
+            .iter()
+            .filter_map(|stmt| match &stmt.stmt {
+                StmtValue::Item(item) => Some(item),
+                _ => None,
+            })
+            .collect();
+        with_items(&items, || {
+            let fns = self.items(&items);
+            self.scopes.push(fns);
+            for stmt in stmts {
+                self.stmt(&stmt.stmt);
+            }
+            self.scopes.pop();
+        });
+    }
+
+    fn stmt(&mut self, stmt: &StmtValue) {
+        match stmt {
+            StmtValue::Block(block) => self.block(block),
+            StmtValue::If(stmt) => {
+                self.expr(&stmt.cond.expr);
+                self.stmt(&stmt.then_branch.stmt);
+                if let Some((_, else_branch)) = &stmt.else_branch {
+                    self.stmt(&else_branch.stmt);
+                }
+            }
+            StmtValue::Switch(stmt) => {
+                self.expr(&stmt.expr);
+                for arm in &stmt.arms {
+                    self.stmts(&arm.stmts);
+                }
+            }
+            StmtValue::For(stmt) => {
+                match &stmt.header {
+                    ForArgs::In(header) => {
+                        self.pattern(&header.pat);
+                        self.expr(&header.iterable);
+                    }
+                    ForArgs::Header(header) => {
+                        if let Some(init) = &header.init {
+                            self.stmt(init);
+                        }
+                        if let Some(cond) = &header.cond {
+                            self.expr(cond);
+                        }
+                        if let Some(after) = &header.after {
+                            self.stmt(after);
+                        }
+                    }
+                }
+                self.stmt(&stmt.body.stmt);
+                if let Some((_, else_block)) = &stmt.else_block {
+                    self.stmt(&else_block.stmt);
+                }
+            }
+            StmtValue::While(stmt) => {
+                self.expr(&stmt.cond.expr);
+                self.stmt(&stmt.body.stmt);
+                if let Some((_, else_block)) = &stmt.else_block {
+                    self.stmt(&else_block.stmt);
+                }
+            }
+            StmtValue::DoWhile(stmt) => {
+                self.stmt(&stmt.body.stmt);
+                self.expr(&stmt.cond.expr);
+                if let Some((_, else_block)) = &stmt.else_block {
+                    self.stmt(&else_block.stmt);
+                }
+            }
+            StmtValue::Break(_) | StmtValue::Continue(_) => {}
+            StmtValue::Return(stmt) => {
+                if let Some(expr) = &stmt.expr {
+                    self.expr(expr);
+                }
+            }
+            StmtValue::Throw(stmt) => {
+                self.expr(&stmt.expr);
+                self.throws.always = true;
+            }
+            // Without a `catch`, errors thrown in the `try` block are thrown on after `finally`.
+            StmtValue::Try(stmt) => {
+                match &stmt.catch {
+                    Some(catch) => {
+                        self.caught(|walk| walk.block(&stmt.block));
+                        if let Some((_, pat)) = &catch.param {
+                            self.pattern(pat);
+                        }
+                        self.block(&catch.block);
+                    }
+                    None => self.block(&stmt.block),
+                }
+                if let Some((_, block)) = &stmt.finally {
+                    self.block(block);
+                }
+            }
+            StmtValue::Labeled(stmt) => self.stmt(&stmt.body.stmt),
+            StmtValue::Item(Item::Local(local)) => {
+                self.pattern(&local.pat);
+                if let Some(init) = &local.init {
+                    self.expr(&init.expr);
+                }
+            }
+            StmtValue::Item(_) => {}
+            StmtValue::Expr(expr) => self.expr(expr),
+        }
+    }
+
+    fn pattern(&mut self, pat: &Pattern) {
+        match pat {
+            Pattern::Ident(_) => {}
+            Pattern::Object(pat) => {
+                for prop in &pat.props {
+                    if let Some((_, value)) = &prop.value {
+                        self.pattern(value);
+                    }
+                    if let Some(default) = &prop.default {
+                        self.expr(&default.expr);
+                    }
+                }
+                if let Some(rest) = &pat.rest {
+                    self.pattern(&rest.pat);
+                }
+            }
+            Pattern::Array(pat) => {
+                for elem in pat.elems.iter().flatten() {
+                    self.pattern(&elem.pat);
+                    if let Some(default) = &elem.default {
+                        self.expr(&default.expr);
+                    }
+                }
+                if let Some(rest) = &pat.rest {
+                    self.pattern(&rest.pat);
+                }
+            }
+        }
+    }
+
+    // Functions and closures nested in an expression throw to their own caller.
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Assign(expr) => {
+                self.expr(&expr.left);
+                self.expr(&expr.right);
+            }
+            Expr::AssignOp(expr) => {
+                self.expr(&expr.left);
+                self.expr(&expr.right);
+            }
+            Expr::Binary(expr) => {
+                self.expr(&expr.left);
+                self.expr(&expr.right);
+            }
+            Expr::Array(expr) => expr.elems.iter().for_each(|elem| self.expr(elem)),
+            Expr::ArrayExpand(expr) => self.expr(&expr.expr),
+            Expr::Await(expr) => {
+                self.expr(&expr.base);
+                if let Expr::Call(call) = &*expr.base {
+                    if let (None, Some(name)) = (expr.base.optional_link(), call.name()) {
+                        self.call(name, true);
+                    }
+                }
+            }
+            Expr::Unary(expr) => self.expr(&expr.expr),
+            Expr::Update(expr) => self.expr(&expr.expr),
+            Expr::Field(expr) => self.expr(&expr.base),
+            Expr::Index(expr) => {
+                self.expr(&expr.base);
+                self.expr(&expr.index);
+            }
+            Expr::Template(expr) => expr.exprs.iter().for_each(|expr| self.expr(expr)),
+            Expr::TaggedTemplate(expr) => {
+                self.expr(&expr.tag);
+                expr.template.exprs.iter().for_each(|expr| self.expr(expr));
+            }
+            // Calls reached through `?.` cannot throw on, and methods are only known when the class
+            // of the receiver is.
+            Expr::MethodCall(call) => {
+                self.expr(&call.receiver);
+                call.args.iter().for_each(|arg| self.expr(arg));
+                if expr.optional_link().is_some() {
+                    return;
+                }
+                let method = call.method.to_string();
+                if let Some(key) = call
+                    .receiver
+                    .class()
+                    .and_then(|class| scope::member_key(&class, &method))
+                {
+                    self.call(key, false);
+                }
+            }
+            Expr::Call(call) => {
+                self.expr(&call.func);
+                call.args.iter().for_each(|arg| self.expr(arg));
+                if expr.optional_link().is_some() {
+                    return;
+                }
+                if let Some(name) = call.name() {
+                    self.call(name, false);
+                } else if let (Expr::Super(_), Some(super_class)) =
+                    (&*call.func, self.super_class.clone())
+                {
+                    self.call(format!("{}.constructor", super_class), false);
+                }
+            }
+            Expr::New(expr) => {
+                if let Some((_, args)) = &expr.args {
+                    args.iter().for_each(|arg| self.expr(arg));
+                }
+                if let Some(class) = expr.path.get_ident() {
+                    self.call(format!("{}.constructor", class), false);
+                }
+            }
+            Expr::If(expr) => {
+                self.expr(&expr.cond);
+                self.expr(&expr.then_branch);
+                self.expr(&expr.else_branch);
+            }
+            // The methods of an object literal cannot throw.
+            Expr::Object(expr) => {
+                for stmt in &expr.fields {
+                    match stmt {
+                        ObjectStmt::Spread(spread) => self.expr(&spread.expr),
+                        ObjectStmt::Field(ObjectField {
+                            init: Some((_, init)),
+                            ..
+                        }) => self.expr(init),
+                        _ => {}
+                    }
+                }
+            }
+            Expr::Paren(expr) => self.expr(&expr.expr),
+            Expr::Yield(expr) => self.expr(&expr.expr),
+            Expr::Infer(_)
+            | Expr::This(_)
+            | Expr::Super(_)
+            | Expr::Lit(_)
+            | Expr::Path(_)
+            | Expr::Function(_)
+            | Expr::ArrowFunction(_)
+            | Expr::Rust(_) => {}
+        }
+    }
+}
+
+// The methods that `item` declares and that have the signature of another one, along with it: the
+// method of a base class they override, or the function of an interface they implement.
+fn overrides(item: &ItemClass) -> Vec<(String, String)> {
+    let super_class = item.super_class().ok().flatten();
+    let mut found = Vec::new();
+    for stmt in &item.stmts {
+        let ClassStmtValue::Method(method) = &stmt.stmt else {
+            continue;
+        };
+        if method.static_token.is_some() || method.is_constructor() {
+            continue;
+        }
+        let name = method.method.ident.ident.to_string();
+        let key = format!("{}.{}", item.ident, name);
+        if let Some(super_class) = super_class {
+            let super_class = &super_class.segments.last().unwrap().ident;
+            if let Some(overridden) = scope::member_key(super_class, &name) {
+                found.push((key.clone(), overridden));
+            }
+        }
+        for path in item.implements.iter().flat_map(|(_, paths)| paths) {
+            let interface = &path.segments.last().unwrap().ident;
+            if scope::interface(interface).is_some_and(|i| i.fns.iter().any(|f| f.ident == name)) {
+                found.push((key.clone(), format!("{}.{}", interface, name)));
+            }
+        }
+    }
+    found
+}
+
+// Makes the interfaces, classes and variables declared among `items` visible to `f`, like a block
+// does for the code lowered in it, so that methods are called on the same classes.
+fn with_items<R>(items: &[&Item], f: impl FnOnce() -> R) -> R {
+    let interfaces = items.iter().filter_map(|item| match item {
+        Item::Interface(item) => Some(scope::Interface::new(item)),
+        _ => None,
+    });
+    scope::with_interfaces(interfaces, || {
+        scope::with_classes(items.iter().filter_map(|item| item.class()), || {
+            scope::with_variables(Item::variables(items.iter().copied()), f)
+        })
+    })
+}
+
+// Whether what `throws` finds makes errors escape, with the functions declared further out already
+// known to the scope.
+fn is_throwing(throws: &Throws) -> bool {
+    throws.always
+        || throws.calls.iter().any(|call| scope::is_throwing_fn(call))
+        || (throws.awaits.iter()).any(|call| scope::throws_on(call) == ThrowsOn::Await)
+}
+
+// Whether errors escape a function expression, which then returns a `Result`.
+pub fn function_expr_throws(expr: &ExprFunction) -> bool {
+    is_throwing(&Walk::default().function_expr(expr).throws)
+}
+
+pub fn arrow_function_throws(expr: &ExprArrowFunction) -> bool {
+    is_throwing(&Walk::default().arrow_function(expr))
+}
+
+// Which of the functions and methods declared in a scope return a `Result`, which calling makes
+// the caller throw too, or awaiting for async functions.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Throwing {
+    pub fns: BTreeMap<String, ThrowsOn>,
+}
+
+impl Throwing {
+    // Finds them among `items`, with those declared further out already known to the scope.
+    pub fn of<'a>(items: impl IntoIterator<Item = &'a Item>) -> Self {
+        let items: Vec<_> = items.into_iter().collect();
+        let found = with_items(&items, || Walk::default().items(&items));
+        let fns = found
+            .into_iter()
+            .map(|(name, callee)| {
+                let throws_on = match (is_throwing(&callee.throws), callee.is_async) {
+                    (false, _) => ThrowsOn::Never,
+                    (true, false) => ThrowsOn::Call,
+                    (true, true) => ThrowsOn::Await,
+                };
+                (name, throws_on)
+            })
+            .collect();
+        Self { fns }
+    }
+
+    pub fn merge(&mut self, other: Throwing) {
+        self.fns.extend(other.fns);
+    }
+
+    pub fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        scope::with_throwing_fns(self.fns, f)
+    }
+}
@@ -1,3 +1,11 @@
+#![allow(clippy::large_enum_variant)]
+
+pub use build::Builder;
+
 pub mod parser;
 
 pub mod encode;
+
+pub mod diagnostic;
+
+pub mod build;
@@ -1,8 +1,11 @@
 pub use expr::Expr;
+pub use file::File;
 pub use item::Item;
 
 pub mod expr;
 
+pub mod file;
+
 pub mod item;
 
 pub mod token;
@@ -13,4 +16,8 @@ pub mod stmt;
 
 pub mod generics;
 
+pub mod pat;
+
+pub mod ty;
+
 pub mod parsing;
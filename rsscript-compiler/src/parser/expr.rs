@@ -1,72 +1,500 @@
+use proc_macro2::{Literal, Spacing, Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
     bracketed, parenthesized,
     parse::Parse,
     punctuated::Punctuated,
+    spanned::Spanned,
     token::{Bracket, Paren},
-    AngleBracketedGenericArguments, BinOp, Ident, Member, Pat,
+    AngleBracketedGenericArguments, Ident, Lit, Member, Type, TypeInfer,
 };
 
 pub mod object;
 
+pub mod precedence;
+
+pub mod template;
+
 use crate::{
-    enum_impl,
+    encode::{
+        self,
+        context::{self, ThrowTarget},
+        narrow::{self, Narrowing},
+        scope::{self, EnumValue, ParamKind, ThrowsOn},
+        throws, ToRustCode,
+    },
     parser::{
+        expr::{
+            object::ExprObject,
+            precedence::Precedence,
+            template::{ExprTaggedTemplate, ExprTemplate},
+        },
         generics::Generics,
-        item::{FnArgs, TypeAnnotation},
-        stmt::{Block, Stmt},
+        item::{self, enumeration, FnArgs, TypeAnnotation},
+        pat::Pattern,
+        stmt::{Block, Stmt, StmtValue},
+        token::IdentPeeker,
     },
     Token,
 };
 
-enum_impl!(
-    pub enum Expr {
-        Assign(ExprAssign),
-        Array(ExprArray),
-        Await(ExprAwait),
-        Binary(ExprBinary),
-        Field(ExprField),
-        Infer(ExprInfer),
-        Lit(ExprLit),
-        MethodCall(ExprMethodCall),
-        Function(ExprFunction),
-        ArrowFunction(ExprArrowFunction),
-        Call(ExprCall),
-        If(ExprIf),
-        Rust(RustCode),
-        Paren(ExprParen),
-    }
-);
+#[derive(Debug)]
+pub enum Expr {
+    Assign(ExprAssign),
+    AssignOp(ExprAssignOp),
+    Array(ExprArray),
+    ArrayExpand(ExprArrayExpand),
+    Await(ExprAwait),
+    Binary(ExprBinary),
+    Unary(ExprUnary),
+    Update(ExprUpdate),
+    Field(ExprField),
+    Index(ExprIndex),
+    Infer(ExprInfer),
+    This(ExprThis),
+    Super(ExprSuper),
+    Lit(ExprLit),
+    Template(ExprTemplate),
+    TaggedTemplate(ExprTaggedTemplate),
+    Path(ExprPath),
+    MethodCall(ExprMethodCall),
+    Function(ExprFunction),
+    ArrowFunction(ExprArrowFunction),
+    Call(ExprCall),
+    Object(ExprObject),
+    New(ExprNew),
+    If(ExprIf),
+    Rust(RustCode),
+    Paren(ExprParen),
+    Yield(ExprYield),
+}
+
+impl Parse for Expr {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        precedence::parse_expr(input, Precedence::Assign)
+    }
+}
+
+impl Expr {
+    fn is_compound(&self) -> bool {
+        matches!(
+            self,
+            Self::Assign(_)
+                | Self::AssignOp(_)
+                | Self::Binary(_)
+                | Self::Update(_)
+                | Self::Function(_)
+                | Self::ArrowFunction(_)
+                | Self::If(_)
+                | Self::Rust(_)
+                | Self::Yield(_)
+        )
+    }
 
+    // The class, or other type, of the object this expression gives when it is known: from the type
+    // of `this` or of a variable, from `new` or an object literal, or from the type a member is
+    // declared with. What functions return is not known.
+    pub(crate) fn class(&self) -> Option<Ident> {
+        let this = |span| scope::type_class(&scope::variable_type(&Ident::new("this", span))?);
+        match self {
+            Self::This(expr) => this(expr.this_token.span),
+            Self::Super(expr) => {
+                let class = scope::class(&this(expr.super_token.span)?)?;
+                Some(class.super_class?.segments.last()?.ident.clone())
+            }
+            Self::Path(ExprPath { path }) => {
+                scope::type_class(&scope::variable_type(path.get_ident()?)?)
+            }
+            Self::New(expr) => Some(expr.path.segments.last()?.ident.clone()),
+            Self::Object(expr) => expr.class(),
+            Self::Paren(expr) => expr.expr.class(),
+            Self::Field(ExprField {
+                base,
+                member: Member::Named(name),
+                ..
+            }) => {
+                let name = name.to_string();
+                let owner = scope::owner(&base.class()?, &name)?;
+                scope::type_class(&scope::member_type(&owner, &name)?)
+            }
+            Self::MethodCall(expr) => {
+                let name = expr.method.to_string();
+                let owner = scope::owner(&expr.receiver.class()?, &name)?;
+                scope::type_class(&scope::member_type(&owner, &name)?)
+            }
+            // `Class::name(...)` calls a static method.
+            Self::Call(ExprCall { func, .. }) => {
+                let Self::Path(ExprPath { path }) = &**func else {
+                    return None;
+                };
+                let [class, name] = path.segments.iter().collect::<Vec<_>>()[..] else {
+                    return None;
+                };
+                let ty = scope::member_type(&class.ident, &name.ident.to_string())?;
+                scope::type_class(&ty)
+            }
+            _ => None,
+        }
+    }
+
+    // The enum in scope this expression names, if it is a plain identifier.
+    fn enumeration(&self) -> Option<(&Ident, scope::Enum)> {
+        let Self::Path(ExprPath { path }) = self else {
+            return None;
+        };
+        let ident = path.get_ident()?;
+        Some((ident, scope::enumeration(ident)?))
+    }
+
+    // Lowers an operand that is borrowed or assigned to rather than read.
+    pub(crate) fn to_place_code(&self, tokens: &mut TokenStream) {
+        let read = match self {
+            Self::Path(ExprPath { path }) => path.get_ident().and_then(|var| {
+                narrow::read(var, None, true).or_else(|| ExprPath::field_param_code(var))
+            }),
+            Self::Field(ExprField {
+                base,
+                optional: None,
+                member,
+                ..
+            }) => match &**base {
+                Self::Path(ExprPath { path }) => path
+                    .get_ident()
+                    .and_then(|var| narrow::read(var, Some(member), true)),
+                _ => None,
+            },
+            Self::Index(expr) if expr.optional.is_none() && expr.base.enumeration().is_none() => {
+                let mut base = TokenStream::new();
+                expr.base.to_place_code(&mut base);
+                Some(expr.to_link_code(base))
+            }
+            _ => None,
+        };
+
+        match read {
+            Some(read) => tokens.extend(read),
+            None => self.to_operand_code(tokens),
+        }
+    }
+
+    // The base of a member access, call or index, and whether it is reached through `?.`.
+    fn link(&self) -> Option<(&Expr, bool)> {
+        match self {
+            Self::Field(expr) => Some((&expr.base, expr.optional.is_some())),
+            Self::Index(expr) => Some((&expr.base, expr.optional.is_some())),
+            Self::Call(expr) => Some((&expr.func, expr.optional.is_some())),
+            Self::MethodCall(expr) => Some((&expr.receiver, expr.optional.is_some())),
+            _ => None,
+        }
+    }
+
+    // The outermost link reached through `?.` in the chain this expression ends.
+    pub(crate) fn optional_link(&self) -> Option<&Expr> {
+        let mut expr = self;
+        while let Some((base, optional)) = expr.link() {
+            if optional {
+                return Some(expr);
+            }
+            expr = base;
+        }
+        None
+    }
+
+    fn to_link_code(&self, base: TokenStream) -> TokenStream {
+        match self {
+            Self::Field(expr) => expr.to_link_code(base),
+            Self::Index(expr) => expr.to_link_code(base),
+            Self::Call(expr) => expr.to_link_code(base),
+            Self::MethodCall(expr) => expr.to_link_code(base),
+            _ => unreachable!(),
+        }
+    }
+
+    // Lowers the chain up to this expression, with the base of `link` already lowered.
+    fn link_code(&self, link: &Expr, base: TokenStream) -> TokenStream {
+        if std::ptr::eq(self, link) {
+            return self.to_link_code(base);
+        }
+        let (inner, _) = self.link().unwrap();
+        let base = inner.link_code(link, base);
+        self.to_link_code(base)
+    }
+
+    // A nullish base of `?.` short-circuits the rest of the chain, which is lowered as a closure
+    // on the `Option` of the base, taking the value mutably so that methods can borrow it. The
+    // closure gives an `Option` itself, so that `?.` reaching something nullish or a later `?.` in
+    // the chain keeps it flat.
+    fn to_chain_code(&self, link: &Expr) -> TokenStream {
+        let runtime = encode::runtime();
+        let value = encode::private_ident("value");
+        let (base, _) = link.link().unwrap();
+        let base = match base.optional_link() {
+            Some(inner) => base.to_chain_code(inner),
+            None => {
+                let base = base.to_rust_token_stream();
+                quote!(#runtime::ops::Nullish::into_option(#base))
+            }
+        };
+        let rest = match self {
+            // The element the chain ends with is read.
+            Self::Index(index) => {
+                let base = if std::ptr::eq(self, link) {
+                    quote!(#value)
+                } else {
+                    index.base.link_code(link, quote!(#value))
+                };
+                index.to_get_code(base)
+            }
+            _ => self.link_code(link, quote!(#value)),
+        };
+
+        quote! {
+            #base.and_then(|#value| {
+                #[allow(unused_mut)]
+                let mut #value = #value;
+                #[allow(unused_imports)]
+                use #runtime::ops::{ChainOption as _, ChainValue as _};
+                #runtime::ops::Chained(::std::option::Option::Some(#rest)).chained()
+            })
+        }
+    }
+
+    pub(crate) fn to_operand_code(&self, tokens: &mut TokenStream) {
+        if self.is_compound() {
+            let inner = self.to_rust_token_stream();
+            tokens.extend(quote!((#inner)));
+        } else {
+            self.to_rust_code(tokens);
+        }
+    }
+}
+
+// Left-recursive expressions are only known once a whole expression has been parsed, so they are
+// parsed as an `Expr` and then unwrapped.
+macro_rules! parse_variant {
+    ($($variant:ident($type:ty), $what:literal;)+) => {
+        $(
+            impl Parse for $type {
+                fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+                    let span = input.span();
+                    match input.parse()? {
+                        Expr::$variant(expr) => Ok(expr),
+                        _ => Err(syn::Error::new(span, concat!("expected ", $what))),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+parse_variant! {
+    Assign(ExprAssign), "an assignment";
+    AssignOp(ExprAssignOp), "a compound assignment";
+    Binary(ExprBinary), "a binary expression";
+    Field(ExprField), "a field access";
+    Index(ExprIndex), "an index expression";
+    MethodCall(ExprMethodCall), "a method call";
+    Call(ExprCall), "a function call";
+    If(ExprIf), "a conditional expression";
+}
+
+impl ToRustCode for Expr {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        if let Some(link) = self.optional_link() {
+            tokens.extend(self.to_chain_code(link));
+            return;
+        }
+
+        match self {
+            Self::Assign(expr) => expr.to_rust_code(tokens),
+            Self::AssignOp(expr) => expr.to_rust_code(tokens),
+            Self::Array(expr) => expr.to_rust_code(tokens),
+            Self::ArrayExpand(expr) => expr.to_rust_code(tokens),
+            Self::Await(expr) => expr.to_rust_code(tokens),
+            Self::Binary(expr) => expr.to_rust_code(tokens),
+            Self::Unary(expr) => expr.to_rust_code(tokens),
+            Self::Update(expr) => expr.to_rust_code(tokens),
+            Self::Field(expr) => expr.to_rust_code(tokens),
+            Self::Index(expr) => expr.to_rust_code(tokens),
+            Self::Infer(expr) => expr.to_rust_code(tokens),
+            Self::This(expr) => expr.to_rust_code(tokens),
+            Self::Super(expr) => expr.to_rust_code(tokens),
+            Self::Lit(expr) => expr.to_rust_code(tokens),
+            Self::Template(expr) => expr.to_rust_code(tokens),
+            Self::TaggedTemplate(expr) => expr.to_rust_code(tokens),
+            Self::Path(expr) => expr.to_rust_code(tokens),
+            Self::MethodCall(expr) => expr.to_rust_code(tokens),
+            Self::Function(expr) => expr.to_rust_code(tokens),
+            Self::ArrowFunction(expr) => expr.to_rust_code(tokens),
+            Self::Call(expr) => expr.to_rust_code(tokens),
+            Self::Object(expr) => expr.to_rust_code(tokens),
+            Self::New(expr) => expr.to_rust_code(tokens),
+            Self::If(expr) => expr.to_rust_code(tokens),
+            Self::Rust(expr) => expr.to_rust_code(tokens),
+            Self::Paren(expr) => expr.to_rust_code(tokens),
+            Self::Yield(expr) => expr.to_rust_code(tokens),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprAssign {
     pub left: Box<Expr>,
     pub eq_token: Token![=],
     pub right: Box<Expr>,
 }
 
+#[derive(Debug)]
 pub struct ExprArrayExpand {
     pub colons: Token![...],
-    pub ident: Ident,
+    pub expr: Box<Expr>,
 }
 
 impl Parse for ExprArrayExpand {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         Ok(Self {
             colons: input.parse()?,
-            ident: input.parse()?,
+            expr: input.parse()?,
         })
     }
 }
 
-impl Parse for ExprAssign {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        Ok(Self {
-            left: input.parse()?,
-            eq_token: input.parse()?,
-            right: input.parse()?,
+// Spread elements are lowered by the array literal or call they appear in.
+impl ToRustCode for ExprArrayExpand {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        tokens.extend(
+            syn::Error::new(
+                self.colons.spans[0],
+                "spread syntax is only allowed in array literals and call arguments",
+            )
+            .to_compile_error(),
+        );
+    }
+}
+
+impl ExprArrayExpand {
+    // Spread values are cloned, so that the spread array can still be used afterwards.
+    fn to_iter_code(&self, tokens: &mut TokenStream) {
+        let expr = self.expr.to_rust_token_stream();
+        tokens.extend(quote_spanned! {self.colons.spans[0]=>
+            ::std::iter::Iterator::cloned(::std::iter::IntoIterator::into_iter(&(#expr)))
+        });
+    }
+}
+
+// Lowers a list of elements that may contain spreads to a `Vec`, chaining together an iterator
+// for every element.
+fn collect_code<'a>(elems: impl IntoIterator<Item = &'a Expr>, span: Span) -> TokenStream {
+    let elems: Vec<_> = elems.into_iter().collect();
+    if !elems
+        .iter()
+        .any(|elem| matches!(elem, Expr::ArrayExpand(_)))
+    {
+        let elems = elems.iter().map(|elem| elem.to_rust_token_stream());
+        return quote_spanned!(span=> ::std::vec![#(#elems),*]);
+    }
+
+    let mut iter = quote!(::std::iter::empty());
+    for elem in elems {
+        let next = match elem {
+            Expr::ArrayExpand(expand) => {
+                let mut iter = TokenStream::new();
+                expand.to_iter_code(&mut iter);
+                iter
+            }
+            elem => {
+                let elem = elem.to_rust_token_stream();
+                quote!(::std::iter::once(#elem))
+            }
+        };
+        iter = quote!(::std::iter::Iterator::chain(#iter, #next));
+    }
+    quote_spanned!(span=> ::std::iter::Iterator::collect::<::std::vec::Vec<_>>(#iter))
+}
+
+// Whether a call spreads arguments it cannot pass as a rest parameter.
+fn spread_error<'a>(args: impl IntoIterator<Item = &'a Expr>) -> Option<TokenStream> {
+    args.into_iter().find_map(|arg| match arg {
+        Expr::ArrayExpand(expand) => Some(
+            syn::Error::new(
+                expand.colons.spans[0],
+                "spread arguments can only be passed to the rest parameter of a function or \
+                 method declared in the script",
+            )
+            .to_compile_error(),
+        ),
+        _ => None,
+    })
+}
+
+// Lowers the arguments of a call to a function with the given parameters. Arguments given for
+// optional parameters are converted into an `Option`, missing ones are passed as `None`, and those
+// past the fixed parameters are collected into the rest parameter.
+fn args_code<'a>(
+    params: Option<Vec<ParamKind>>,
+    args: impl IntoIterator<Item = &'a Expr>,
+    span: Span,
+) -> Result<Vec<TokenStream>, TokenStream> {
+    let args: Vec<_> = args.into_iter().collect();
+    let Some(params) = params else {
+        if let Some(error) = spread_error(args.iter().copied()) {
+            return Err(error);
+        }
+        return Ok(args.iter().map(|arg| arg.to_rust_token_stream()).collect());
+    };
+
+    let fixed = params
+        .iter()
+        .position(|kind| *kind == ParamKind::Rest)
+        .unwrap_or(params.len());
+    let (args, rest) = args.split_at(fixed.min(args.len()));
+    let has_rest = fixed < params.len();
+    // Extra arguments to a function without a rest parameter are passed on for the Rust compiler
+    // to reject.
+    let extra = if has_rest { &[][..] } else { rest };
+    if let Some(error) = spread_error(args.iter().chain(extra).copied()) {
+        return Err(error);
+    }
+
+    let mut code: Vec<_> = params[..fixed]
+        .iter()
+        .zip(args.iter().map(Some).chain(std::iter::repeat(None)))
+        .map(|(kind, arg)| match (kind, arg) {
+            (ParamKind::Optional, Some(arg)) => {
+                let arg = arg.to_rust_token_stream();
+                quote!(::std::convert::Into::into(#arg))
+            }
+            (ParamKind::Optional, None) => quote!(::std::option::Option::None),
+            (_, arg) => arg.to_rust_token_stream(),
         })
+        .collect();
+    code.extend(extra.iter().map(|arg| arg.to_rust_token_stream()));
+    if has_rest {
+        code.push(collect_code(rest.iter().copied(), span));
     }
+    Ok(code)
 }
 
+impl ToRustCode for ExprAssign {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        if let Expr::Field(field) = &*self.left {
+            if let (None, Some((receiver, name, accessor))) = (field.optional, field.accessor()) {
+                if accessor.setter {
+                    let setter = format_ident!("set_{}", name);
+                    let right = self.right.to_rust_token_stream();
+                    tokens.extend(quote!(#receiver #setter(#right)));
+                    return;
+                }
+            }
+        }
+
+        self.left.to_place_code(tokens);
+        self.eq_token.to_tokens(tokens);
+        self.right.to_rust_code(tokens);
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprArray {
     pub bracket_token: Bracket,
     pub elems: Punctuated<Expr, Token![,]>,
@@ -79,7 +507,11 @@ impl Parse for ExprArray {
         let mut elems = Punctuated::new();
 
         while !content.is_empty() {
-            let first: Expr = content.parse()?;
+            let first = if content.peek(Token![...]) {
+                Expr::ArrayExpand(content.parse()?)
+            } else {
+                content.parse()?
+            };
             elems.push_value(first);
             if content.is_empty() {
                 break;
@@ -95,6 +527,13 @@ impl Parse for ExprArray {
     }
 }
 
+impl ToRustCode for ExprArray {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        tokens.extend(collect_code(&self.elems, self.bracket_token.span.join()));
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprAwait {
     pub await_token: Token![await],
     pub base: Box<Expr>,
@@ -104,27 +543,520 @@ impl Parse for ExprAwait {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         Ok(Self {
             await_token: input.parse()?,
-            base: input.parse()?,
+            base: Box::new(precedence::parse_unary(input)?),
+        })
+    }
+}
+
+impl ToRustCode for ExprAwait {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        // A generator is only resumed up to a `yield`, so it cannot wait on anything else.
+        if context::in_generator() {
+            tokens.extend(
+                syn::Error::new(
+                    self.await_token.span,
+                    "`await` is not allowed in a generator",
+                )
+                .to_compile_error(),
+            );
+            return;
+        }
+
+        let mut awaited = TokenStream::new();
+        self.base.to_operand_code(&mut awaited);
+        let await_token = &self.await_token;
+        awaited.extend(quote!(.#await_token));
+
+        // Errors returned by an async function are thrown on from awaiting it.
+        let throwing = match &*self.base {
+            Expr::Call(call) if self.base.optional_link().is_none() => call
+                .name()
+                .is_some_and(|name| scope::throws_on(&name) == ThrowsOn::Await),
+            _ => false,
+        };
+        tokens.extend(throwing_call_code(self.await_token.span, awaited, throwing));
+    }
+}
+
+// `yield value` suspends the generator with the value, and `yield* values` with each of the
+// values in turn.
+#[derive(Debug)]
+pub struct ExprYield {
+    pub yield_token: Token![yield],
+    pub star_token: Option<Token![*]>,
+    pub expr: Box<Expr>,
+}
+
+impl Parse for ExprYield {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            yield_token: input.parse()?,
+            star_token: input.parse()?,
+            expr: Box::new(precedence::parse_expr(input, Precedence::Assign)?),
         })
     }
 }
 
+impl ToRustCode for ExprYield {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        if !context::in_generator() {
+            tokens.extend(
+                syn::Error::new(
+                    self.yield_token.span,
+                    "`yield` is only allowed in a generator function",
+                )
+                .to_compile_error(),
+            );
+            return;
+        }
+
+        let yielder = encode::private_ident("yielder");
+        let expr = self.expr.to_rust_token_stream();
+        if self.star_token.is_none() {
+            tokens
+                .extend(quote_spanned!(self.yield_token.span=> #yielder.yield_value(#expr).await));
+            return;
+        }
+
+        let item = encode::private_ident("item");
+        tokens.extend(quote_spanned! {self.yield_token.span=>
+            for #item in ::std::iter::IntoIterator::into_iter(#expr) {
+                #yielder.yield_value(#item).await;
+            }
+        });
+    }
+}
+
+#[derive(Debug)]
+pub enum BinOp {
+    Rust(syn::BinOp),
+    Exp(Token![*], Token![*]),
+    Coalesce(Token![?], Token![?]),
+    StrictEq(Token![==], Token![=]),
+    StrictNe(Token![!=], Token![=]),
+    UnsignedShr(Token![>>], Token![>]),
+    In(Token![in]),
+    InstanceOf(Token![instanceof]),
+}
+
+impl Parse for BinOp {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![?]) && input.peek2(Token![?]) {
+            Ok(Self::Coalesce(input.parse()?, input.parse()?))
+        } else if input.peek(Token![*]) && input.peek2(Token![*]) && !input.peek2(Token![*=]) {
+            Ok(Self::Exp(input.parse()?, input.parse()?))
+        } else if input.peek(Token![==]) && input.peek3(Token![=]) {
+            Ok(Self::StrictEq(input.parse()?, input.parse()?))
+        } else if input.peek(Token![!=]) && input.peek3(Token![=]) {
+            Ok(Self::StrictNe(input.parse()?, input.parse()?))
+        } else if input.peek(Token![>>]) && input.peek3(Token![>]) && !input.peek3(Token![>=]) {
+            Ok(Self::UnsignedShr(input.parse()?, input.parse()?))
+        } else if input.peek(Token![in]) {
+            Ok(Self::In(input.parse()?))
+        } else if input.ipeek::<Token![instanceof]>() {
+            Ok(Self::InstanceOf(input.parse()?))
+        } else {
+            let span = input.span();
+            let op = input.parse()?;
+            if precedence::of_rust_op(&op).is_none() {
+                return Err(syn::Error::new(span, "expected a binary operator"));
+            }
+            Ok(Self::Rust(op))
+        }
+    }
+}
+
+impl BinOp {
+    pub fn precedence(&self) -> Precedence {
+        match self {
+            Self::Rust(op) => precedence::of_rust_op(op).unwrap(),
+            Self::Exp(..) => Precedence::Exponent,
+            Self::Coalesce(..) => Precedence::Coalesce,
+            Self::StrictEq(..) | Self::StrictNe(..) => Precedence::Equality,
+            Self::UnsignedShr(..) => Precedence::Shift,
+            Self::In(_) | Self::InstanceOf(_) => Precedence::Relational,
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprBinary {
     pub left: Box<Expr>,
     pub op: BinOp,
     pub right: Box<Expr>,
 }
 
-impl Parse for ExprBinary {
+impl ToRustCode for ExprBinary {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let left = {
+            let mut left = TokenStream::new();
+            self.left.to_operand_code(&mut left);
+            left
+        };
+        let right = {
+            let mut right = TokenStream::new();
+            self.right.to_operand_code(&mut right);
+            right
+        };
+
+        match &self.op {
+            BinOp::Rust(op) => tokens.extend(quote!(#left #op #right)),
+            BinOp::Exp(star_token, _) => tokens.extend(quote_spanned! {star_token.span=>
+                #runtime::number::Number::pow(#left, #right)
+            }),
+            BinOp::Coalesce(question_token, _) => {
+                tokens.extend(quote_spanned! {question_token.span=>
+                    #runtime::ops::Coalesce::coalesce(#left, || #right)
+                })
+            }
+            BinOp::StrictEq(eq_token, _) => {
+                let op = Token![==](eq_token.spans);
+                tokens.extend(quote!(#left #op #right));
+            }
+            BinOp::StrictNe(ne_token, _) => {
+                let op = Token![!=](ne_token.spans);
+                tokens.extend(quote!(#left #op #right));
+            }
+            BinOp::UnsignedShr(shr_token, _) => tokens.extend(quote_spanned! {shr_token.spans[0]=>
+                #runtime::number::Number::unsigned_shr(#left, #right)
+            }),
+            // Types are only known for variables of union types, where these check the variant.
+            BinOp::In(_) | BinOp::InstanceOf(_) => match Narrowing::of_binary(self) {
+                Some(narrowing) => tokens.extend(narrowing.to_cond_code()),
+                None => {
+                    let (span, op) = match &self.op {
+                        BinOp::In(in_token) => (in_token.span, "in"),
+                        BinOp::InstanceOf(instanceof_token) => {
+                            (instanceof_token.span, "instanceof")
+                        }
+                        _ => unreachable!(),
+                    };
+                    tokens.extend(
+                        syn::Error::new(
+                            span,
+                            format!("`{}` can only check a variable of a union type", op),
+                        )
+                        .to_compile_error(),
+                    );
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AssignOp {
+    Rust(syn::BinOp),
+    Exp(Token![*], Token![*=]),
+    UnsignedShr(Token![>>], Token![>=]),
+    Coalesce(Token![?], Token![?], Token![=]),
+    Or(Token![||], Token![=]),
+    And(Token![&&], Token![=]),
+}
+
+impl Parse for AssignOp {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        Ok(Self {
-            left: input.parse()?,
-            op: input.parse()?,
-            right: input.parse()?,
-        })
+        use syn::BinOp::*;
+
+        if input.peek(Token![?]) && input.peek2(Token![?]) && input.peek3(Token![=]) {
+            Ok(Self::Coalesce(
+                input.parse()?,
+                input.parse()?,
+                input.parse()?,
+            ))
+        } else if input.peek(Token![||]) && input.peek3(Token![=]) {
+            Ok(Self::Or(input.parse()?, input.parse()?))
+        } else if input.peek(Token![&&]) && input.peek3(Token![=]) {
+            Ok(Self::And(input.parse()?, input.parse()?))
+        } else if input.peek(Token![*]) && input.peek2(Token![*=]) {
+            Ok(Self::Exp(input.parse()?, input.parse()?))
+        } else if input.peek(Token![>>]) && input.peek3(Token![>=]) {
+            Ok(Self::UnsignedShr(input.parse()?, input.parse()?))
+        } else {
+            let span = input.span();
+            match input.parse()? {
+                op @ (AddAssign(_) | SubAssign(_) | MulAssign(_) | DivAssign(_) | RemAssign(_)
+                | BitXorAssign(_) | BitAndAssign(_) | BitOrAssign(_) | ShlAssign(_)
+                | ShrAssign(_)) => Ok(Self::Rust(op)),
+                _ => Err(syn::Error::new(span, "expected an assignment operator")),
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExprAssignOp {
+    pub left: Box<Expr>,
+    pub op: AssignOp,
+    pub right: Box<Expr>,
+}
+
+// The right-hand side of `??=`, `||=` and `&&=` is only evaluated when the assignment happens, so
+// those evaluate the place twice rather than holding a borrow of it across the right-hand side.
+impl ToRustCode for ExprAssignOp {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let place = encode::private_ident("place");
+        let value = encode::private_ident("value");
+        let left = {
+            let mut left = TokenStream::new();
+            self.left.to_place_code(&mut left);
+            left
+        };
+        let right = self.right.to_rust_token_stream();
+
+        let number_op = |op: Ident| {
+            quote! {{
+                let #value = #right;
+                let #place = &mut #left;
+                *#place = #runtime::number::Number::#op(*#place, #value);
+            }}
+        };
+
+        if let (AssignOp::Rust(op), Expr::Field(field)) = (&self.op, &*self.left) {
+            if let Some((get, set)) = field.accessor_calls() {
+                let set = set(quote!(#value));
+                tokens.extend(quote! {{
+                    let mut #value = #get;
+                    #value #op #right;
+                    #set;
+                }});
+                return;
+            }
+        }
+
+        match &self.op {
+            AssignOp::Rust(op) => tokens.extend(quote!(#left #op #right)),
+            AssignOp::Exp(star_token, _) => {
+                tokens.extend(number_op(Ident::new("pow", star_token.span)))
+            }
+            AssignOp::UnsignedShr(shr_token, _) => {
+                tokens.extend(number_op(Ident::new("unsigned_shr", shr_token.spans[0])))
+            }
+            AssignOp::Coalesce(question_token, _, _) => {
+                tokens.extend(quote_spanned! {question_token.span=>
+                    if ::std::option::Option::is_none(&#left) {
+                        #left = ::std::option::Option::Some(#right);
+                    }
+                })
+            }
+            AssignOp::Or(or_token, _) => tokens.extend(quote_spanned! {or_token.spans[0]=>
+                if !#runtime::ops::Truthy::truthy(&#left) {
+                    #left = #right;
+                }
+            }),
+            AssignOp::And(and_token, _) => tokens.extend(quote_spanned! {and_token.spans[0]=>
+                if #runtime::ops::Truthy::truthy(&#left) {
+                    #left = #right;
+                }
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum UnOp {
+    Neg(Token![-]),
+    Plus(Token![+]),
+    Not(Token![!]),
+    BitNot(Token![~]),
+    Typeof(Token![typeof]),
+    Void(Token![void]),
+    Delete(Token![delete]),
+}
+
+impl Parse for UnOp {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![-]) {
+            Ok(Self::Neg(input.parse()?))
+        } else if input.peek(Token![+]) {
+            Ok(Self::Plus(input.parse()?))
+        } else if input.peek(Token![!]) {
+            Ok(Self::Not(input.parse()?))
+        } else if input.peek(Token![~]) {
+            Ok(Self::BitNot(input.parse()?))
+        } else if input.peek(Token![typeof]) {
+            Ok(Self::Typeof(input.parse()?))
+        } else if input.ipeek::<Token![void]>() {
+            Ok(Self::Void(input.parse()?))
+        } else if input.ipeek::<Token![delete]>() {
+            Ok(Self::Delete(input.parse()?))
+        } else {
+            Err(input.error("expected a unary operator"))
+        }
+    }
+}
+
+impl UnOp {
+    pub fn peek(input: syn::parse::ParseStream) -> bool {
+        input.peek(Token![-])
+            || input.peek(Token![+])
+            || input.peek(Token![!])
+            || input.peek(Token![~])
+            || input.peek(Token![typeof])
+            || input.ipeek::<Token![void]>()
+            || input.ipeek::<Token![delete]>()
+    }
+}
+
+#[derive(Debug)]
+pub struct ExprUnary {
+    pub op: UnOp,
+    pub expr: Box<Expr>,
+}
+
+impl ToRustCode for ExprUnary {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let operand = {
+            let mut operand = TokenStream::new();
+            self.expr.to_operand_code(&mut operand);
+            operand
+        };
+
+        match &self.op {
+            UnOp::Neg(minus_token) => tokens.extend(quote!(#minus_token #operand)),
+            UnOp::Plus(plus_token) => tokens.extend(quote_spanned! {plus_token.span=>
+                #runtime::JsValue::toNumber(#operand)
+            }),
+            UnOp::Not(not_token) => tokens.extend(quote_spanned! {not_token.span=>
+                !#runtime::ops::Truthy::truthy(&#operand)
+            }),
+            // `!` on a `Number` is the bitwise not.
+            UnOp::BitNot(tilde_token) => {
+                let not_token = Token![!](tilde_token.span);
+                tokens.extend(quote!(#not_token #operand));
+            }
+            UnOp::Typeof(typeof_token) => tokens.extend(quote_spanned! {typeof_token.span=>
+                #runtime::ops::TypeOf::type_of(&#operand)
+            }),
+            UnOp::Void(void_token) => tokens.extend(quote_spanned! {void_token.span=>
+                {
+                    let _ = #operand;
+                }
+            }),
+            // Only optional properties can be deleted, which leaves them `undefined`.
+            UnOp::Delete(delete_token) => match &*self.expr {
+                Expr::Field(_) | Expr::Index(_) => {
+                    let place = {
+                        let mut place = TokenStream::new();
+                        self.expr.to_place_code(&mut place);
+                        place
+                    };
+                    tokens.extend(quote_spanned! {delete_token.span=>
+                        {
+                            ::std::option::Option::take(&mut #place);
+                            true
+                        }
+                    })
+                }
+                _ => tokens.extend(
+                    syn::Error::new(
+                        delete_token.span,
+                        "the operand of `delete` must be a property access",
+                    )
+                    .to_compile_error(),
+                ),
+            },
+        }
     }
 }
 
+#[derive(Debug)]
+pub enum UpdateOp {
+    Incr(Token![+], Token![+]),
+    Decr(Token![-], Token![-]),
+}
+
+impl Parse for UpdateOp {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if UpdateOp::peek(input, '+') {
+            Ok(Self::Incr(input.parse()?, input.parse()?))
+        } else if UpdateOp::peek(input, '-') {
+            Ok(Self::Decr(input.parse()?, input.parse()?))
+        } else {
+            Err(input.error("expected `++` or `--`"))
+        }
+    }
+}
+
+impl UpdateOp {
+    // `++` is two joint `+` tokens, while `+ +a` is not an update.
+    pub fn peek(input: syn::parse::ParseStream, ch: char) -> bool {
+        match input.cursor().punct() {
+            Some((first, rest)) if first.as_char() == ch && first.spacing() == Spacing::Joint => {
+                matches!(rest.punct(), Some((second, _)) if second.as_char() == ch)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExprUpdate {
+    pub op: UpdateOp,
+    pub prefix: bool,
+    pub expr: Box<Expr>,
+}
+
+impl ToRustCode for ExprUpdate {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let place = encode::private_ident("place");
+        let old = encode::private_ident("old");
+        let expr = {
+            let mut expr = TokenStream::new();
+            self.expr.to_place_code(&mut expr);
+            expr
+        };
+        let (span, op) = match &self.op {
+            UpdateOp::Incr(plus_token, _) => (plus_token.span, quote!(+=)),
+            UpdateOp::Decr(minus_token, _) => (minus_token.span, quote!(-=)),
+        };
+        let update = quote_spanned! {span=>
+            *#place #op #runtime::number::Number::from(1f64);
+        };
+
+        if let Expr::Field(field) = &*self.expr {
+            if let Some((get, set)) = field.accessor_calls() {
+                let set = set(quote!(*#place));
+                let result = if self.prefix {
+                    quote!(*#place)
+                } else {
+                    quote!(#old)
+                };
+                let value = encode::private_ident("value");
+                tokens.extend(quote! {{
+                    let mut #value = #get;
+                    let #old = #value;
+                    let #place = &mut #value;
+                    #update
+                    #set;
+                    #result
+                }});
+                return;
+            }
+        }
+
+        if self.prefix {
+            tokens.extend(quote! {{
+                let #place = &mut #expr;
+                #update
+                *#place
+            }});
+        } else {
+            tokens.extend(quote! {{
+                let #place = &mut #expr;
+                let #old = *#place;
+                #update
+                #old
+            }});
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprField {
     pub base: Box<Expr>,
     pub optional: Option<Token![?]>,
@@ -132,17 +1064,180 @@ pub struct ExprField {
     pub member: Member,
 }
 
-impl Parse for ExprField {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        Ok(Self {
-            base: input.parse()?,
-            optional: input.parse()?,
-            dot_token: input.parse()?,
-            member: input.parse()?,
-        })
+impl ExprField {
+    // The accessors a named member goes through, along with what to call them on: `Class::` for a
+    // static member, otherwise the base followed by a dot.
+    fn accessor(&self) -> Option<(TokenStream, &Ident, scope::Accessor)> {
+        let Member::Named(name) = &self.member else {
+            return None;
+        };
+
+        if let Expr::Path(ExprPath { path }) = &*self.base {
+            if let Some(class) = path.get_ident() {
+                if let Some(accessor) = scope::accessor(&format!("{}.{}", class, name))
+                    .filter(|accessor| accessor.is_static)
+                {
+                    return Some((quote!(#class::), name, accessor));
+                }
+            }
+        }
+
+        let accessor = Self::instance_accessor(&self.base, name)?;
+        let mut receiver = TokenStream::new();
+        self.base.to_operand_code(&mut receiver);
+        self.dot_token.to_tokens(&mut receiver);
+        Some((receiver, name, accessor))
+    }
+
+    // Members of objects only go through accessors when the class of the object is known to
+    // declare them.
+    fn instance_accessor(base: &Expr, name: &Ident) -> Option<scope::Accessor> {
+        let key = scope::member_key(&base.class()?, &name.to_string())?;
+        scope::accessor(&key).filter(|accessor| !accessor.is_static)
+    }
+
+    // The getter and setter calls of a member that has both, for updating it in place.
+    fn accessor_calls(&self) -> Option<(TokenStream, impl FnOnce(TokenStream) -> TokenStream)> {
+        if self.optional.is_some() {
+            return None;
+        }
+        let (receiver, name, accessor) = self.accessor()?;
+        if !accessor.getter || !accessor.setter {
+            return None;
+        }
+
+        let setter = format_ident!("set_{}", name);
+        let get = quote!(#receiver #name());
+        Some((get, move |value| quote!(#receiver #setter(#value))))
+    }
+
+    // Reads the member of a base that is reached through `?.`.
+    fn to_link_code(&self, base: TokenStream) -> TokenStream {
+        let dot_token = &self.dot_token;
+        match &self.member {
+            Member::Named(name)
+                if Self::instance_accessor(&self.base, name)
+                    .is_some_and(|accessor| accessor.getter) =>
+            {
+                quote!(#base #dot_token #name())
+            }
+            member => quote!(#base #dot_token #member),
+        }
+    }
+}
+
+impl ToRustCode for ExprField {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        if let (None, Expr::Path(ExprPath { path })) = (self.optional, &*self.base) {
+            if let Some(var) = path.get_ident() {
+                if let Some(read) = narrow::read(var, Some(&self.member), false) {
+                    tokens.extend(read);
+                    return;
+                }
+                // The discriminant of a union is read through a method, like an accessor.
+                if let (Some(union), Member::Named(name)) =
+                    (scope::variable_union(var), &self.member)
+                {
+                    if union.tag.as_ref() == Some(name) {
+                        tokens.extend(quote!(#var.#name()));
+                        return;
+                    }
+                }
+            }
+        }
+
+        let base = {
+            let mut base = TokenStream::new();
+            self.base.to_place_code(&mut base);
+            base
+        };
+        let dot_token = &self.dot_token;
+        let member = &self.member;
+
+        if let (None, Some((ident, item)), Member::Named(name)) =
+            (self.optional, self.base.enumeration(), member)
+        {
+            match item.member(&name.to_string()) {
+                Some(value) if item.is_const => tokens.extend(enumeration::value_code(value)),
+                Some(_) => tokens.extend(quote!(#ident::#name)),
+                None => tokens.extend(
+                    syn::Error::new(
+                        name.span(),
+                        format!("enum `{}` has no member `{}`", ident, name),
+                    )
+                    .to_compile_error(),
+                ),
+            }
+            return;
+        }
+
+        match self.accessor() {
+            Some((receiver, name, accessor)) if accessor.getter => {
+                tokens.extend(quote!(#receiver #name()))
+            }
+            _ => tokens.extend(quote!(#base #dot_token #member)),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExprIndex {
+    pub base: Box<Expr>,
+    pub optional: Option<(Token![?], Token![.])>,
+    pub bracket_token: Bracket,
+    pub index: Box<Expr>,
+}
+
+impl ExprIndex {
+    // An element that is assigned to or borrowed must exist.
+    fn to_link_code(&self, base: TokenStream) -> TokenStream {
+        let mut tokens = base;
+        self.bracket_token
+            .surround(&mut tokens, |tokens| self.index.to_rust_code(tokens));
+        tokens
+    }
+
+    // An element that is read may not exist, which gives `None`.
+    fn to_get_code(&self, base: TokenStream) -> TokenStream {
+        let runtime = encode::runtime();
+        let index = self.index.to_rust_token_stream();
+        quote_spanned! {self.bracket_token.span.join()=>
+            ::std::option::Option::<&_>::cloned(#runtime::ops::Get::get(&#base, #index))
+        }
+    }
+}
+
+impl ToRustCode for ExprIndex {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        if let Some((ident, item)) = self.base.enumeration() {
+            let runtime = encode::runtime();
+            let key = encode::private_ident("key");
+            let index = self.index.to_rust_token_stream();
+            let numeric = item
+                .members
+                .iter()
+                .all(|(_, value)| matches!(value, EnumValue::Number(_)));
+            let message = if item.is_const {
+                "a `const enum` has no reverse mapping"
+            } else if !numeric {
+                "only numeric enums have a reverse mapping"
+            } else {
+                tokens.extend(quote! {
+                    #ident::#key(::std::convert::Into::<#runtime::number::Number>::into(#index))
+                });
+                return;
+            };
+            tokens.extend(syn::Error::new(ident.span(), message).to_compile_error());
+            return;
+        }
+
+        let mut base = TokenStream::new();
+        self.base.to_operand_code(&mut base);
+        tokens.extend(self.to_get_code(base));
     }
 }
 
+#[derive(Debug)]
 pub struct ExprInfer {
     pub underscore_token: Token![_],
 }
@@ -155,6 +1250,56 @@ impl Parse for ExprInfer {
     }
 }
 
+impl ToRustCode for ExprInfer {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.underscore_token.to_tokens(tokens);
+    }
+}
+
+#[derive(Debug)]
+pub struct ExprThis {
+    pub this_token: Token![this],
+}
+
+impl Parse for ExprThis {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            this_token: input.parse()?,
+        })
+    }
+}
+
+impl ToRustCode for ExprThis {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        match scope::this() {
+            Some(this) => tokens.extend(quote_spanned!(self.this_token.span=> #this)),
+            None => Token![self](self.this_token.span).to_tokens(tokens),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExprSuper {
+    pub super_token: Token![super],
+}
+
+impl Parse for ExprSuper {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            super_token: input.parse()?,
+        })
+    }
+}
+
+impl ToRustCode for ExprSuper {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        tokens.extend(quote_spanned! {self.super_token.span=>
+            ::std::ops::DerefMut::deref_mut(self)
+        });
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprLit {
     pub lit: syn::Lit,
 }
@@ -167,8 +1312,77 @@ impl Parse for ExprLit {
     }
 }
 
+impl ToRustCode for ExprLit {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let span = self.lit.span();
+
+        match &self.lit {
+            Lit::Int(lit) => match lit.base10_parse::<f64>() {
+                Ok(value) => {
+                    let mut value = Literal::f64_suffixed(value);
+                    value.set_span(span);
+                    tokens.extend(quote_spanned!(span=> #runtime::number::Number::from(#value)));
+                }
+                Err(err) => tokens.extend(err.to_compile_error()),
+            },
+            Lit::Float(lit) => match lit.base10_parse::<f64>() {
+                Ok(value) => {
+                    let mut value = Literal::f64_suffixed(value);
+                    value.set_span(span);
+                    tokens.extend(quote_spanned!(span=> #runtime::number::Number::from(#value)));
+                }
+                Err(err) => tokens.extend(err.to_compile_error()),
+            },
+            Lit::Str(_) | Lit::Char(_) => {
+                let lit = &self.lit;
+                tokens.extend(quote_spanned! {span=>
+                    #runtime::string::JsString::new(::std::string::String::from(#lit))
+                });
+            }
+            lit => lit.to_tokens(tokens),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExprPath {
+    pub path: syn::Path,
+}
+
+impl Parse for ExprPath {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            path: syn::Path::parse_mod_style(input)?,
+        })
+    }
+}
+
+impl ExprPath {
+    fn field_param_code(var: &Ident) -> Option<TokenStream> {
+        scope::is_field_param(var).then(|| quote!(self.#var))
+    }
+}
+
+impl ToRustCode for ExprPath {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        if let Some(read) = self
+            .path
+            .get_ident()
+            .and_then(|var| narrow::read(var, None, false).or_else(|| Self::field_param_code(var)))
+        {
+            tokens.extend(read);
+            return;
+        }
+
+        self.path.to_tokens(tokens);
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprMethodCall {
     pub receiver: Box<Expr>,
+    pub optional: Option<Token![?]>,
     pub dot_token: Token![.],
     pub method: Ident,
     pub turbofish: Option<AngleBracketedGenericArguments>,
@@ -176,45 +1390,65 @@ pub struct ExprMethodCall {
     pub args: Punctuated<Expr, Token![,]>,
 }
 
-impl Parse for ExprMethodCall {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let receiver: Box<Expr> = input.parse()?;
-        let dot_token: Token![.] = input.parse()?;
-        let method: Ident = input.parse()?;
-        let turbofish: Option<AngleBracketedGenericArguments> =
-            if input.peek(Token![::]) && input.peek3(Token![<]) {
-                Some(input.parse()?)
-            } else {
-                None
-            };
-        let content;
-        let paren_token = parenthesized!(content in input);
-        let mut args = Punctuated::new();
+impl ExprMethodCall {
+    // The method is known to the scope after the class declaring it, when the class of the
+    // receiver is known.
+    fn key(&self) -> Option<String> {
+        scope::member_key(&self.receiver.class()?, &self.method.to_string())
+    }
 
-        while !content.is_empty() {
-            let first: Expr = content.parse()?;
-            args.push_value(first);
-            if content.is_empty() {
-                break;
-            }
-            let punct = content.parse()?;
-            args.push_punct(punct);
+    fn is_throwing(&self) -> bool {
+        self.key().is_some_and(|key| scope::is_throwing_fn(&key))
+    }
+
+    // A method reached through `?.` is called in a closure, which its errors cannot be thrown on
+    // from.
+    fn to_link_code(&self, receiver: TokenStream) -> TokenStream {
+        if self.is_throwing() {
+            return syn::Error::new(
+                self.method.span(),
+                format!(
+                    "`{}` can throw, so it cannot be called through `?.`",
+                    self.method
+                ),
+            )
+            .to_compile_error();
         }
+        self.to_call_code(receiver)
+    }
 
-        Ok(Self {
-            receiver,
-            dot_token,
-            method,
-            turbofish,
-            paren_token,
-            args,
-        })
+    fn to_call_code(&self, receiver: TokenStream) -> TokenStream {
+        let params = self.key().and_then(|key| scope::signature(&key));
+        let args = match args_code(params, &self.args, self.paren_token.span.join()) {
+            Ok(args) => args,
+            Err(error) => return error,
+        };
+
+        let mut tokens = receiver;
+        self.dot_token.to_tokens(&mut tokens);
+        self.method.to_tokens(&mut tokens);
+        self.turbofish.to_tokens(&mut tokens);
+        self.paren_token
+            .surround(&mut tokens, |tokens| tokens.extend(quote!(#(#args),*)));
+        tokens
+    }
+}
+
+impl ToRustCode for ExprMethodCall {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let mut receiver = TokenStream::new();
+        self.receiver.to_place_code(&mut receiver);
+        let call = self.to_call_code(receiver);
+        let span = self.method.span();
+        tokens.extend(throwing_call_code(span, call, self.is_throwing()));
     }
 }
 
+#[derive(Debug)]
 pub struct ExprFunction {
     pub async_token: Option<Token![async]>,
     pub function_token: Token![function],
+    pub star_token: Option<Token![*]>,
     pub generics: Generics,
     pub inputs: FnArgs,
     pub output: Option<TypeAnnotation>,
@@ -226,6 +1460,7 @@ impl Parse for ExprFunction {
         Ok(Self {
             async_token: input.parse()?,
             function_token: input.parse()?,
+            star_token: input.parse()?,
             generics: input.parse()?,
             inputs: input.parse()?,
             output: if input.peek(Token![:]) {
@@ -238,9 +1473,134 @@ impl Parse for ExprFunction {
     }
 }
 
+impl ExprFunction {
+    fn to_closure_inputs_code(&self) -> TokenStream {
+        match &self.inputs.receiver {
+            Some(receiver) => {
+                syn::Error::new(receiver.span(), "function expressions cannot take `self`")
+                    .to_compile_error()
+            }
+            None => self.inputs.to_params_code(),
+        }
+    }
+
+    // Unlike generator functions declared as items, closures can leave the type of the values
+    // they yield to be inferred.
+    fn to_generator_code(&self, star_token: &Token![*]) -> syn::Result<TokenStream> {
+        if let Some(async_token) = &self.async_token {
+            return Err(syn::Error::new(
+                async_token.span,
+                "async generators are not supported",
+            ));
+        }
+
+        if !self.generics.params.is_empty() {
+            let runtime = encode::runtime();
+            let yield_ty = item::yield_type(self.output.as_ref(), star_token.spans[0])?;
+            let body = item::generator_code(&self.inputs, &self.body, &yield_ty);
+            let ident = Ident::new("function", self.function_token.span);
+            let generics = self.generics.to_rust_token_stream();
+            let inputs = self.inputs.to_rust_token_stream();
+            return Ok(quote! {
+                {
+                    fn #ident #generics #inputs
+                        -> #runtime::generator::Generator<'static, #yield_ty>
+                    {
+                        #body
+                    }
+                    #ident
+                }
+            });
+        }
+
+        let yield_ty = match &self.output {
+            Some(output) => item::yield_type(Some(output), star_token.spans[0])?,
+            None => Type::Infer(TypeInfer {
+                underscore_token: Token![_](star_token.spans[0]),
+            }),
+        };
+        let inputs = self.to_closure_inputs_code();
+        let body = item::generator_code(&self.inputs, &self.body, &yield_ty);
+        Ok(quote!(|#inputs| #body))
+    }
+}
+
+impl ToRustCode for ExprFunction {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        if let Some(star_token) = &self.star_token {
+            tokens.extend(
+                self.to_generator_code(star_token)
+                    .unwrap_or_else(|err| err.to_compile_error()),
+            );
+            return;
+        }
+
+        // A closure that throws returns a `Result`, like a function.
+        let throws = throws::function_expr_throws(self);
+        let (body, _) = context::with_throw_target(closure_target(throws), || {
+            let mut body = TokenStream::new();
+            self.inputs.to_body_code(&self.body, &mut body);
+            body
+        });
+        let body = match throws {
+            true => quote!({ ::std::result::Result::Ok(#body) }),
+            false => body,
+        };
+        let output = closure_output(self.output.as_ref(), throws);
+
+        // Closures cannot be generic, so generic function expressions become a local item.
+        if !self.generics.params.is_empty() {
+            let async_token = &self.async_token;
+            let ident = Ident::new("function", self.function_token.span);
+            let generics = self.generics.to_rust_token_stream();
+            let inputs = self.inputs.to_rust_token_stream();
+
+            tokens.extend(quote! {
+                {
+                    #async_token fn #ident #generics #inputs #output #body
+                    #ident
+                }
+            });
+            return;
+        }
+
+        let inputs = self.to_closure_inputs_code();
+
+        if self.async_token.is_some() {
+            tokens.extend(quote!(|#inputs| async move #body));
+        } else {
+            tokens.extend(quote!(|#inputs| #output #body));
+        }
+    }
+}
+
+// Errors thrown in a closure that is found not to throw have nowhere to go, but it has no `try`
+// of its own that could hide them either.
+fn closure_target(throws: bool) -> ThrowTarget {
+    match throws {
+        true => ThrowTarget::Return,
+        false => ThrowTarget::Nowhere("errors cannot be thrown out of this closure"),
+    }
+}
+
+// What a closure is declared to return, in a `Result` when it throws, with the type left to be
+// inferred if it is not given.
+fn closure_output(output: Option<&TypeAnnotation>, throws: bool) -> Option<TokenStream> {
+    let ty = output.map(ToRustCode::to_rust_token_stream);
+    match (throws, ty) {
+        (true, ty) => {
+            let runtime = encode::runtime();
+            let ty = ty.unwrap_or_else(|| quote!(_));
+            Some(quote!(-> ::std::result::Result<#ty, #runtime::error::Error>))
+        }
+        (false, ty) => ty.map(|ty| quote!(-> #ty)),
+    }
+}
+
+#[derive(Debug)]
 pub struct ArrowFunctionArgs {
     pub paren_token: Paren,
-    pub inputs: Punctuated<Pat, Token![,]>,
+    pub inputs: Punctuated<Pattern, Token![,]>,
 }
 
 impl Parse for ArrowFunctionArgs {
@@ -250,7 +1610,7 @@ impl Parse for ArrowFunctionArgs {
         let mut inputs = Punctuated::new();
 
         while !content.is_empty() {
-            let new = Pat::parse_multi(&content)?;
+            let new = content.parse()?;
             inputs.push_value(new);
 
             if content.is_empty() {
@@ -267,6 +1627,40 @@ impl Parse for ArrowFunctionArgs {
     }
 }
 
+// Destructured parameters are taken as plain arguments, like in `FnArgs`.
+impl ToRustCode for ArrowFunctionArgs {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let inputs = self.inputs.iter().enumerate().map(|(i, pat)| match pat {
+            Pattern::Ident(ident) => ident.clone(),
+            _ => encode::private_ident(&format!("arg{}", i)),
+        });
+        tokens.extend(quote!(|#(#inputs),*|));
+    }
+}
+
+impl ArrowFunctionArgs {
+    // The parameters taken by name, which have no type.
+    pub(crate) fn variables(&self) -> Vec<(Ident, Option<Type>)> {
+        self.inputs
+            .iter()
+            .filter_map(|pat| match pat {
+                Pattern::Ident(ident) => Some((ident.clone(), None)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn to_destructuring_code(&self, tokens: &mut TokenStream) {
+        for (i, pat) in self.inputs.iter().enumerate() {
+            if !pat.is_ident() {
+                let ident = encode::private_ident(&format!("arg{}", i));
+                pat.to_binding_code(quote!(#ident), None, true, tokens);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprArrowFunction {
     pub inputs: ArrowFunctionArgs,
     pub output: Option<TypeAnnotation>,
@@ -289,39 +1683,203 @@ impl Parse for ExprArrowFunction {
     }
 }
 
+impl ToRustCode for ExprArrowFunction {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.inputs.to_rust_code(tokens);
+
+        let throws = throws::arrow_function_throws(self);
+        let (body, _) = context::with_throw_target(closure_target(throws), || {
+            scope::with_variables(self.inputs.variables(), || match &self.body.stmt {
+                StmtValue::Block(block) => block.to_rust_token_stream(),
+                // A closure declaring what it returns needs a block for its body.
+                StmtValue::Expr(expr) => {
+                    let expr = expr.to_rust_token_stream();
+                    match self.output.is_none() && !throws {
+                        true => expr,
+                        false => quote!({ #expr }),
+                    }
+                }
+                _ => {
+                    let stmt = self.body.to_rust_token_stream();
+                    quote!({ #stmt })
+                }
+            })
+        });
+        let body = match throws {
+            true => quote!({ ::std::result::Result::Ok(#body) }),
+            false => body,
+        };
+
+        let mut bindings = TokenStream::new();
+        self.inputs.to_destructuring_code(&mut bindings);
+        let body = if bindings.is_empty() {
+            body
+        } else {
+            quote!({ #bindings #body })
+        };
+
+        tokens.extend(closure_output(self.output.as_ref(), throws));
+        tokens.extend(body);
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprCall {
     pub func: Box<Expr>,
+    pub optional: Option<(Token![?], Token![.])>,
     pub paren_token: Paren,
     pub args: Punctuated<Expr, Token![,]>,
 }
 
-impl Parse for ExprCall {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let func = input.parse()?;
-        let content;
-        let paren_token = parenthesized!(content in input);
-        let mut args = Punctuated::new();
+impl ExprCall {
+    // `f(...)` calls a function, and `Class::f(...)` a static method, known as `Class.f`.
+    pub(crate) fn name(&self) -> Option<String> {
+        let Expr::Path(ExprPath { path }) = &*self.func else {
+            return None;
+        };
+        match path.segments.len() {
+            1 => Some(path.segments[0].ident.to_string()),
+            2 => Some(format!(
+                "{}.{}",
+                path.segments[0].ident, path.segments[1].ident
+            )),
+            _ => None,
+        }
+    }
 
-        while !content.is_empty() {
-            let new = input.parse()?;
-            args.push_value(new);
+    // Calls a function reached through `?.`, which is neither known to take a rest parameter nor
+    // to throw.
+    fn to_link_code(&self, func: TokenStream) -> TokenStream {
+        if let Some(error) = spread_error(&self.args) {
+            return error;
+        }
 
-            if content.is_empty() {
-                break;
-            }
+        let mut tokens = quote!((#func));
+        self.paren_token
+            .surround(&mut tokens, |tokens| self.args.to_rust_code(tokens));
+        tokens
+    }
+}
 
-            let punct = input.parse()?;
-            args.push_punct(punct);
+impl ToRustCode for ExprCall {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        // `super(...)` runs the base class constructor on the base part of `this`.
+        if let Expr::Super(expr) = &*self.func {
+            let runtime = encode::runtime();
+            let args = self.args.to_rust_token_stream();
+            let comma = (!self.args.is_empty()).then(|| quote!(,));
+            let call = quote_spanned! {expr.super_token.span=>
+                <<Self as #runtime::class::Extends>::Super>::constructor(
+                    ::std::ops::DerefMut::deref_mut(self) #comma #args
+                )
+            };
+            let throwing = scope::is_throwing_fn("super");
+            tokens.extend(throwing_call_code(expr.super_token.span, call, throwing));
+            return;
         }
 
+        let name = self.name();
+        let params = name.as_deref().and_then(scope::signature);
+        let args = match args_code(params, &self.args, self.paren_token.span.join()) {
+            Ok(args) => args,
+            Err(error) => {
+                tokens.extend(error);
+                return;
+            }
+        };
+
+        let mut call = TokenStream::new();
+        self.func.to_operand_code(&mut call);
+        self.paren_token
+            .surround(&mut call, |tokens| tokens.extend(quote!(#(#args),*)));
+
+        let throwing = name.is_some_and(|name| scope::is_throwing_fn(&name));
+        let span = self.paren_token.span.join();
+        tokens.extend(throwing_call_code(span, call, throwing));
+    }
+}
+
+// Errors returned by a throwing function or method are thrown on from the call.
+fn throwing_call_code(span: Span, call: TokenStream, throwing: bool) -> TokenStream {
+    if !throwing {
+        return call;
+    }
+
+    let value = encode::private_ident("value");
+    let error = encode::private_ident("error");
+    let propagate = context::propagate(span, quote!(#error));
+    quote! {
+        (match #call {
+            ::std::result::Result::Ok(#value) => #value,
+            ::std::result::Result::Err(#error) => #propagate,
+        })
+    }
+}
+
+// `new Foo(args)` goes through the runtime `Constructor` trait, which classes implement with their
+// constructor's signature, so that built-in types are instantiated the same way.
+#[derive(Debug)]
+pub struct ExprNew {
+    pub new_token: Token![new],
+    pub path: syn::Path,
+    pub generics: Option<AngleBracketedGenericArguments>,
+    pub args: Option<(Paren, Punctuated<Expr, Token![,]>)>,
+}
+
+impl Parse for ExprNew {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         Ok(Self {
-            func,
-            paren_token,
-            args,
+            new_token: input.parse()?,
+            path: syn::Path::parse_mod_style(input)?,
+            generics: if input.peek(Token![<]) {
+                Some(input.parse()?)
+            } else {
+                None
+            },
+            args: if input.peek(Paren) {
+                let content;
+                let paren_token = parenthesized!(content in input);
+                Some((paren_token, precedence::parse_args(&content)?))
+            } else {
+                None
+            },
         })
     }
 }
 
+impl ToRustCode for ExprNew {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let path = &self.path;
+        let generics = &self.generics;
+        // The constructor of a class in scope is known as `Class.constructor`.
+        let params = path
+            .get_ident()
+            .and_then(|class| scope::signature(&format!("{}.constructor", class)));
+        let span = match &self.args {
+            Some((paren_token, _)) => paren_token.span.join(),
+            None => self.path.span(),
+        };
+        let args = match args_code(params, self.args.iter().flat_map(|(_, args)| args), span) {
+            Ok(args) => args,
+            Err(error) => {
+                tokens.extend(error);
+                return;
+            }
+        };
+
+        let params = args.iter().map(|_| quote!(_));
+        let call = quote_spanned! {self.new_token.span=>
+            <#path #generics as #runtime::Constructor<fn(#(#params),*) -> _>>::constructor(#(#args),*)
+        };
+        let throwing = path
+            .get_ident()
+            .is_some_and(|class| scope::is_throwing_fn(&format!("{}.constructor", class)));
+        tokens.extend(throwing_call_code(self.new_token.span, call, throwing));
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprIf {
     pub cond: Box<Expr>,
     pub question_token: Token![?],
@@ -330,18 +1888,17 @@ pub struct ExprIf {
     pub else_branch: Box<Expr>,
 }
 
-impl Parse for ExprIf {
-    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        Ok(Self {
-            cond: input.parse()?,
-            question_token: input.parse()?,
-            then_branch: input.parse()?,
-            colon_token: input.parse()?,
-            else_branch: input.parse()?,
-        })
+impl ToRustCode for ExprIf {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let cond = self.cond.to_rust_token_stream();
+        let then_branch = self.then_branch.to_rust_token_stream();
+        let else_branch = self.else_branch.to_rust_token_stream();
+
+        tokens.extend(quote!(if #cond { #then_branch } else { #else_branch }));
     }
 }
 
+#[derive(Debug)]
 pub struct RustCode {
     pub rust_token: Token![rust],
     pub body: syn::Block,
@@ -356,6 +1913,13 @@ impl Parse for RustCode {
     }
 }
 
+impl ToRustCode for RustCode {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.body.to_tokens(tokens);
+    }
+}
+
+#[derive(Debug)]
 pub struct ExprParen {
     pub paren_token: Paren,
     pub expr: Box<Expr>,
@@ -370,3 +1934,457 @@ impl Parse for ExprParen {
         })
     }
 }
+
+impl ToRustCode for ExprParen {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.paren_token
+            .surround(tokens, |tokens| self.expr.to_rust_code(tokens));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lit_to_rust_code() {
+        let lit: ExprLit = syn::parse2(quote!(1)).unwrap();
+        assert_eq!(
+            lit.to_rust_token_stream().to_string(),
+            quote!(::rsscript::types::number::Number::from(1f64)).to_string()
+        );
+
+        let lit: ExprLit = syn::parse2(quote!("text")).unwrap();
+        assert_eq!(
+            lit.to_rust_token_stream().to_string(),
+            quote!(::rsscript::types::string::JsString::new(
+                ::std::string::String::from("text")
+            ))
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn expr_to_rust_code() {
+        let lower = |source: &str| {
+            let expr: Expr = syn::parse_str(source).unwrap();
+            expr.to_rust_token_stream().to_string()
+        };
+
+        assert_eq!(lower("a = b"), quote!(a = b).to_string());
+        assert_eq!(lower("[a, b]"), quote!(::std::vec![a, b]).to_string());
+        assert_eq!(lower("await a"), quote!(a.await).to_string());
+        assert_eq!(lower("a + b"), quote!(a + b).to_string());
+        assert_eq!(lower("a.b"), quote!(a.b).to_string());
+        assert_eq!(lower("a.b(c)"), quote!(a.b(c)).to_string());
+        assert_eq!(
+            lower("function (a: Number): Number {}"),
+            quote!(|a: Number| -> Number {}).to_string()
+        );
+        assert_eq!(lower("(a, b) => a + b"), quote!(|a, b| a + b).to_string());
+        assert_eq!(lower("f(a, b)"), quote!(f(a, b)).to_string());
+        assert_eq!(
+            lower("a ? b : c"),
+            quote!(if a { b } else { c }).to_string()
+        );
+        assert_eq!(lower("rust { a.len() }"), quote!({ a.len() }).to_string());
+        assert_eq!(lower("(a + b) * c"), quote!((a + b) * c).to_string());
+    }
+
+    // Renders the tree as an s-expression to pin down how operators grouped.
+    fn shape(expr: &Expr) -> String {
+        match expr {
+            Expr::Assign(expr) => format!("(= {} {})", shape(&expr.left), shape(&expr.right)),
+            Expr::Binary(expr) => {
+                let op = match &expr.op {
+                    BinOp::Rust(op) => op.to_token_stream().to_string().replace(' ', ""),
+                    BinOp::Exp(..) => "**".to_string(),
+                    BinOp::Coalesce(..) => "??".to_string(),
+                    BinOp::StrictEq(..) => "===".to_string(),
+                    BinOp::StrictNe(..) => "!==".to_string(),
+                    BinOp::UnsignedShr(..) => ">>>".to_string(),
+                    BinOp::In(_) => "in".to_string(),
+                    BinOp::InstanceOf(_) => "instanceof".to_string(),
+                };
+                format!("({} {} {})", op, shape(&expr.left), shape(&expr.right))
+            }
+            Expr::If(expr) => format!(
+                "(? {} {} {})",
+                shape(&expr.cond),
+                shape(&expr.then_branch),
+                shape(&expr.else_branch)
+            ),
+            Expr::Field(expr) => format!(
+                "({}. {} {})",
+                optional(expr.optional.is_some()),
+                shape(&expr.base),
+                expr.member.to_token_stream()
+            ),
+            Expr::Index(expr) => format!(
+                "({}[] {} {})",
+                optional(expr.optional.is_some()),
+                shape(&expr.base),
+                shape(&expr.index)
+            ),
+            Expr::Call(expr) => {
+                let args: Vec<_> = expr.args.iter().map(shape).collect();
+                format!(
+                    "({}call {} {})",
+                    optional(expr.optional.is_some()),
+                    shape(&expr.func),
+                    args.join(" ")
+                )
+            }
+            Expr::MethodCall(expr) => {
+                let args: Vec<_> = expr.args.iter().map(shape).collect();
+                format!(
+                    "({}.call {} {} {})",
+                    optional(expr.optional.is_some()),
+                    shape(&expr.receiver),
+                    expr.method,
+                    args.join(" ")
+                )
+            }
+            Expr::AssignOp(expr) => {
+                let op = match &expr.op {
+                    AssignOp::Rust(op) => op.to_token_stream().to_string().replace(' ', ""),
+                    AssignOp::Exp(..) => "**=".to_string(),
+                    AssignOp::UnsignedShr(..) => ">>>=".to_string(),
+                    AssignOp::Coalesce(..) => "??=".to_string(),
+                    AssignOp::Or(..) => "||=".to_string(),
+                    AssignOp::And(..) => "&&=".to_string(),
+                };
+                format!("({} {} {})", op, shape(&expr.left), shape(&expr.right))
+            }
+            Expr::Unary(expr) => {
+                let op = match &expr.op {
+                    UnOp::Neg(_) => "-",
+                    UnOp::Plus(_) => "+",
+                    UnOp::Not(_) => "!",
+                    UnOp::BitNot(_) => "~",
+                    UnOp::Typeof(_) => "typeof",
+                    UnOp::Void(_) => "void",
+                    UnOp::Delete(_) => "delete",
+                };
+                format!("({} {})", op, shape(&expr.expr))
+            }
+            Expr::Update(expr) => {
+                let op = match (&expr.op, expr.prefix) {
+                    (UpdateOp::Incr(..), true) => "++_",
+                    (UpdateOp::Incr(..), false) => "_++",
+                    (UpdateOp::Decr(..), true) => "--_",
+                    (UpdateOp::Decr(..), false) => "_--",
+                };
+                format!("({} {})", op, shape(&expr.expr))
+            }
+            Expr::New(expr) => {
+                let args: Vec<_> = expr
+                    .args
+                    .iter()
+                    .flat_map(|(_, args)| args)
+                    .map(shape)
+                    .collect();
+                format!("(new {} {})", expr.path.to_token_stream(), args.join(" "))
+            }
+            Expr::ArrayExpand(expr) => format!("(... {})", shape(&expr.expr)),
+            Expr::Await(expr) => format!("(await {})", shape(&expr.base)),
+            Expr::Yield(expr) => {
+                let star = if expr.star_token.is_some() { "*" } else { "" };
+                format!("(yield{} {})", star, shape(&expr.expr))
+            }
+            Expr::Paren(expr) => shape(&expr.expr),
+            Expr::Path(expr) => expr.path.to_token_stream().to_string(),
+            Expr::Lit(expr) => expr.lit.to_token_stream().to_string(),
+            _ => "?".to_string(),
+        }
+    }
+
+    fn optional(optional: bool) -> &'static str {
+        if optional {
+            "?"
+        } else {
+            ""
+        }
+    }
+
+    fn parse_shape(source: &str) -> String {
+        shape(&syn::parse_str(source).unwrap())
+    }
+
+    #[test]
+    fn binary_precedence() {
+        assert_eq!(parse_shape("a + b * c"), "(+ a (* b c))");
+        assert_eq!(parse_shape("a * b + c"), "(+ (* a b) c)");
+        assert_eq!(parse_shape("a << b + c"), "(<< a (+ b c))");
+        assert_eq!(parse_shape("a < b << c"), "(< a (<< b c))");
+        assert_eq!(parse_shape("a == b < c"), "(== a (< b c))");
+        assert_eq!(parse_shape("a & b == c"), "(& a (== b c))");
+        assert_eq!(parse_shape("a | b ^ c & d"), "(| a (^ b (& c d)))");
+        assert_eq!(parse_shape("a || b && c | d"), "(|| a (&& b (| c d)))");
+        assert_eq!(parse_shape("a ?? b || c"), "(?? a (|| b c))");
+        assert_eq!(parse_shape("a * b ** c"), "(* a (** b c))");
+        assert_eq!(parse_shape("(a + b) * c"), "(* (+ a b) c)");
+    }
+
+    #[test]
+    fn associativity() {
+        assert_eq!(parse_shape("a - b - c"), "(- (- a b) c)");
+        assert_eq!(parse_shape("a === b !== c"), "(!== (=== a b) c)");
+        assert_eq!(parse_shape("a >>> b >> c"), "(>> (>>> a b) c)");
+        assert_eq!(parse_shape("a ** b ** c"), "(** a (** b c))");
+        assert_eq!(parse_shape("(await a) ** b"), "(** (await a) b)");
+        assert!(syn::parse_str::<Expr>("await a ** b").is_err());
+        assert_eq!(parse_shape("(-a) ** b"), "(** (- a) b)");
+        assert!(syn::parse_str::<Expr>("-a ** b").is_err());
+        assert!(syn::parse_str::<Expr>("a ** -b ** c").is_err());
+        assert_eq!(parse_shape("a = b = c + d"), "(= a (= b (+ c d)))");
+        assert_eq!(parse_shape("a ? b : c ? d : e"), "(? a b (? c d e))");
+        assert_eq!(
+            parse_shape("x = a || b ? c = d : e = f"),
+            "(= x (? (|| a b) (= c d) (= e f)))"
+        );
+    }
+
+    #[test]
+    fn postfix_chains() {
+        assert_eq!(
+            parse_shape("a.b.c(d, e + f)[g](h)"),
+            "(call ([] (.call (. a b) c d (+ e f)) g) h)"
+        );
+        assert_eq!(parse_shape("await a.b() + 1"), "(+ (await (.call a b )) 1)");
+    }
+
+    #[test]
+    fn yield_precedence() {
+        assert_eq!(parse_shape("yield a + b"), "(yield (+ a b))");
+        assert_eq!(parse_shape("x = yield* a"), "(= x (yield* a))");
+        assert_eq!(parse_shape("a ? yield b : c"), "(? a (yield b) c)");
+        assert!(syn::parse_str::<Expr>("a + yield b").is_err());
+    }
+
+    #[test]
+    fn optional_chains() {
+        assert_eq!(
+            parse_shape("a?.b.c?.(d)?.[e]?.f(g) ?? h"),
+            "(?? (?.call (?[] (?call (. (?. a b) c) d) e) f g) h)"
+        );
+        assert_eq!(parse_shape("a ? b : c?.d"), "(? a b (?. c d))");
+
+        let nullish = quote!(::rsscript::types::ops::Nullish::into_option);
+        let value = quote!(__rsscript_value);
+        let chained = |rest: TokenStream| {
+            quote!(|#value| {
+                #[allow(unused_mut)]
+                let mut #value = #value;
+                #[allow(unused_imports)]
+                use ::rsscript::types::ops::{ChainOption as _, ChainValue as _};
+                ::rsscript::types::ops::Chained(::std::option::Option::Some(#rest)).chained()
+            })
+        };
+
+        let expr: Expr = syn::parse_str("a?.b.c?.d(e?.f)").unwrap();
+        let inner = chained(quote!(#value.f));
+        let first = chained(quote!(#value.b.c));
+        let second = chained(quote!(#value.d(#nullish(e).and_then(#inner))));
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!(#nullish(a).and_then(#first).and_then(#second)).to_string()
+        );
+
+        let expr: Expr = syn::parse_str("(a?.b).c ?? d").unwrap();
+        let first = chained(quote!(#value.b));
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!(::rsscript::types::ops::Coalesce::coalesce(
+                (#nullish(a).and_then(#first)).c,
+                || d
+            ))
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn binary_to_rust_code() {
+        let expr: Expr = syn::parse_str("a & b == c").unwrap();
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!(a & (b == c)).to_string()
+        );
+
+        let expr: Expr = syn::parse_str("a ** b === c").unwrap();
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!((::rsscript::types::number::Number::pow(a, b)) == c).to_string()
+        );
+    }
+
+    #[test]
+    fn unary_and_update() {
+        assert_eq!(parse_shape("-a * b"), "(* (- a) b)");
+        assert_eq!(parse_shape("!a && ~b"), "(&& (! a) (~ b))");
+        assert_eq!(parse_shape("typeof a.b == c"), "(== (typeof (. a b)) c)");
+        assert_eq!(parse_shape("void delete a[b]"), "(void (delete ([] a b)))");
+        assert_eq!(parse_shape("a - -b"), "(- a (- b))");
+        assert_eq!(parse_shape("a++ + ++b"), "(+ (_++ a) (++_ b))");
+        assert_eq!(parse_shape("-a.b--"), "(- (_-- (. a b)))");
+    }
+
+    #[test]
+    fn compound_assignment() {
+        assert_eq!(parse_shape("a += b * c"), "(+= a (* b c))");
+        assert_eq!(parse_shape("a **= b >>>= c"), "(**= a (>>>= b c))");
+        assert_eq!(parse_shape("a ??= b || c"), "(??= a (|| b c))");
+        assert_eq!(parse_shape("a ||= b &&= c"), "(||= a (&&= b c))");
+        assert_eq!(parse_shape("a <<= b = c"), "(<<= a (= b c))");
+    }
+
+    #[test]
+    fn update_to_rust_code() {
+        let expr: Expr = syn::parse_str("i++").unwrap();
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!({
+                let __rsscript_place = &mut i;
+                let __rsscript_old = *__rsscript_place;
+                *__rsscript_place += ::rsscript::types::number::Number::from(1f64);
+                __rsscript_old
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn spread() {
+        assert_eq!(
+            parse_shape("f(a, ...b.c, ...d ?? e)"),
+            "(call f a (... (. b c)) (... (?? d e)))"
+        );
+
+        let expr: Expr = syn::parse_str("[...a, 1]").unwrap();
+        let one = quote!(::rsscript::types::number::Number::from(1f64));
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!(::std::iter::Iterator::collect::<::std::vec::Vec<_>>(
+                ::std::iter::Iterator::chain(
+                    ::std::iter::Iterator::chain(
+                        ::std::iter::empty(),
+                        ::std::iter::Iterator::cloned(::std::iter::IntoIterator::into_iter(&(a)))
+                    ),
+                    ::std::iter::once(#one)
+                )
+            ))
+            .to_string()
+        );
+
+        let expr: Expr = syn::parse_str("f(...a)").unwrap();
+        assert!(expr
+            .to_rust_token_stream()
+            .to_string()
+            .contains("compile_error"));
+
+        // An object of a class that is not known is cloned.
+        let expr: Expr = syn::parse_str("{ ...a, b: 1 }").unwrap();
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!({
+                #[allow(unused_mut)]
+                let mut __rsscript_object = ::std::clone::Clone::clone(&(a));
+                __rsscript_object.b = #one;
+                __rsscript_object
+            })
+            .to_string()
+        );
+
+        let expr: Expr = syn::parse_str("{ b: 1, ...a }").unwrap();
+        assert!(expr
+            .to_rust_token_stream()
+            .to_string()
+            .contains("a spread must be the first member of an object literal"));
+    }
+
+    #[test]
+    fn object_literal() {
+        let expr: Expr = syn::parse_str(
+            "{ x: 1, y, get double(): Number { return this.x * 2; }, reset() { this.x = 0; } }",
+        )
+        .unwrap();
+        let (one, zero, two) = (
+            quote!(::rsscript::types::number::Number::from(1f64)),
+            quote!(::rsscript::types::number::Number::from(0f64)),
+            quote!(::rsscript::types::number::Number::from(2f64)),
+        );
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!({
+                #[allow(non_camel_case_types, dead_code)]
+                #[derive(Clone, Debug)]
+                struct __rsscript_Object_x_y_get_double_reset<__rsscript_T0, __rsscript_T1> {
+                    x: __rsscript_T0,
+                    y: __rsscript_T1,
+                    __rsscript_get_double: fn(&mut Self,) -> Number,
+                    __rsscript_reset: fn(&mut Self,) -> (),
+                }
+
+                #[allow(dead_code)]
+                impl<__rsscript_T0, __rsscript_T1>
+                    __rsscript_Object_x_y_get_double_reset<__rsscript_T0, __rsscript_T1>
+                {
+                    pub fn double(&mut self,) -> Number {
+                        (self.__rsscript_get_double)(self,)
+                    }
+                    pub fn reset(&mut self,) -> () {
+                        (self.__rsscript_reset)(self,)
+                    }
+                }
+
+                __rsscript_Object_x_y_get_double_reset {
+                    x: #one,
+                    y: y,
+                    __rsscript_get_double: |__rsscript_this,| -> Number {
+                        return __rsscript_this.x * #two;
+                    },
+                    __rsscript_reset: |__rsscript_this,| -> () {
+                        __rsscript_this.x = #zero;
+                    },
+                }
+            })
+            .to_string()
+        );
+
+        for (source, error) in [
+            ("{ #x: 1 }", "private names are only allowed in classes"),
+            (
+                "{ *m() {} }",
+                "the methods of an object literal cannot be generators",
+            ),
+            (
+                "{ m<T>() {} }",
+                "the methods of an object literal cannot be generic",
+            ),
+            (
+                "{ ...a, m() {} }",
+                "an object literal spreading another object can only set fields",
+            ),
+        ] {
+            let expr: Expr = syn::parse_str(source).unwrap();
+            assert!(
+                expr.to_rust_token_stream().to_string().contains(error),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn new_expression() {
+        assert_eq!(parse_shape("new Point(1, 2).x"), "(. (new Point 1 2) x)");
+        assert_eq!(parse_shape("old + new"), "(+ old new)");
+
+        let expr: Expr = syn::parse_str("new Box<T>(a)").unwrap();
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!(<Box<T> as ::rsscript::types::Constructor<fn(_) -> _>>::constructor(a))
+                .to_string()
+        );
+    }
+}
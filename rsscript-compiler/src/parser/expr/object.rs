@@ -1,16 +1,29 @@
-use syn::{braced, parse::Parse, punctuated::Punctuated, token::Brace, Ident};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{
+    braced, parse::Parse, parse_quote, punctuated::Punctuated, token::Brace, Ident, Path,
+    PathArguments, Type,
+};
 
 use crate::{
+    encode::{
+        self,
+        context::{self, ThrowTarget},
+        scope, ToRustCode,
+    },
     enum_impl,
     parser::{
+        expr::{ExprArrayExpand, ExprPath},
         generics::Generics,
         item::{FnArgs, TypeAnnotation},
         stmt::Block,
+        token::IdentPeeker,
         Expr,
     },
     Token,
 };
 
+#[derive(Debug)]
 pub struct ObjectIdent {
     pub sharp_token: Option<Token![#]>,
     pub ident: Ident,
@@ -29,7 +42,9 @@ impl Parse for ObjectIdent {
     }
 }
 
+#[derive(Debug)]
 pub struct ObjectMethod {
+    pub star_token: Option<Token![*]>,
     pub ident: ObjectIdent,
     pub generics: Generics,
     pub inputs: FnArgs,
@@ -40,6 +55,7 @@ pub struct ObjectMethod {
 impl Parse for ObjectMethod {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         Ok(Self {
+            star_token: input.parse()?,
             ident: input.parse()?,
             generics: input.parse()?,
             inputs: input.parse()?,
@@ -53,6 +69,72 @@ impl Parse for ObjectMethod {
     }
 }
 
+#[derive(Debug)]
+pub enum AccessorKind {
+    Get(Token![get]),
+    Set(Token![set]),
+}
+
+impl Parse for AccessorKind {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.ipeek::<Token![get]>() {
+            Ok(Self::Get(input.parse()?))
+        } else if input.ipeek::<Token![set]>() {
+            Ok(Self::Set(input.parse()?))
+        } else {
+            Err(input.error("expected `get` or `set`"))
+        }
+    }
+}
+
+// `get name() {}` or `set name(value) {}`. A method named `get` or `set` is not an accessor, as
+// the name is followed by its parameters.
+#[derive(Debug)]
+pub struct ObjectAccessor {
+    pub kind: AccessorKind,
+    pub method: ObjectMethod,
+}
+
+impl Parse for ObjectAccessor {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            kind: input.parse()?,
+            method: input.parse()?,
+        })
+    }
+}
+
+impl ObjectAccessor {
+    pub fn is_getter(&self) -> bool {
+        matches!(self.kind, AccessorKind::Get(_))
+    }
+
+    pub fn check(&self) -> syn::Result<()> {
+        if let Some(star_token) = &self.method.star_token {
+            return Err(syn::Error::new(
+                star_token.spans[0],
+                "an accessor cannot be a generator",
+            ));
+        }
+
+        let inputs = &self.method.inputs;
+        let params = inputs.params.len();
+
+        match &self.kind {
+            AccessorKind::Get(_) if params != 0 => Err(syn::Error::new(
+                inputs.paren_token.span.join(),
+                "a getter cannot have parameters",
+            )),
+            AccessorKind::Set(_) if params != 1 || inputs.has_rest() => Err(syn::Error::new(
+                inputs.paren_token.span.join(),
+                "a setter must have exactly one parameter",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ObjectField {
     pub ident: ObjectIdent,
     pub init: Option<(Token![:], Expr)>,
@@ -71,13 +153,46 @@ impl Parse for ObjectField {
     }
 }
 
+impl ObjectField {
+    // `{ name }` is short for `{ name: name }`.
+    fn value_code(&self) -> TokenStream {
+        match &self.init {
+            Some((_, expr)) => expr.to_rust_token_stream(),
+            None => Expr::Path(ExprPath {
+                path: self.ident.ident.clone().into(),
+            })
+            .to_rust_token_stream(),
+        }
+    }
+}
+
 enum_impl! {
     pub enum ObjectStmt {
+        Spread(ExprArrayExpand),
+        Accessor(ObjectAccessor),
         ObjectMethod(ObjectMethod),
         Field(ObjectField),
     }
 }
 
+impl ObjectStmt {
+    fn ident(&self) -> Option<&ObjectIdent> {
+        match self {
+            Self::Spread(_) => None,
+            Self::Accessor(accessor) => Some(&accessor.method.ident),
+            Self::ObjectMethod(method) => Some(&method.ident),
+            Self::Field(field) => Some(&field.ident),
+        }
+    }
+}
+
+// An object literal spreading an object of a class, `{ ...value, a: 1 }`, is a copy of it with its
+// own fields set, and one given the type of a class, `const p: Point = { x: 1 }`, sets those fields
+// and leaves the others to their default. Any other object literal is an object of a struct of its
+// own, declared where it is created, whose fields take the type of their value. Its methods and
+// accessors are function pointers stored in the object, which are called with the object for
+// `this` and so cannot use the variables around the literal.
+#[derive(Debug)]
 pub struct ExprObject {
     pub brace_token: Brace,
     pub fields: Punctuated<ObjectStmt, Token![,]>,
@@ -92,3 +207,299 @@ impl Parse for ExprObject {
         })
     }
 }
+
+impl ExprObject {
+    fn spread(&self) -> Option<&ExprArrayExpand> {
+        match self.fields.first() {
+            Some(ObjectStmt::Spread(spread)) => Some(spread),
+            _ => None,
+        }
+    }
+
+    fn members(&self) -> impl Iterator<Item = &ObjectStmt> {
+        self.fields
+            .iter()
+            .filter(|stmt| !matches!(stmt, ObjectStmt::Spread(_)))
+    }
+
+    // The struct of an object literal that neither spreads another object nor is given a type,
+    // which is named after its members.
+    pub fn struct_ident(&self) -> Ident {
+        let mut name = "Object".to_string();
+        for stmt in self.members() {
+            let prefix = match stmt {
+                ObjectStmt::Accessor(accessor) if accessor.is_getter() => "get_",
+                ObjectStmt::Accessor(_) => "set_",
+                _ => "",
+            };
+            let ident = &stmt.ident().unwrap().ident;
+            name.push_str(&format!("_{}{}", prefix, ident));
+        }
+        encode::private_ident(&name)
+    }
+
+    // The class, or struct, of the object this literal gives, unless it spreads an object whose
+    // class is not known.
+    pub(crate) fn class(&self) -> Option<Ident> {
+        match self.spread() {
+            Some(spread) => spread.expr.class(),
+            None => Some(self.struct_ident()),
+        }
+    }
+
+    // The accessors of the struct of the object, known as `Struct.member`.
+    pub fn accessors(&self) -> Vec<(String, scope::Accessor)> {
+        if self.spread().is_some() {
+            return Vec::new();
+        }
+        let ident = self.struct_ident();
+        self.members()
+            .filter_map(|stmt| match stmt {
+                ObjectStmt::Accessor(accessor) => Some((
+                    format!("{}.{}", ident, accessor.method.ident.ident),
+                    scope::Accessor {
+                        getter: accessor.is_getter(),
+                        setter: !accessor.is_getter(),
+                        is_static: false,
+                    },
+                )),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn check(&self) -> syn::Result<()> {
+        for (i, stmt) in self.fields.iter().enumerate() {
+            if let (ObjectStmt::Spread(spread), 1..) = (stmt, i) {
+                return Err(syn::Error::new(
+                    spread.colons.spans[0],
+                    "a spread must be the first member of an object literal",
+                ));
+            }
+            if let Some(sharp_token) = stmt.ident().and_then(|ident| ident.sharp_token) {
+                return Err(syn::Error::new(
+                    sharp_token.span,
+                    "private names are only allowed in classes",
+                ));
+            }
+            let method = match stmt {
+                ObjectStmt::Accessor(accessor) => {
+                    accessor.check()?;
+                    &accessor.method
+                }
+                ObjectStmt::ObjectMethod(method) => method,
+                _ => continue,
+            };
+            if let Some(star_token) = &method.star_token {
+                return Err(syn::Error::new(
+                    star_token.spans[0],
+                    "the methods of an object literal cannot be generators",
+                ));
+            }
+            if let Some(lt_token) = &method.generics.lt_token {
+                return Err(syn::Error::new(
+                    lt_token.span,
+                    "the methods of an object literal cannot be generic",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Only fields can be set on an object of a class.
+    fn fields_only(&self, what: &str) -> syn::Result<Vec<&ObjectField>> {
+        self.members()
+            .map(|stmt| match stmt {
+                ObjectStmt::Field(field) => Ok(field),
+                stmt => Err(syn::Error::new(
+                    stmt.ident().unwrap().ident.span(),
+                    format!("an object literal {} can only set fields", what),
+                )),
+            })
+            .collect()
+    }
+
+    // Lowers the literal as the initial value of a local annotated with `ty`.
+    pub fn to_typed_code(&self, ty: Option<&Type>, tokens: &mut TokenStream) {
+        let code = self.check().and_then(|_| match (self.spread(), ty) {
+            (Some(spread), ty) => self.spread_code(spread, ty),
+            (None, Some(Type::Path(ty))) if ty.qself.is_none() => self.typed_code(&ty.path),
+            (None, _) => self.struct_code(),
+        });
+        tokens.extend(code.unwrap_or_else(syn::Error::into_compile_error));
+    }
+
+    // An object of a class is copied through its rest when the class of the spread object is
+    // known, and anything else is cloned.
+    fn spread_code(&self, spread: &ExprArrayExpand, ty: Option<&Type>) -> syn::Result<TokenStream> {
+        let runtime = encode::runtime();
+        let object = encode::private_ident("object");
+        let fields = self.fields_only("spreading another object")?;
+
+        let source = spread.expr.to_rust_token_stream();
+        let class = spread.expr.class();
+        let copy = match class.as_ref().and_then(scope::class) {
+            Some(_) => quote!(#runtime::class::spread(&(#source))),
+            None => quote!(::std::clone::Clone::clone(&(#source))),
+        };
+        let sets = fields.iter().map(|field| {
+            let name = &field.ident.ident;
+            let value = field.value_code();
+            let setter = class
+                .as_ref()
+                .and_then(|class| scope::member_key(class, &name.to_string()))
+                .and_then(|key| scope::accessor(&key))
+                .is_some_and(|accessor| accessor.setter && !accessor.is_static);
+            if setter {
+                let setter = format_ident!("set_{}", name);
+                quote!(#object.#setter(#value);)
+            } else {
+                quote!(#object.#name = #value;)
+            }
+        });
+        let ty = ty.map(|ty| quote!(: #ty));
+
+        Ok(quote! {{
+            #[allow(unused_mut)]
+            let mut #object #ty = #copy;
+            #(#sets)*
+            #object
+        }})
+    }
+
+    // The fields an object of a class is not given are left to their default, and an object of
+    // an interface has to be created from a class implementing it.
+    fn typed_code(&self, path: &Path) -> syn::Result<TokenStream> {
+        let ident = &path.segments.last().unwrap().ident;
+        if scope::interface(ident).is_some() {
+            return Err(syn::Error::new(
+                ident.span(),
+                format!(
+                    "an object of interface `{}` has to be created from a class implementing it",
+                    ident
+                ),
+            ));
+        }
+
+        // The generic arguments are inferred from the type of the local.
+        let mut path = path.clone();
+        for segment in &mut path.segments {
+            segment.arguments = PathArguments::None;
+        }
+        let fields = self.fields_only(&format!("of type `{}`", ident))?;
+        let fields = fields.iter().map(|field| {
+            let name = &field.ident.ident;
+            let value = field.value_code();
+            quote!(#name: #value,)
+        });
+        let rest = scope::class(ident).map(|_| quote!(..::std::default::Default::default()));
+
+        Ok(quote!(#path { #(#fields)* #rest }))
+    }
+
+    fn struct_code(&self) -> syn::Result<TokenStream> {
+        let ident = self.struct_ident();
+        let this = encode::private_ident("this");
+
+        let mut params = Vec::new();
+        let mut fields = TokenStream::new();
+        let mut methods = TokenStream::new();
+        let mut values = TokenStream::new();
+        let mut fns = TokenStream::new();
+        let accessors = self.accessors();
+        for stmt in self.members() {
+            let (name, method, setter) = match stmt {
+                ObjectStmt::Field(field) => {
+                    let name = &field.ident.ident;
+                    let param = encode::private_ident(&format!("T{}", params.len()));
+                    let value = field.value_code();
+                    fields.extend(quote!(#name: #param,));
+                    values.extend(quote!(#name: #value,));
+                    params.push(param);
+                    continue;
+                }
+                ObjectStmt::Accessor(accessor) if accessor.is_getter() => {
+                    (&accessor.method.ident.ident, &accessor.method, false)
+                }
+                ObjectStmt::Accessor(accessor) => {
+                    (&accessor.method.ident.ident, &accessor.method, true)
+                }
+                ObjectStmt::ObjectMethod(method) => (&method.ident.ident, method, false),
+                ObjectStmt::Spread(_) => unreachable!(),
+            };
+
+            let (method_ident, fn_field) = match (stmt, setter) {
+                (ObjectStmt::Accessor(_), false) => (
+                    name.clone(),
+                    encode::private_ident(&format!("get_{}", name)),
+                ),
+                (ObjectStmt::Accessor(_), true) => (
+                    format_ident!("set_{}", name),
+                    encode::private_ident(&format!("set_{}", name)),
+                ),
+                _ => (name.clone(), encode::private_ident(&name.to_string())),
+            };
+            let param_tys = method.inputs.rust_types();
+            let params_code = method.inputs.to_params_code();
+            let args = (method.inputs.params.iter().enumerate()).map(|(i, param)| param.ident(i));
+            let output = method
+                .outputs
+                .as_ref()
+                .map_or_else(|| quote!(()), ToRustCode::to_rust_token_stream);
+
+            fields.extend(quote!(#fn_field: fn(&mut Self, #(#param_tys),*) -> #output,));
+            methods.extend(quote! {
+                pub fn #method_ident(&mut self, #params_code) -> #output {
+                    (self.#fn_field)(self, #(#args),*)
+                }
+            });
+
+            let body = scope::with_accessors(accessors.clone(), || {
+                method_body_code(&ident, &this, &method.inputs, &method.body)
+            });
+            fns.extend(quote!(#fn_field: |#this, #params_code| -> #output #body,));
+        }
+
+        Ok(quote! {{
+            #[allow(non_camel_case_types, dead_code)]
+            #[derive(Clone, Debug)]
+            struct #ident<#(#params),*> {
+                #fields
+            }
+
+            #[allow(dead_code)]
+            impl<#(#params),*> #ident<#(#params),*> {
+                #methods
+            }
+
+            #ident {
+                #values
+                #fns
+            }
+        }})
+    }
+}
+
+// `this` in the body of a method of an object literal is the object it is called with.
+fn method_body_code(ident: &Ident, this: &Ident, inputs: &FnArgs, body: &Block) -> TokenStream {
+    let target = ThrowTarget::Nowhere(
+        "errors cannot be thrown out of the methods of an object literal, catch them in its body",
+    );
+    let this_var = Ident::new("this", Span::call_site());
+    let (body, _) = context::with_throw_target(target, || {
+        scope::with_this(Some(this.clone()), || {
+            scope::with_variables([(this_var, Some(parse_quote!(#ident)))], || {
+                let mut tokens = TokenStream::new();
+                inputs.to_body_code(body, &mut tokens);
+                tokens
+            })
+        })
+    });
+    body
+}
+
+impl ToRustCode for ExprObject {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.to_typed_code(None, tokens);
+    }
+}
@@ -0,0 +1,368 @@
+use syn::{
+    bracketed, parenthesized,
+    parse::ParseStream,
+    punctuated::Punctuated,
+    token::{Brace, Bracket, Paren},
+    Member,
+};
+
+use crate::{
+    parser::{
+        expr::{
+            template::{ExprTaggedTemplate, ExprTemplate},
+            ArrowFunctionArgs, AssignOp, BinOp, Expr, ExprArrowFunction, ExprAssign, ExprAssignOp,
+            ExprBinary, ExprCall, ExprField, ExprIf, ExprIndex, ExprMethodCall, ExprUnary,
+            ExprUpdate, UnOp, UpdateOp,
+        },
+        item::TypeAnnotation,
+        token::IdentPeeker,
+    },
+    Token,
+};
+
+// JavaScript operator precedence, from the loosest binding to the tightest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precedence {
+    Assign,
+    Ternary,
+    Coalesce,
+    Or,
+    And,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Equality,
+    Relational,
+    Shift,
+    Additive,
+    Multiplicative,
+    Exponent,
+    Unary,
+}
+
+impl Precedence {
+    fn next(self) -> Self {
+        match self {
+            Self::Assign => Self::Ternary,
+            Self::Ternary => Self::Coalesce,
+            Self::Coalesce => Self::Or,
+            Self::Or => Self::And,
+            Self::And => Self::BitOr,
+            Self::BitOr => Self::BitXor,
+            Self::BitXor => Self::BitAnd,
+            Self::BitAnd => Self::Equality,
+            Self::Equality => Self::Relational,
+            Self::Relational => Self::Shift,
+            Self::Shift => Self::Additive,
+            Self::Additive => Self::Multiplicative,
+            Self::Multiplicative => Self::Exponent,
+            Self::Exponent | Self::Unary => Self::Unary,
+        }
+    }
+
+    fn is_right_associative(self) -> bool {
+        matches!(self, Self::Assign | Self::Ternary | Self::Exponent)
+    }
+}
+
+// Compound assignments are not binary operators.
+pub fn of_rust_op(op: &syn::BinOp) -> Option<Precedence> {
+    use syn::BinOp::*;
+
+    Some(match op {
+        Add(_) | Sub(_) => Precedence::Additive,
+        Mul(_) | Div(_) | Rem(_) => Precedence::Multiplicative,
+        And(_) => Precedence::And,
+        Or(_) => Precedence::Or,
+        BitXor(_) => Precedence::BitXor,
+        BitAnd(_) => Precedence::BitAnd,
+        BitOr(_) => Precedence::BitOr,
+        Shl(_) | Shr(_) => Precedence::Shift,
+        Eq(_) | Ne(_) => Precedence::Equality,
+        Lt(_) | Le(_) | Gt(_) | Ge(_) => Precedence::Relational,
+        _ => return None,
+    })
+}
+
+fn peek_assign(input: ParseStream) -> bool {
+    input.peek(Token![=]) && !input.peek(Token![==]) && !input.peek(Token![=>])
+}
+
+fn peek_assign_op(input: ParseStream) -> bool {
+    input.fork().parse::<AssignOp>().is_ok()
+}
+
+// `?` starts a conditional expression unless it is part of `??` or `?.`.
+fn peek_ternary(input: ParseStream) -> bool {
+    input.peek(Token![?]) && !input.peek2(Token![?]) && !input.peek2(Token![.])
+}
+
+fn peek_bin_op(input: ParseStream) -> Option<Precedence> {
+    input.fork().parse::<BinOp>().ok().map(|op| op.precedence())
+}
+
+// Parses an expression made of operators binding at least as tightly as `min`.
+pub fn parse_expr(input: ParseStream, min: Precedence) -> syn::Result<Expr> {
+    // `yield` takes an assignment expression, so it can only be where one can.
+    if input.peek(Token![yield]) {
+        if min > Precedence::Assign {
+            return Err(input.error("a `yield` expression must be parenthesized here"));
+        }
+        return Ok(Expr::Yield(input.parse()?));
+    }
+
+    let mut expr = parse_unary(input)?;
+
+    loop {
+        let precedence = if peek_assign(input) || peek_assign_op(input) {
+            Precedence::Assign
+        } else if peek_ternary(input) {
+            Precedence::Ternary
+        } else if let Some(precedence) = peek_bin_op(input) {
+            precedence
+        } else {
+            break;
+        };
+
+        if precedence < min {
+            break;
+        }
+        // `-a ** b` is ambiguous, so JavaScript requires the unary operand to be parenthesized.
+        if precedence == Precedence::Exponent && matches!(expr, Expr::Unary(_) | Expr::Await(_)) {
+            return Err(input
+                .error("the left operand of `**` cannot be a unary expression, parenthesize it"));
+        }
+        let right_min = if precedence.is_right_associative() {
+            precedence
+        } else {
+            precedence.next()
+        };
+
+        expr = match precedence {
+            Precedence::Assign if peek_assign(input) => Expr::Assign(ExprAssign {
+                left: Box::new(expr),
+                eq_token: input.parse()?,
+                right: Box::new(parse_expr(input, right_min)?),
+            }),
+            Precedence::Assign => Expr::AssignOp(ExprAssignOp {
+                left: Box::new(expr),
+                op: input.parse()?,
+                right: Box::new(parse_expr(input, right_min)?),
+            }),
+            Precedence::Ternary => Expr::If(ExprIf {
+                cond: Box::new(expr),
+                question_token: input.parse()?,
+                then_branch: Box::new(parse_expr(input, Precedence::Assign)?),
+                colon_token: input.parse()?,
+                else_branch: Box::new(parse_expr(input, Precedence::Assign)?),
+            }),
+            _ => Expr::Binary(ExprBinary {
+                left: Box::new(expr),
+                op: input.parse()?,
+                right: Box::new(parse_expr(input, right_min)?),
+            }),
+        };
+    }
+
+    Ok(expr)
+}
+
+pub fn parse_unary(input: ParseStream) -> syn::Result<Expr> {
+    if input.peek(Token![await]) {
+        return Ok(Expr::Await(input.parse()?));
+    }
+    if UpdateOp::peek(input, '+') || UpdateOp::peek(input, '-') {
+        return Ok(Expr::Update(ExprUpdate {
+            op: input.parse()?,
+            prefix: true,
+            expr: Box::new(parse_unary(input)?),
+        }));
+    }
+    if UnOp::peek(input) {
+        return Ok(Expr::Unary(ExprUnary {
+            op: input.parse()?,
+            expr: Box::new(parse_unary(input)?),
+        }));
+    }
+
+    let expr = parse_primary(input)?;
+    parse_postfix(input, expr)
+}
+
+fn parse_postfix(input: ParseStream, mut expr: Expr) -> syn::Result<Expr> {
+    loop {
+        if input.peek(Token![?]) && input.peek2(Token![.]) || input.peek(Token![.]) {
+            if input.peek(Token![..]) {
+                break;
+            }
+
+            let optional: Option<Token![?]> = input.parse()?;
+            let dot_token = input.parse()?;
+
+            // `?.(` and `?.[` call and index a value that may be nullish.
+            if let Some(question_token) = optional {
+                if input.peek(Paren) {
+                    expr = parse_call(input, expr, Some((question_token, dot_token)))?;
+                    continue;
+                } else if input.peek(Bracket) {
+                    expr = parse_index(input, expr, Some((question_token, dot_token)))?;
+                    continue;
+                }
+            }
+
+            let member: Member = input.parse()?;
+            expr = match member {
+                Member::Named(method)
+                    if input.peek(Paren) || input.peek(Token![::]) && input.peek3(Token![<]) =>
+                {
+                    let turbofish = if input.peek(Token![::]) {
+                        Some(input.parse()?)
+                    } else {
+                        None
+                    };
+                    let content;
+                    let paren_token = parenthesized!(content in input);
+
+                    Expr::MethodCall(ExprMethodCall {
+                        receiver: Box::new(expr),
+                        optional,
+                        dot_token,
+                        method,
+                        turbofish,
+                        paren_token,
+                        args: parse_args(&content)?,
+                    })
+                }
+                member => Expr::Field(ExprField {
+                    base: Box::new(expr),
+                    optional,
+                    dot_token,
+                    member,
+                }),
+            };
+        } else if input.peek(Paren) {
+            expr = parse_call(input, expr, None)?;
+        } else if input.peek(Bracket) {
+            expr = parse_index(input, expr, None)?;
+        } else if ExprTemplate::peek(input) {
+            expr = Expr::TaggedTemplate(ExprTaggedTemplate {
+                tag: Box::new(expr),
+                template: input.parse()?,
+            });
+        } else if UpdateOp::peek(input, '+') || UpdateOp::peek(input, '-') {
+            expr = Expr::Update(ExprUpdate {
+                op: input.parse()?,
+                prefix: false,
+                expr: Box::new(expr),
+            });
+            break;
+        } else {
+            break;
+        }
+    }
+
+    Ok(expr)
+}
+
+fn parse_call(
+    input: ParseStream,
+    func: Expr,
+    optional: Option<(Token![?], Token![.])>,
+) -> syn::Result<Expr> {
+    let content;
+    let paren_token = parenthesized!(content in input);
+
+    Ok(Expr::Call(ExprCall {
+        func: Box::new(func),
+        optional,
+        paren_token,
+        args: parse_args(&content)?,
+    }))
+}
+
+fn parse_index(
+    input: ParseStream,
+    base: Expr,
+    optional: Option<(Token![?], Token![.])>,
+) -> syn::Result<Expr> {
+    let content;
+    let bracket_token = bracketed!(content in input);
+
+    Ok(Expr::Index(ExprIndex {
+        base: Box::new(base),
+        optional,
+        bracket_token,
+        index: content.parse()?,
+    }))
+}
+
+// `(a, b) => ..` and `(a): T => ..` cannot be told apart from a parenthesized expression before
+// the arrow.
+fn peek_arrow_function(input: ParseStream) -> bool {
+    let fork = input.fork();
+    if fork.parse::<ArrowFunctionArgs>().is_err() {
+        return false;
+    }
+    if fork.peek(Token![:]) && fork.parse::<TypeAnnotation>().is_err() {
+        return false;
+    }
+    fork.peek(Token![=>])
+}
+
+fn parse_primary(input: ParseStream) -> syn::Result<Expr> {
+    if input.peek(syn::Lit) {
+        Ok(Expr::Lit(input.parse()?))
+    } else if ExprTemplate::peek(input) {
+        Ok(Expr::Template(input.parse()?))
+    } else if input.peek(Bracket) {
+        Ok(Expr::Array(input.parse()?))
+    } else if input.peek(Paren) {
+        if peek_arrow_function(input) {
+            Ok(Expr::ArrowFunction(input.parse::<ExprArrowFunction>()?))
+        } else {
+            Ok(Expr::Paren(input.parse()?))
+        }
+    } else if input.ipeek::<Token![rust]>() {
+        Ok(Expr::Rust(input.parse()?))
+    } else if input.ipeek::<Token![new]>() && (input.peek2(syn::Ident) || input.peek2(Token![::])) {
+        Ok(Expr::New(input.parse()?))
+    } else if input.ipeek::<Token![this]>() {
+        Ok(Expr::This(input.parse()?))
+    } else if input.peek(Token![super]) {
+        Ok(Expr::Super(input.parse()?))
+    } else if input.peek(Token![_]) {
+        Ok(Expr::Infer(input.parse()?))
+    } else if input.ipeek::<Token![function]>()
+        || input.peek(Token![async]) && input.ipeekn::<Token![function]>(2)
+    {
+        Ok(Expr::Function(input.parse()?))
+    } else if input.peek(syn::Ident) || input.peek(Token![::]) {
+        Ok(Expr::Path(input.parse()?))
+    } else if input.peek(Brace) {
+        Ok(Expr::Object(input.parse()?))
+    } else {
+        Err(input.error("expected an expression"))
+    }
+}
+
+pub fn parse_args(input: ParseStream) -> syn::Result<Punctuated<Expr, Token![,]>> {
+    let mut args = Punctuated::new();
+
+    while !input.is_empty() {
+        let arg = if input.peek(Token![...]) {
+            Expr::ArrayExpand(input.parse()?)
+        } else {
+            input.parse()?
+        };
+        args.push_value(arg);
+
+        if input.is_empty() {
+            break;
+        }
+
+        let punct = input.parse()?;
+        args.push_punct(punct);
+    }
+
+    Ok(args)
+}
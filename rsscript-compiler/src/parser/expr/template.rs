@@ -0,0 +1,430 @@
+use std::{
+    iter::{Enumerate, Peekable},
+    str::Chars,
+};
+
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned};
+use syn::{parse::Parse, LitStr};
+
+use crate::{
+    encode::{self, ToRustCode},
+    parser::Expr,
+    Token,
+};
+
+// Backticks are not Rust tokens, so a template literal is written as a string literal marked
+// with `$`: `$"Hello, ${name}!"`. Inside the string, `${` starts an interpolation, which can be
+// escaped as `\u{24}{`. Script files can use backticks, which `desugar` rewrites to this form.
+#[derive(Debug)]
+pub struct ExprTemplate {
+    pub dollar_token: Token![$],
+    pub lit: LitStr,
+    pub quasis: Vec<String>,
+    pub exprs: Vec<Expr>,
+}
+
+impl Parse for ExprTemplate {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let dollar_token = input.parse()?;
+        let lit: LitStr = input.parse()?;
+        let (quasis, exprs) = split(&lit)?;
+
+        Ok(Self {
+            dollar_token,
+            lit,
+            quasis,
+            exprs,
+        })
+    }
+}
+
+impl ExprTemplate {
+    pub fn peek(input: syn::parse::ParseStream) -> bool {
+        input.peek(Token![$]) && input.peek2(LitStr)
+    }
+
+    fn quasi_code(&self, quasi: &str) -> TokenStream {
+        let runtime = encode::runtime();
+        let span = self.lit.span();
+        quote_spanned! {span=>
+            #runtime::string::JsString::new(::std::string::String::from(#quasi))
+        }
+    }
+}
+
+impl ToRustCode for ExprTemplate {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let mut code = self.quasi_code(&self.quasis[0]);
+
+        for (expr, quasi) in self.exprs.iter().zip(&self.quasis[1..]) {
+            let expr = expr.to_rust_token_stream();
+            code.extend(quote!(+ #runtime::JsValue::toString(&#expr)));
+            if !quasi.is_empty() {
+                let quasi = self.quasi_code(quasi);
+                code.extend(quote!(+ #quasi));
+            }
+        }
+
+        tokens.extend(quote!((#code)));
+    }
+}
+
+// `tag$"a${b}c"` calls `tag(vec!["a", "c"], b)`, like `` tag`a${b}c` `` in JavaScript.
+#[derive(Debug)]
+pub struct ExprTaggedTemplate {
+    pub tag: Box<Expr>,
+    pub template: ExprTemplate,
+}
+
+impl ToRustCode for ExprTaggedTemplate {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let mut tag = TokenStream::new();
+        self.tag.to_operand_code(&mut tag);
+        let quasis = self
+            .template
+            .quasis
+            .iter()
+            .map(|quasi| self.template.quasi_code(quasi));
+        let exprs = self
+            .template
+            .exprs
+            .iter()
+            .map(ToRustCode::to_rust_token_stream);
+
+        tokens.extend(quote!(#tag(::std::vec![#(#quasis),*], #(#exprs),*)));
+    }
+}
+
+// Splits the literal into its cooked string parts and the expressions between them. This works on
+// the literal as written, so that an escaped `$` does not start an interpolation.
+fn split(lit: &LitStr) -> syn::Result<(Vec<String>, Vec<Expr>)> {
+    let repr = lit.token().to_string();
+    let Some(body) = repr
+        .strip_prefix('"')
+        .and_then(|repr| repr.strip_suffix('"'))
+    else {
+        return Err(syn::Error::new(
+            lit.span(),
+            "a template must be a plain string literal",
+        ));
+    };
+    let cook = |raw: &str, span: Span| {
+        syn::parse_str::<LitStr>(&format!("\"{}\"", raw))
+            .map(|lit| lit.value())
+            .map_err(|err| syn::Error::new(span, err))
+    };
+
+    let mut quasis = Vec::new();
+    let mut exprs = Vec::new();
+    let mut quasi = String::new();
+    let mut chars = body.chars().enumerate().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            '\\' => {
+                quasi.push(c);
+                quasi.extend(chars.next().map(|(_, c)| c));
+            }
+            '$' if chars.peek().map(|(_, c)| *c) == Some('{') => {
+                chars.next();
+                quasis.push(cook(&quasi, lit.span())?);
+                quasi.clear();
+
+                let mut raw = String::new();
+                let mut depth = 0;
+                let mut quoted = None;
+                let end = loop {
+                    let Some((end, unit, c)) = next_unit(&mut chars) else {
+                        return Err(syn::Error::new(lit.span(), "unclosed `${` in template"));
+                    };
+                    // Braces within strings and char literals of the code do not count.
+                    match (quoted, c) {
+                        (Some(_), Some('\\')) => {
+                            raw.push_str(&unit);
+                            raw.extend(next_unit(&mut chars).map(|(_, unit, _)| unit));
+                            continue;
+                        }
+                        (Some(quote), Some(c)) if c == quote => quoted = None,
+                        (Some(_), _) => {}
+                        (None, Some('"')) => quoted = Some('"'),
+                        (None, Some('\'')) if is_quoted_char(&chars) => quoted = Some('\''),
+                        (None, Some('{')) => depth += 1,
+                        (None, Some('}')) if depth == 0 => break end,
+                        (None, Some('}')) => depth -= 1,
+                        _ => {}
+                    }
+                    raw.push_str(&unit);
+                };
+
+                // The code of the interpolation takes its place in the literal, skipping the
+                // opening quote, where spans within literals are known.
+                let span = lit
+                    .token()
+                    .subspan(start + 1..end + 2)
+                    .unwrap_or_else(|| lit.span());
+                let source: TokenStream = cook(&raw, span)?
+                    .parse()
+                    .map_err(|err| syn::Error::new(span, err))?;
+                let expr = syn::parse2(respan(source, span)).map_err(|err| {
+                    syn::Error::new(span, format!("in template interpolation: {}", err))
+                })?;
+                exprs.push(expr);
+            }
+            c => quasi.push(c),
+        }
+    }
+    quasis.push(cook(&quasi, lit.span())?);
+
+    Ok((quasis, exprs))
+}
+
+type Units<'a> = Peekable<Enumerate<Chars<'a>>>;
+
+// Reads one character of the literal as written, along with what it stands for when it is one of
+// the escapes that quoting the code of an interpolation introduces.
+fn next_unit(chars: &mut Units) -> Option<(usize, String, Option<char>)> {
+    let (index, c) = chars.next()?;
+    if c != '\\' {
+        return Some((index, c.to_string(), Some(c)));
+    }
+    let escaped = chars.next().map(|(_, c)| c);
+    let unit = std::iter::once(c).chain(escaped).collect();
+    Some((
+        index,
+        unit,
+        escaped.filter(|c| matches!(c, '"' | '\\' | '\'')),
+    ))
+}
+
+// Tells a char literal from a lifetime or label, given the units after its opening quote.
+fn is_quoted_char(chars: &Units) -> bool {
+    let mut ahead = chars.clone();
+    match next_unit(&mut ahead) {
+        Some((_, _, Some('\\'))) => true,
+        Some(_) => matches!(next_unit(&mut ahead), Some((_, _, Some('\'')))),
+        None => false,
+    }
+}
+
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(group) => {
+                let mut new = Group::new(group.delimiter(), respan(group.stream(), span));
+                new.set_span(span);
+                TokenTree::Group(new)
+            }
+            mut token => {
+                token.set_span(span);
+                token
+            }
+        })
+        .collect()
+}
+
+// Rewrites backtick template literals in a script file to the `$"..."` form, leaving comments,
+// string and char literals alone. Templates cannot be nested.
+pub fn desugar(source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                output.push(c);
+                for c in chars.by_ref() {
+                    output.push(c);
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                output.push(c);
+                output.extend(chars.next());
+                let mut last = ' ';
+                for c in chars.by_ref() {
+                    output.push(c);
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '"' => {
+                output.push(c);
+                push_quoted(c, &mut chars, &mut output, String::push);
+            }
+            '\'' => {
+                output.push(c);
+                if is_char_literal(&chars) {
+                    push_quoted(c, &mut chars, &mut output, String::push);
+                }
+            }
+            '`' => {
+                output.push_str("$\"");
+                let mut depth = 0;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '`' if depth == 0 => break,
+                        // Within an interpolation, the code is kept as it is written, and so are
+                        // its string and char literals, whatever braces they hold.
+                        '"' if depth > 0 => {
+                            push_escaped(&mut output, c);
+                            push_quoted(c, &mut chars, &mut output, push_escaped);
+                        }
+                        '\'' if depth > 0 => {
+                            output.push(c);
+                            if is_char_literal(&chars) {
+                                push_quoted(c, &mut chars, &mut output, push_escaped);
+                            }
+                        }
+                        '\\' if depth > 0 => output.push_str("\\\\"),
+                        '"' => output.push_str("\\\""),
+                        '\\' => match chars.next() {
+                            Some('`') => output.push('`'),
+                            Some('$') => output.push_str("\\u{24}"),
+                            Some(c) => {
+                                output.push('\\');
+                                output.push(c);
+                            }
+                            None => output.push('\\'),
+                        },
+                        '$' if depth == 0 && chars.peek() == Some(&'{') => {
+                            output.push(c);
+                            output.extend(chars.next());
+                            depth = 1;
+                        }
+                        '{' if depth > 0 => {
+                            output.push(c);
+                            depth += 1;
+                        }
+                        '}' if depth > 0 => {
+                            output.push(c);
+                            depth -= 1;
+                        }
+                        c => output.push(c),
+                    }
+                }
+                output.push('"');
+            }
+            c => output.push(c),
+        }
+    }
+
+    output
+}
+
+// A `'` starts a char literal, such as `'"'` or `'\''`, unless it starts a lifetime or a label.
+fn is_char_literal(chars: &Peekable<Chars>) -> bool {
+    let mut ahead = chars.clone();
+    match ahead.next() {
+        Some('\\') => true,
+        Some(_) => ahead.next() == Some('\''),
+        None => false,
+    }
+}
+
+// Copies the rest of a literal up to its closing `quote`, passing escape sequences through.
+fn push_quoted(
+    quote: char,
+    chars: &mut Peekable<Chars>,
+    output: &mut String,
+    push: fn(&mut String, char),
+) {
+    while let Some(c) = chars.next() {
+        push(output, c);
+        match c {
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    push(output, c);
+                }
+            }
+            c if c == quote => break,
+            _ => {}
+        }
+    }
+}
+
+// Code within an interpolation ends up in a string literal.
+fn push_escaped(output: &mut String, c: char) {
+    match c {
+        '"' | '\\' => {
+            output.push('\\');
+            output.push(c);
+        }
+        c => output.push(c),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn desugar_backticks() {
+        assert_eq!(
+            desugar(r#"let s = `a "b" ${f("c")} \` \${`; // `d`"#),
+            r#"let s = $"a \"b\" ${f(\"c\")} ` \u{24}{"; // `d`"#
+        );
+        assert_eq!(desugar(r#"tag`x${ {y} }z`"#), r#"tag$"x${ {y} }z""#);
+        assert_eq!(
+            desugar(r#"let q = '"'; let b = '`'; 'outer: loop {}"#),
+            r#"let q = '"'; let b = '`'; 'outer: loop {}"#
+        );
+        assert_eq!(
+            desugar(r#"`${f("}`", '}', '"')}`"#),
+            r#"$"${f(\"}`\", '}', '\"')}""#
+        );
+    }
+
+    #[test]
+    fn braces_in_interpolated_strings() {
+        let expr: Expr = syn::parse_str(&desugar(r#"`<${f("}", '{', "\\\"}")}>`"#)).unwrap();
+        let Expr::Template(template) = &expr else {
+            panic!("expected a template");
+        };
+        assert_eq!(template.quasis, ["<", ">"]);
+        assert_eq!(
+            template.exprs[0].to_rust_token_stream().to_string(),
+            quote!(f(
+                ::rsscript::types::string::JsString::new(::std::string::String::from("}")),
+                ::rsscript::types::string::JsString::new(::std::string::String::from('{')),
+                ::rsscript::types::string::JsString::new(::std::string::String::from("\\\"}"))
+            ))
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn template_to_rust_code() {
+        let expr: Expr = syn::parse_str(r#"$"a${b}\u{24}{c}""#).unwrap();
+        let Expr::Template(template) = &expr else {
+            panic!("expected a template");
+        };
+        assert_eq!(template.quasis, ["a", "${c}"]);
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!(
+                (::rsscript::types::string::JsString::new(::std::string::String::from("a"))
+                    + ::rsscript::types::JsValue::toString(&b)
+                    + ::rsscript::types::string::JsString::new(::std::string::String::from(
+                        "${c}"
+                    )))
+            )
+            .to_string()
+        );
+
+        let expr: Expr = syn::parse_str(r#"tag$"${a}""#).unwrap();
+        let empty = quote!(::rsscript::types::string::JsString::new(
+            ::std::string::String::from("")
+        ));
+        assert_eq!(
+            expr.to_rust_token_stream().to_string(),
+            quote!(tag(::std::vec![#empty, #empty], a)).to_string()
+        );
+    }
+}
@@ -0,0 +1,500 @@
+use proc_macro2::TokenStream;
+use syn::parse::Parse;
+
+use crate::{
+    encode::{scope, throws, ToRustCode},
+    parser::Item,
+};
+
+#[derive(Debug)]
+pub struct File {
+    pub items: Vec<Item>,
+}
+
+impl Parse for File {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut items = Vec::new();
+
+        while !input.is_empty() {
+            let next = input.parse()?;
+            items.push(next);
+        }
+
+        Ok(Self { items })
+    }
+}
+
+impl ToRustCode for File {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let interfaces = self.items.iter().filter_map(|item| match item {
+            Item::Interface(item) => Some(scope::Interface::new(item)),
+            _ => None,
+        });
+
+        scope::with_interfaces(interfaces, || {
+            scope::with_signatures(self.items.iter().flat_map(Item::signatures), || {
+                scope::with_accessors(self.items.iter().flat_map(Item::accessors), || {
+                    scope::with_enums(self.items.iter().filter_map(Item::enumeration), || {
+                        scope::with_unions(self.items.iter().filter_map(Item::union), || {
+                            throws::Throwing::of(&self.items).scope(|| {
+                                let classes = self.items.iter().filter_map(Item::class);
+                                scope::with_classes(classes, || {
+                                    let variables = Item::variables(&self.items);
+                                    scope::with_variables(variables, || {
+                                        self.items.to_rust_code(tokens)
+                                    })
+                                })
+                            })
+                        })
+                    })
+                })
+            })
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lower(source: &str) -> String {
+        let file: File = syn::parse_str(source).unwrap();
+        file.to_rust_token_stream().to_string()
+    }
+
+    #[test]
+    fn interface_to_trait() {
+        let code = lower(
+            "interface Shape { type Unit = Number; function area(): Number; }
+            class Square implements Shape { area(): Number {} }",
+        );
+
+        assert!(code.contains(
+            &quote::quote!(
+                trait Shape {
+                    type Unit;
+                    fn area(&mut self) -> Number;
+                }
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote::quote!(impl Shape for Square {
+                fn area(&mut self) -> Number {
+                    let __rsscript_frame = ::rsscript::types::error::Frame::enter("Square.area");
+                    {}
+                }
+                type Unit = Number;
+            })
+            .to_string()
+        ));
+    }
+
+    #[test]
+    fn enums() {
+        let code = lower(
+            "const enum Flag { A = 1, B }
+            enum Mode { Read = \"r\", Write }
+            enum Level { Low, High }
+            function f() { Flag.B; Level.High; Flag[1]; Level.Middle; }",
+        );
+
+        assert!(code.contains(
+            &quote::quote!(
+                type Flag = ::rsscript::types::number::Number;
+            )
+            .to_string()
+        ));
+        assert!(code
+            .contains(&quote::quote!(::rsscript::types::number::Number::from(2f64)).to_string()));
+        assert!(code.contains(&quote::quote!(Level::High).to_string()));
+        assert!(code.contains("enum member must have an initializer"));
+        assert!(code.contains("a `const enum` has no reverse mapping"));
+        assert!(code.contains("enum `Level` has no member `Middle`"));
+    }
+
+    #[test]
+    fn type_aliases() {
+        let code = lower(
+            "type Shape = { kind: \"circle\", r: Number } | { kind: \"square\", s: Number }
+            type Point = { x: Number } & { y?: Number }
+            type Id = Number | null
+            type Level = 1 | 2 | 3
+            type Bad = { x: Number } & Number
+            type Twice = { x: Number } & { x: Number }
+            let anonymous: Number | JsString = 1",
+        );
+
+        assert!(code.contains(
+            &quote::quote!(
+                enum Shape {
+                    Circle { r: Number },
+                    Square { s: Number },
+                }
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote::quote!(fn kind(&self) -> ::rsscript::types::string::JsString).to_string()
+        ));
+        assert!(code.contains(
+            &quote::quote!(
+                struct Point {
+                    pub x: Number,
+                    pub y: ::std::option::Option<Number>,
+                }
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote::quote!(
+                type Id = ::std::option::Option<Number>;
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote::quote!(
+                type Level = ::rsscript::types::number::Number;
+            )
+            .to_string()
+        ));
+        assert!(code.contains("intersection types can only combine object types"));
+        assert!(code.contains("duplicate field `x` in intersection"));
+        assert!(code.contains("union types must be named with a `type` alias"));
+    }
+
+    #[test]
+    fn narrowing() {
+        let code = lower(
+            "type Value = Number | JsString
+            function f(v: Value) {
+                if (typeof v === \"number\") { g(v); } else { h(); }
+                v instanceof Number;
+            }",
+        );
+
+        assert!(code.contains(&quote::quote!(let mut v = v;).to_string()));
+        assert!(code.contains(
+            &quote::quote!(match &mut v {
+                #[allow(unused_variables)]
+                Value::Number(__rsscript_narrowed_v) => {
+                    {
+                        g(::std::clone::Clone::clone(&*__rsscript_narrowed_v));
+                    }
+                }
+                #[allow(unused_variables)]
+                Value::JsString(__rsscript_narrowed_v) => {
+                    {
+                        h();
+                    }
+                }
+            })
+            .to_string()
+        ));
+        assert!(code.contains(&quote::quote!(::std::matches!(&v, Value::Number(..))).to_string()));
+    }
+
+    #[test]
+    fn missing_interface_member() {
+        let code = lower(
+            "interface Shape { function area(): Number; }
+            class Circle implements Shape {}",
+        );
+
+        assert!(code.contains("class `Circle` is missing `area` required by interface `Shape`"));
+
+        // Reported at the class, not at the interface it names.
+        let file: File = syn::parse_str(
+            "interface Shape { function area(): Number; }
+class Circle implements Shape {}",
+        )
+        .unwrap();
+        let tokens = file.to_rust_token_stream();
+        let diagnostics = crate::diagnostic::Diagnostic::from_tokens("main.rss", &tokens);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (2, 7));
+    }
+
+    #[test]
+    fn object_literals() {
+        let code = lower(
+            "class Size { width: Number = 1; get area(): Number {} set area(value: Number) {} }
+            function f(size: Size) {
+                let wider = { ...size, width: 3, area: 4 };
+                let taller: Size = { width: 2 };
+                let named = { get name(): JsString {}, set name(value: JsString) {} };
+                wider.area; named.name; named.name = \"a\";
+                let shape: Shape = { sides: 3 };
+            }
+            interface Shape { function sides(): Number; }",
+        );
+        let number = |n: f64| quote::quote!(::rsscript::types::number::Number::from(#n));
+        let (three, four, two) = (number(3.0), number(4.0), number(2.0));
+
+        // Spreading an object of a class copies it through its rest, and sets accessors with their
+        // setter.
+        assert!(code.contains(
+            &quote::quote!(
+                let mut __rsscript_object = ::rsscript::types::class::spread(&(size));
+                __rsscript_object.width = #three;
+                __rsscript_object.set_area(#four);
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote::quote!(
+                taller: Size = Size { width: #two, ..::std::default::Default::default() };
+            )
+            .to_string()
+        ));
+
+        // Accessors are called on objects of the class and of the struct of a literal alike.
+        assert!(code.contains(&quote::quote!(wider.area();).to_string()));
+        assert!(code.contains(&quote::quote!(named.name();).to_string()));
+        assert!(code.contains(&quote::quote!(named.set_name).to_string()));
+        assert!(code.contains("has to be created from a class implementing it"));
+    }
+
+    #[test]
+    fn throwing_functions() {
+        let code = lower(
+            "function fail() { throw Error(\"no\"); }
+            function relay() { fail(); }
+            function guard() { try { relay(); } catch { } }
+            function shadow() { function fail() {} fail(); }",
+        );
+        let result = |name: &str| {
+            let ident = quote::format_ident!("{}", name);
+            quote::quote!(fn #ident() -> ::std::result::Result<(), ::rsscript::types::error::Error>)
+                .to_string()
+        };
+
+        assert!(code.contains(&result("fail")));
+        assert!(code.contains(&result("relay")));
+        assert!(!code.contains(&result("guard")));
+        assert!(!code.contains(&result("shadow")));
+
+        // Every nested function is only looked at once per enclosing scope.
+        let depth = 24;
+        let mut source = "throw Error(\"deep\");".to_string();
+        for i in 0..depth {
+            source = format!("function f{}() {{ {} }} f{}();", i, source, i);
+        }
+        let code = lower(&format!("function outer() {{ {} }}", source));
+        assert!(code.contains(&result("outer")));
+    }
+
+    #[test]
+    fn throwing_closures_and_async_functions() {
+        let code = lower(
+            "function fail() { throw Error(\"no\"); }
+            async function later(): Number { fail(); 1 }
+            async function quiet(): Number { 1 }
+            async function main() {
+                let f = (x) => fail();
+                let g = (x) => x;
+                f(1); g(1); await later(); later(); await quiet();
+            }",
+        );
+        let error = quote::quote!(::rsscript::types::error::Error);
+        let propagated = |call: proc_macro2::TokenStream| {
+            quote::quote!((match #call {
+                ::std::result::Result::Ok(__rsscript_value) => __rsscript_value,
+                ::std::result::Result::Err(__rsscript_error) =>
+                    return ::std::result::Result::Err(__rsscript_error),
+            }))
+        };
+        let fail = propagated(quote::quote!(fail()));
+
+        assert!(code.contains(
+            &quote::quote!(async fn later() -> ::std::result::Result<Number, #error>).to_string()
+        ));
+        assert!(code.contains(&quote::quote!(async fn quiet() -> Number).to_string()));
+        assert!(code.contains(
+            &quote::quote!(let mut f = |x| -> ::std::result::Result<_, #error> {
+                ::std::result::Result::Ok({ #fail })
+            };)
+            .to_string()
+        ));
+        assert!(code.contains(&quote::quote!(let mut g = |x| x;).to_string()));
+        let one = quote::quote!(::rsscript::types::number::Number::from(1f64));
+        assert!(code.contains(&propagated(quote::quote!(f(#one))).to_string()));
+        assert!(code.contains(&propagated(quote::quote!(later().await)).to_string()));
+        assert!(code.contains(&quote::quote!(later();).to_string()));
+        assert!(code.contains(&quote::quote!(quiet().await;).to_string()));
+
+        for (source, error) in [
+            (
+                "function* f(): Generator<Number> { throw Error(\"no\"); }",
+                "errors cannot be thrown out of a generator, catch them in its body",
+            ),
+            (
+                "function f() { let o = { f() { throw Error(\"no\"); } }; }",
+                "errors cannot be thrown out of the methods of an object literal, catch them in its body",
+            ),
+            (
+                "class A { static { throw Error(\"no\"); } }",
+                "errors cannot be thrown out of a static block, catch them in the block",
+            ),
+            (
+                "class A { get x(): Number { throw Error(\"no\"); } }",
+                "errors cannot be thrown out of an accessor, catch them in its body",
+            ),
+        ] {
+            assert!(lower(source).contains(error), "{}", source);
+        }
+    }
+
+    #[test]
+    fn methods_resolve_through_the_receiver() {
+        let code = lower(
+            "class Box { get size(): Number {} m(a: Number, ...rest: Number[]) {} fail() { throw Error(\"no\"); } }
+            class Bag { size: Number = 0; m(a: Number, b: Number) {} fail() {} }
+            class Base { run() {} }
+            class Derived extends Base { run() { throw Error(\"no\"); } }
+            function f(bag: Bag, other: Other) {
+                let boxed = new Box();
+                bag.size; boxed.size; bag.fail(); other.fail(); boxed.fail();
+                bag.m(1, 2); other.m(1, 2); boxed.m(1, 2);
+            }",
+        );
+        let result = quote::quote!(::std::result::Result<(), ::rsscript::types::error::Error>);
+        let args = quote::quote!(
+            ::rsscript::types::number::Number::from(1f64),
+            ::rsscript::types::number::Number::from(2f64)
+        );
+
+        // Only the receiver's own class decides what a member lowers to.
+        assert!(code.contains(&quote::quote!(bag.size;).to_string()));
+        assert!(code.contains(&quote::quote!(boxed.size();).to_string()));
+        assert!(code.contains(&quote::quote!(bag.fail();).to_string()));
+        assert!(code.contains(&quote::quote!(other.fail();).to_string()));
+        assert!(!code.contains(&quote::quote!(boxed.fail();).to_string()));
+        assert!(code.contains(&quote::quote!(bag.m(#args);).to_string()));
+        assert!(code.contains(&quote::quote!(other.m(#args);).to_string()));
+        assert!(code.contains(
+            &quote::quote!(boxed.m(
+                ::rsscript::types::number::Number::from(1f64),
+                ::std::vec![::rsscript::types::number::Number::from(2f64)]
+            );)
+            .to_string()
+        ));
+        assert!(code.contains(&quote::quote!(pub fn fail(&mut self) -> #result).to_string()));
+        assert!(code.contains(&format!("{} {{", quote::quote!(pub fn fail(&mut self)))));
+
+        // An override that throws makes the whole family return a Result.
+        assert!(!code.contains(&format!("{} {{", quote::quote!(pub fn run(&mut self)))));
+        assert!(code.contains(&quote::quote!(fn run(&mut self) -> #result;).to_string()));
+
+        // A call reached through `?.` has nowhere to throw its errors on to.
+        let code = lower(
+            "class Box { fail() { throw Error(\"no\"); } }
+            function f(boxed: Option<Box>) { boxed?.fail(); }",
+        );
+        assert!(code.contains("`fail` can throw, so it cannot be called through `?.`"));
+    }
+
+    #[test]
+    fn optional_parameters() {
+        let code = lower(
+            "function f(a: Number, b: Number = a, c?: Number, ...d: Number[]) {}
+            function g() { f(1); f(1, 2, 3, 4, 5); }",
+        );
+
+        assert!(code.contains(
+            &quote::quote!(fn f(
+                a: Number,
+                b: ::std::option::Option<Number>,
+                c: ::std::option::Option<Number>,
+                d: ::std::vec::Vec<Number>
+            ))
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote::quote!(let b: Number = match b {
+                ::std::option::Option::Some(b) => b,
+                ::std::option::Option::None => a,
+            };)
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote::quote!(f(
+                ::rsscript::types::number::Number::from(1f64),
+                ::std::option::Option::None,
+                ::std::option::Option::None,
+                ::std::vec![]
+            ))
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote::quote!(::std::convert::Into::into(
+                ::rsscript::types::number::Number::from(3f64)
+            ))
+            .to_string()
+        ));
+
+        for (source, error) in [
+            (
+                "function f(...a: Number[], b: Number) {}",
+                "a rest parameter must be the last one",
+            ),
+            (
+                "function f(a?: Number = 1) {}",
+                "a parameter cannot be optional and have a default",
+            ),
+            (
+                "function f(a?: Number, b: Number) {}",
+                "a required parameter cannot follow an optional parameter",
+            ),
+        ] {
+            let err = syn::parse_str::<File>(source).unwrap_err();
+            assert_eq!(err.to_string(), error);
+        }
+    }
+
+    #[test]
+    fn generators() {
+        let code = lower(
+            "function* count(n: Number): Generator<Number> { yield n; yield* [n]; }
+            class Bag { *items(): Iterable<Number> {} }",
+        );
+
+        assert!(code.contains(
+            &quote::quote!(fn count(n: Number) -> ::rsscript::types::generator::Generator<'static, Number>)
+                .to_string()
+        ));
+        assert!(code.contains(&quote::quote!(__rsscript_yielder.yield_value(n).await;).to_string()));
+        assert!(code.contains(
+            &quote::quote!(pub fn items(&mut self) -> ::rsscript::types::generator::Generator<'_, Number>)
+                .to_string()
+        ));
+
+        for (source, error) in [
+            (
+                "function f() { yield 1; }",
+                "`yield` is only allowed in a generator function",
+            ),
+            (
+                "function* f(): Number {}",
+                "a generator must be declared to return `Generator<T>`",
+            ),
+            (
+                "async function* f(): Generator<Number> {}",
+                "async generators are not supported",
+            ),
+            (
+                "function* f(): Generator<Number> { return 1; }",
+                "a generator cannot return a value",
+            ),
+            (
+                "function* f(): Generator<Number> { await g(); }",
+                "`await` is not allowed in a generator",
+            ),
+            (
+                "class A { get *x(): Number {} }",
+                "an accessor cannot be a generator",
+            ),
+        ] {
+            assert!(lower(source).contains(error), "{}", source);
+        }
+    }
+}
@@ -1,17 +1,28 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
 use syn::{
     parse::Parse, punctuated::Punctuated, ConstParam, Ident, Lifetime, Type, TypeParamBound,
 };
 
-use crate::{parser::token::IdentPeeker, Token};
+use crate::{encode::ToRustCode, parser::token::IdentPeeker, Token};
 
+#[derive(Debug)]
 pub struct Generics {
-    pub lt_token: Token![<],
+    pub lt_token: Option<Token![<]>,
     pub params: Punctuated<GenericsParam, Token![,]>,
-    pub gt_token: Token![>],
+    pub gt_token: Option<Token![>]>,
 }
 
 impl Parse for Generics {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if !input.peek(Token![<]) {
+            return Ok(Self {
+                lt_token: None,
+                params: Punctuated::new(),
+                gt_token: None,
+            });
+        }
+
         let lt_token = input.parse()?;
         let mut params = Punctuated::new();
 
@@ -30,13 +41,64 @@ impl Parse for Generics {
         let gt_token = input.parse()?;
 
         Ok(Self {
-            lt_token,
+            lt_token: Some(lt_token),
             params,
-            gt_token,
+            gt_token: Some(gt_token),
         })
     }
 }
 
+impl Generics {
+    pub fn to_impl_generics_code(&self, tokens: &mut TokenStream) {
+        if self.params.is_empty() {
+            return;
+        }
+
+        let params = self.params.iter().map(|param| match param {
+            GenericsParam::Type(param) => {
+                let ident = &param.ident;
+                let bounds = &param.bounds;
+                if bounds.is_empty() {
+                    quote!(#ident)
+                } else {
+                    quote!(#ident: #bounds)
+                }
+            }
+            GenericsParam::Lifetime(param) => param.to_rust_token_stream(),
+            GenericsParam::Const(param) => {
+                let const_token = &param.const_token;
+                let ident = &param.ident;
+                let colon_token = &param.colon_token;
+                let ty = &param.ty;
+                quote!(#const_token #ident #colon_token #ty)
+            }
+        });
+        tokens.extend(quote!(<#(#params),*>));
+    }
+
+    pub fn to_type_generics_code(&self, tokens: &mut TokenStream) {
+        if self.params.is_empty() {
+            return;
+        }
+
+        let params = self.params.iter().map(|param| match param {
+            GenericsParam::Type(param) => param.ident.to_token_stream(),
+            GenericsParam::Lifetime(param) => param.lifetime.to_token_stream(),
+            GenericsParam::Const(param) => param.ident.to_token_stream(),
+        });
+        tokens.extend(quote!(<#(#params),*>));
+    }
+}
+
+impl ToRustCode for Generics {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.lt_token.to_tokens(tokens);
+        self.params.to_rust_code(tokens);
+        self.gt_token.to_tokens(tokens);
+    }
+}
+
+#[derive(Debug)]
 pub enum GenericsParam {
     Type(TypeParam),
     Lifetime(LifetimeParam),
@@ -57,6 +119,17 @@ impl Parse for GenericsParam {
     }
 }
 
+impl ToRustCode for GenericsParam {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Type(param) => param.to_rust_code(tokens),
+            Self::Lifetime(param) => param.to_rust_code(tokens),
+            Self::Const(param) => param.to_tokens(tokens),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct TypeParam {
     pub ident: Ident,
     pub extends_token: Option<Token![extends]>,
@@ -102,6 +175,22 @@ impl Parse for TypeParam {
     }
 }
 
+impl ToRustCode for TypeParam {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let ident = &self.ident;
+        let bounds = &self.bounds;
+
+        tokens.extend(quote!(#ident));
+        if !bounds.is_empty() {
+            tokens.extend(quote!(: #bounds));
+        }
+        if let (Some(eq_token), Some(default)) = (&self.eq_token, &self.default) {
+            tokens.extend(quote!(#eq_token #default));
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct LifetimeParam {
     pub lifetime: Lifetime,
     pub extends_token: Option<Token![extends]>,
@@ -136,3 +225,15 @@ impl Parse for LifetimeParam {
         })
     }
 }
+
+impl ToRustCode for LifetimeParam {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let lifetime = &self.lifetime;
+        let bounds = &self.bounds;
+
+        tokens.extend(quote!(#lifetime));
+        if !bounds.is_empty() {
+            tokens.extend(quote!(: #bounds));
+        }
+    }
+}
@@ -1,36 +1,68 @@
 use class::ItemClass;
+use enumeration::ItemEnum;
 use interface::ItemInterface;
+use param::Param;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
 use syn::{
     braced, parenthesized,
     parse::Parse,
+    parse_quote,
     punctuated::Punctuated,
+    spanned::Spanned,
     token::{Brace, Paren},
-    FnArg, Ident, Pat, Path, Type,
+    GenericArgument, Ident, Path, PathArguments, Receiver, Type, TypePath,
 };
 
 use crate::{
+    encode::{
+        self,
+        context::{self, ThrowTarget},
+        scope::{self, ParamKind, ThrowsOn},
+        ToRustCode,
+    },
     parser::{
-        expr::Expr, generics::Generics, restrinction::Visibility, stmt::Block, token::IdentPeeker,
+        expr::Expr,
+        generics::Generics,
+        pat::Pattern,
+        restrinction::{ClassVisibility, Visibility},
+        stmt::Block,
+        token::IdentPeeker,
+        ty::{self, ScriptType},
     },
     Token,
 };
 
 pub mod class;
 
+pub mod enumeration;
+
 pub mod interface;
 
+pub mod param;
+
+#[derive(Debug)]
 pub enum Item {
     Local(Local),
     Function(ItemFunction),
     Class(ItemClass),
     Interface(ItemInterface),
     TypeAlias(ItemTypeAlias),
+    Enum(ItemEnum),
     Import(ItemImport),
 }
 
 impl Parse for Item {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        if input.peek(Token![const]) || input.peek(Token![let]) {
+        if input.peek(Token![enum])
+            || input.peek(Token![const]) && input.peek2(Token![enum])
+            || input.ipeek::<Token![export]>() && input.peek2(Token![enum])
+            || input.ipeek::<Token![export]>()
+                && input.peek2(Token![const])
+                && input.peek3(Token![enum])
+        {
+            Ok(Self::Enum(input.parse()?))
+        } else if input.peek(Token![const]) || input.peek(Token![let]) {
             Ok(Self::Local(input.parse()?))
         } else if input.ipeek::<Token![function]>()
             || input.peek(Token![async]) && input.ipeekn::<Token![function]>(2)
@@ -62,6 +94,137 @@ impl Parse for Item {
     }
 }
 
+impl Item {
+    // The names other scripts can import from the script declaring this item.
+    pub fn exports(&self) -> Vec<&Ident> {
+        let (vis, idents) = match self {
+            Self::Local(_) => return Vec::new(),
+            Self::Function(item) => (&item.export_token, vec![&item.ident]),
+            Self::Class(item) => (&item.vis, vec![&item.ident]),
+            Self::Interface(item) => (&item.vis, vec![&item.ident]),
+            Self::TypeAlias(item) => (&item.vis, vec![&item.ident]),
+            Self::Enum(item) => (&item.vis, vec![&item.ident]),
+            Self::Import(item) => (
+                &item.exsport_token,
+                item.items
+                    .iter()
+                    .filter_map(ImportName::local_ident)
+                    .collect(),
+            ),
+        };
+
+        if vis.export_token.is_some() {
+            idents
+        } else {
+            Vec::new()
+        }
+    }
+
+    // A local declared with a single name, along with its type when it is annotated or the class
+    // of its initial value is known. Only annotated locals are narrowed, so they are the only ones
+    // of a union type.
+    pub fn variable(&self) -> Option<(Ident, Option<Type>)> {
+        let Self::Local(Local {
+            pat: Pattern::Ident(ident),
+            ty,
+            init,
+            ..
+        }) = self
+        else {
+            return None;
+        };
+        let ty = match (ty, init) {
+            (Some(ty), _) => Some(ty.ty.clone()),
+            (None, Some(init)) => init
+                .expr
+                .class()
+                .filter(|class| scope::union(class).is_none())
+                .map(|class| parse_quote!(#class)),
+            (None, None) => None,
+        };
+        Some((ident.clone(), ty))
+    }
+
+    // The variables declared among `items`, each typed with those declared before it in scope, so
+    // that a local initialized from another one has its class.
+    pub fn variables<'a>(items: impl IntoIterator<Item = &'a Item>) -> Vec<(Ident, Option<Type>)> {
+        let mut variables = Vec::new();
+        for item in items {
+            let variable = scope::with_variables(variables.clone(), || item.variable());
+            variables.extend(variable);
+        }
+        variables
+    }
+
+    // The accessors of a class, or of the object literal a local is initialized with.
+    pub fn accessors(&self) -> Vec<(String, scope::Accessor)> {
+        match self {
+            Self::Class(item) => item.accessors(),
+            Self::Local(Local {
+                ty: None,
+                init: Some(init),
+                ..
+            }) => match &*init.expr {
+                Expr::Object(expr) => expr.accessors(),
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn union(&self) -> Option<scope::Union> {
+        match self {
+            Self::TypeAlias(item) => item.union()?.ok(),
+            _ => None,
+        }
+    }
+
+    pub fn class(&self) -> Option<scope::Class> {
+        match self {
+            Self::Class(item) => Some(scope::Class::new(item)),
+            _ => None,
+        }
+    }
+
+    pub fn enumeration(&self) -> Option<(Ident, scope::Enum)> {
+        match self {
+            Self::Enum(item) => Some(item.to_scope()),
+            _ => None,
+        }
+    }
+
+    // The functions and methods declared by this item whose calls need their arguments padded or
+    // collected.
+    pub fn signatures(&self) -> Vec<(String, Vec<ParamKind>)> {
+        match self {
+            Self::Function(item) => item
+                .inputs
+                .signature()
+                .map(|kinds| (item.ident.to_string(), kinds))
+                .into_iter()
+                .collect(),
+            Self::Class(item) => item.signatures(),
+            Self::Interface(item) => item.signatures(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl ToRustCode for Item {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Local(item) => item.to_rust_code(tokens),
+            Self::Function(item) => item.to_rust_code(tokens),
+            Self::Class(item) => item.to_rust_code(tokens),
+            Self::Interface(item) => item.to_rust_code(tokens),
+            Self::TypeAlias(item) => item.to_rust_code(tokens),
+            Self::Enum(item) => item.to_rust_code(tokens),
+            Self::Import(item) => item.to_rust_code(tokens),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum DeclarationKeyword {
     Let,
     Const,
@@ -81,23 +244,38 @@ impl Parse for DeclarationKeyword {
     }
 }
 
+#[derive(Debug)]
 pub struct TypeAnnotation {
     pub colon_token: Token![:],
     pub ty: syn::Type,
 }
 
+// Types that would need an item generated for them are reported where the annotation is lowered.
 impl Parse for TypeAnnotation {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let colon_token = input.parse()?;
+        let ty: ScriptType = input.parse()?;
+
         Ok(Self {
-            colon_token: input.parse()?,
-            ty: input.parse()?,
+            colon_token,
+            ty: ty
+                .rust_type()
+                .unwrap_or_else(|err| Type::Verbatim(err.to_compile_error())),
         })
     }
 }
 
+impl ToRustCode for TypeAnnotation {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.ty.to_tokens(tokens);
+    }
+}
+
+#[derive(Debug)]
 pub struct Local {
     pub let_token: DeclarationKeyword,
-    pub pat: Pat,
+    pub pat: Pattern,
+    pub ty: Option<TypeAnnotation>,
     pub init: Option<InitVar>,
 }
 
@@ -105,12 +283,60 @@ impl Parse for Local {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         Ok(Self {
             let_token: input.parse()?,
-            pat: Pat::parse_multi(input)?,
-            init: input.parse::<InitVar>().ok(),
+            pat: input.parse()?,
+            ty: if input.peek(Token![:]) {
+                Some(input.parse()?)
+            } else {
+                None
+            },
+            init: if input.peek(Token![=]) {
+                Some(input.parse()?)
+            } else {
+                None
+            },
         })
     }
 }
 
+// `const` only forbids rebinding, which is what an immutable Rust binding gives us.
+impl ToRustCode for Local {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        // Variables of union types are narrowed through a mutable borrow.
+        let mutable = matches!(self.let_token, DeclarationKeyword::Let)
+            || self
+                .ty
+                .as_ref()
+                .is_some_and(|ty| scope::union_of(&ty.ty).is_some());
+        let ty = self.ty.as_ref().map(ToRustCode::to_rust_token_stream);
+
+        if let Some(init) = &self.init {
+            // An object literal is created as an object of the type it is annotated with.
+            let value = match (&*init.expr, &self.ty) {
+                (Expr::Object(expr), Some(ty)) => {
+                    let mut value = TokenStream::new();
+                    expr.to_typed_code(Some(&ty.ty), &mut value);
+                    value
+                }
+                _ => init.expr.to_rust_token_stream(),
+            };
+            self.pat.to_binding_code(value, ty, mutable, tokens);
+            return;
+        }
+
+        let pat = self.pat.to_rust_token_stream();
+        let ty = ty.map(|ty| quote!(: #ty));
+        if mutable {
+            tokens.extend(quote! {
+                #[allow(unused_mut)]
+                let mut #pat #ty;
+            });
+        } else {
+            tokens.extend(quote!(let #pat #ty;));
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct InitVar {
     pub eq_token: Token![=],
     pub expr: Box<Expr>,
@@ -125,35 +351,272 @@ impl Parse for InitVar {
     }
 }
 
+impl ToRustCode for InitVar {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.eq_token.to_tokens(tokens);
+        self.expr.to_rust_code(tokens);
+    }
+}
+
+// A rest parameter is the last argument, taking a `Vec`. Optional parameters and those with a
+// default take an `Option`, and calls to functions known in scope pad the missing arguments.
+#[derive(Debug)]
 pub struct FnArgs {
     pub paren_token: Paren,
-    pub args: Punctuated<FnArg, Token![,]>,
+    pub receiver: Option<Receiver>,
+    pub params: Punctuated<Param, Token![,]>,
+}
+
+// `constructor(private x: Number)` declares the field `x` and assigns the argument to it.
+#[derive(Debug)]
+pub struct ParamProperty<'a> {
+    pub vis: &'a ClassVisibility,
+    pub ident: Ident,
+    pub ty: Type,
 }
 
 impl Parse for FnArgs {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let content;
         let paren_token = parenthesized!(content in input);
-        let mut args = Punctuated::new();
+        let receiver = if content.peek(Token![self])
+            || content.peek(Token![&])
+            || content.peek(Token![mut]) && content.peek2(Token![self])
+        {
+            let receiver = content.parse()?;
+            if !content.is_empty() {
+                let _: Token![,] = content.parse()?;
+            }
+            Some(receiver)
+        } else {
+            None
+        };
+        let params: Punctuated<Param, Token![,]> =
+            content.parse_terminated(Param::parse, Token![,])?;
 
-        while !content.is_empty() {
-            let next = content.parse()?;
-            args.push_value(next);
-            if content.is_empty() {
-                break;
+        let mut optional: Option<&Param> = None;
+        for (i, param) in params.iter().enumerate() {
+            let span = param.ty.colon_token.span;
+            if param.rest_token.is_some() {
+                if i + 1 != params.len() {
+                    return Err(syn::Error::new(
+                        span,
+                        "a rest parameter must be the last one",
+                    ));
+                }
+                if param.question_token.is_some() || param.default.is_some() {
+                    return Err(syn::Error::new(
+                        span,
+                        "a rest parameter cannot be optional or have a default",
+                    ));
+                }
+            }
+            if param.question_token.is_some() && param.default.is_some() {
+                return Err(syn::Error::new(
+                    span,
+                    "a parameter cannot be optional and have a default",
+                ));
+            }
+            // A parameter with a default can be followed by required ones, which then have to be
+            // given `undefined` to use the default.
+            if param.question_token.is_some() {
+                optional = Some(param);
+            } else if param.kind() == ParamKind::Required && optional.is_some() {
+                return Err(syn::Error::new(
+                    span,
+                    "a required parameter cannot follow an optional parameter",
+                ));
+            }
+            if !matches!(param.vis, ClassVisibility::Inherited) && param.is_destructured() {
+                return Err(syn::Error::new(
+                    span,
+                    "a parameter property must be an identifier",
+                ));
             }
-            let punct = content.parse()?;
-            args.push_punct(punct);
         }
 
-        Ok(Self { paren_token, args })
+        Ok(Self {
+            paren_token,
+            receiver,
+            params,
+        })
     }
 }
 
+impl ToRustCode for FnArgs {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let receiver = &self.receiver;
+        let comma = (receiver.is_some() && !self.params.is_empty()).then(|| quote!(,));
+        let params = self.to_params_code();
+        self.paren_token.surround(tokens, |tokens| {
+            tokens.extend(quote!(#receiver #comma #params))
+        });
+    }
+}
+
+impl FnArgs {
+    // The parameters as they are declared in Rust, without the receiver.
+    pub fn to_params_code(&self) -> TokenStream {
+        let params = self.params.iter().enumerate().map(|(i, param)| {
+            let ident = param.ident(i);
+            let ty = param.rust_type();
+            quote!(#ident: #ty)
+        });
+        quote!(#(#params),*)
+    }
+
+    // The inputs of a method, which takes `&mut self` unless it declares its own receiver.
+    pub fn to_method_inputs_code(&self) -> TokenStream {
+        if self.receiver.is_some() {
+            return self.to_rust_token_stream();
+        }
+
+        let comma = (!self.params.is_empty()).then(|| quote!(,));
+        let params = self.to_params_code();
+        let mut tokens = TokenStream::new();
+        self.paren_token.surround(&mut tokens, |tokens| {
+            tokens.extend(quote!(&mut self #comma #params))
+        });
+        tokens
+    }
+
+    pub fn rust_types(&self) -> Vec<Type> {
+        self.params.iter().map(Param::rust_type).collect()
+    }
+
+    pub fn kinds(&self) -> Vec<ParamKind> {
+        self.params.iter().map(Param::kind).collect()
+    }
+
+    // The kinds of parameters, for calls to pad or collect the arguments of, which is only needed
+    // when some are not required.
+    pub fn signature(&self) -> Option<Vec<ParamKind>> {
+        let kinds = self.kinds();
+        kinds
+            .iter()
+            .any(|kind| *kind != ParamKind::Required)
+            .then_some(kinds)
+    }
+
+    pub fn has_rest(&self) -> bool {
+        self.params.iter().any(|param| param.rest_token.is_some())
+    }
+
+    pub fn properties(&self) -> Vec<ParamProperty<'_>> {
+        self.params
+            .iter()
+            .enumerate()
+            .filter(|(_, param)| !matches!(param.vis, ClassVisibility::Inherited))
+            .map(|(i, param)| ParamProperty {
+                vis: &param.vis,
+                ident: param.ident(i),
+                ty: param.body_type(),
+            })
+            .collect()
+    }
+
+    pub fn to_body_code(&self, body: &Block, tokens: &mut TokenStream) {
+        let errors = self.properties().into_iter().map(|property| {
+            syn::Error::new(
+                property.ident.span(),
+                "parameter properties are only allowed in a class constructor",
+            )
+            .to_compile_error()
+        });
+        self.to_body_code_with(quote!(#(#errors)*), Vec::new(), body, tokens);
+    }
+
+    // Parameter properties are moved into their fields before the body runs, like in TypeScript,
+    // and the body uses the fields in place of the parameters.
+    pub fn to_constructor_body_code(&self, body: &Block, tokens: &mut TokenStream) {
+        let properties = self.properties();
+        let assigns = properties.iter().map(|property| {
+            let ident = &property.ident;
+            quote!(self.#ident = #ident;)
+        });
+        let field_params = properties
+            .iter()
+            .map(|property| (property.ident.clone(), true))
+            .collect();
+        self.to_body_code_with(quote!(#(#assigns)*), field_params, body, tokens);
+    }
+
+    // The parameters that are not destructured, along with their type in the body.
+    pub(crate) fn variables(&self) -> Vec<(Ident, Option<Type>)> {
+        self.params
+            .iter()
+            .enumerate()
+            .filter(|(_, param)| !param.is_destructured())
+            .map(|(i, param)| (param.ident(i), Some(param.body_type())))
+            .collect()
+    }
+
+    // Defaults are applied from left to right, so that each can use the parameters before it,
+    // before any parameter is destructured.
+    fn to_body_code_with(
+        &self,
+        prelude: TokenStream,
+        field_params: Vec<(Ident, bool)>,
+        body: &Block,
+        tokens: &mut TokenStream,
+    ) {
+        let mut bindings = TokenStream::new();
+        for (i, param) in self.params.iter().enumerate() {
+            let ident = param.ident(i);
+            if let Some((_, default)) = &param.default {
+                let ty = &param.ty.ty;
+                let default = default.to_rust_token_stream();
+                bindings.extend(quote! {
+                    let #ident: #ty = match #ident {
+                        ::std::option::Option::Some(#ident) => #ident,
+                        ::std::option::Option::None => #default,
+                    };
+                });
+            }
+            if param.is_destructured() {
+                param
+                    .pat
+                    .to_binding_code(quote!(#ident), None, true, &mut bindings);
+            }
+        }
+        bindings.extend(prelude);
+
+        // Parameters of union types are narrowed through a mutable borrow.
+        let variables = self.variables();
+        for (ident, ty) in &variables {
+            if ty.as_ref().and_then(scope::union_of).is_some() {
+                bindings.extend(quote! {
+                    #[allow(unused_mut)]
+                    let mut #ident = #ident;
+                });
+            }
+        }
+
+        scope::with_variables(variables, || {
+            scope::with_field_params(field_params, || {
+                if bindings.is_empty() {
+                    body.to_rust_code(tokens);
+                    return;
+                }
+
+                let body = encode::unbraced(body.to_rust_token_stream());
+                tokens.extend(quote! {
+                    {
+                        #bindings
+                        #body
+                    }
+                });
+            })
+        });
+    }
+}
+
+#[derive(Debug)]
 pub struct ItemFunction {
     pub export_token: Visibility,
     pub async_token: Option<Token![async]>,
     pub function_token: Token![function],
+    pub star_token: Option<Token![*]>,
     pub ident: Ident,
     pub generics: Generics,
     pub inputs: FnArgs,
@@ -167,6 +630,7 @@ impl Parse for ItemFunction {
             export_token: input.parse()?,
             async_token: input.parse()?,
             function_token: input.parse()?,
+            star_token: input.parse()?,
             ident: input.parse()?,
             generics: input.parse()?,
             inputs: input.parse()?,
@@ -180,13 +644,153 @@ impl Parse for ItemFunction {
     }
 }
 
+impl ItemFunction {
+    fn body_code(&self, target: ThrowTarget) -> TokenStream {
+        let (body, _) = context::with_throw_target(target, || {
+            let mut body = TokenStream::new();
+            self.inputs.to_body_code(&self.body, &mut body);
+            body
+        });
+        encode::unbraced(body)
+    }
+}
+
+impl ToRustCode for ItemFunction {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let vis = self.export_token.to_rust_token_stream();
+        let async_token = &self.async_token;
+        let fn_token = Token![fn](self.function_token.span);
+        let ident = &self.ident;
+        let generics = self.generics.to_rust_token_stream();
+        let inputs = self.inputs.to_rust_token_stream();
+        let output = self
+            .output
+            .as_ref()
+            .map_or_else(|| quote!(()), ToRustCode::to_rust_token_stream);
+        let frame = frame_code(&ident.to_string());
+
+        if let Some(star_token) = &self.star_token {
+            let code = match &self.async_token {
+                Some(async_token) => Err(syn::Error::new(
+                    async_token.span,
+                    "async generators are not supported",
+                )),
+                None => yield_type(self.output.as_ref(), star_token.spans[0]),
+            };
+            tokens.extend(match code {
+                Ok(yield_ty) => {
+                    let body = generator_code(&self.inputs, &self.body, &yield_ty);
+                    quote! {
+                        #vis #fn_token #ident #generics #inputs
+                            -> #runtime::generator::Generator<'static, #yield_ty>
+                        {
+                            #body
+                        }
+                    }
+                }
+                Err(err) => err.to_compile_error(),
+            });
+            return;
+        }
+
+        // An async function returns its errors to whatever awaits it.
+        if scope::throws_on(&ident.to_string()) != ThrowsOn::Never {
+            let async_token = &self.async_token;
+            let body = self.body_code(ThrowTarget::Return);
+            tokens.extend(quote! {
+                #vis #async_token #fn_token #ident #generics #inputs
+                    -> ::std::result::Result<#output, #runtime::error::Error>
+                {
+                    #frame
+                    ::std::result::Result::Ok(#body)
+                }
+            });
+        } else {
+            let body = self.body_code(ThrowTarget::Nowhere(UNTHROWN));
+            tokens.extend(quote! {
+                #vis #async_token #fn_token #ident #generics #inputs -> #output {
+                    #frame
+                    #body
+                }
+            });
+        }
+    }
+}
+
+// The walk in `throws` finds every error that a function or method body throws, so none is
+// thrown to the function when it is found not to throw.
+pub const UNTHROWN: &str = "errors cannot be thrown out of this function";
+
+// Records the function on the runtime call stack, for the errors created while it runs.
+pub fn frame_code(name: &str) -> TokenStream {
+    let runtime = encode::runtime();
+    let frame = encode::private_ident("frame");
+    quote!(let #frame = #runtime::error::Frame::enter(#name);)
+}
+
+// A generator is declared to return `Generator<T>`, `Iterator<T>`, `IterableIterator<T>` or
+// `Iterable<T>`, which all give the type of the values it yields.
+pub fn yield_type(output: Option<&TypeAnnotation>, span: Span) -> syn::Result<Type> {
+    let ty = output.map(|output| &output.ty);
+    if let Some(Type::Path(TypePath { qself: None, path })) = ty {
+        let segment = path.segments.last().unwrap();
+        let names = ["Generator", "Iterator", "IterableIterator", "Iterable"];
+        if let (true, PathArguments::AngleBracketed(args)) = (
+            names.iter().any(|name| segment.ident == name),
+            &segment.arguments,
+        ) {
+            if let Some(GenericArgument::Type(ty)) = args.args.first() {
+                if args.args.len() == 1 {
+                    return Ok(ty.clone());
+                }
+            }
+        }
+    }
+
+    Err(syn::Error::new(
+        ty.map_or(span, |ty| ty.span()),
+        "a generator must be declared to return `Generator<T>`",
+    ))
+}
+
+// The body of a generator function becomes an `async` block, which the runtime `Generator`
+// resumes up to every `yield`.
+pub fn generator_code(inputs: &FnArgs, body: &Block, yield_ty: &Type) -> TokenStream {
+    let runtime = encode::runtime();
+    let yielder = encode::private_ident("yielder");
+    let (body, _) = context::with_throw_target(ThrowTarget::Generator, || {
+        let mut tokens = TokenStream::new();
+        inputs.to_body_code(body, &mut tokens);
+        tokens
+    });
+
+    quote! {
+        #runtime::generator::Generator::new(
+            move |#yielder: #runtime::generator::Yielder<#yield_ty>| async move #body
+        )
+    }
+}
+
+#[derive(Debug)]
 pub struct ItemTypeAlias {
     pub vis: Visibility,
     pub type_token: Token![type],
     pub ident: Ident,
     pub generics: Generics,
     pub eq_token: Token![=],
-    pub alias: Type,
+    pub alias: ScriptType,
+}
+
+impl ItemTypeAlias {
+    pub fn union(&self) -> Option<syn::Result<scope::Union>> {
+        match &self.alias {
+            ScriptType::Union(members) if self.alias.rust_type().is_err() => {
+                Some(ty::to_union(&self.ident, &self.generics, members))
+            }
+            _ => None,
+        }
+    }
 }
 
 impl Parse for ItemTypeAlias {
@@ -202,6 +806,29 @@ impl Parse for ItemTypeAlias {
     }
 }
 
+impl ToRustCode for ItemTypeAlias {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let vis = self.vis.to_rust_token_stream();
+        let type_token = &self.type_token;
+        let ident = &self.ident;
+        let generics = self.generics.to_rust_token_stream();
+        let eq_token = &self.eq_token;
+
+        // Unions become an enum and object types a struct, anything else stays an alias.
+        let code = match self.union() {
+            Some(union) => union.map(|union| ty::union_code(&vis, &self.generics, &union)),
+            None => {
+                ty::struct_code(&vis, ident, &self.generics, &self.alias).unwrap_or_else(|| {
+                    let alias = self.alias.rust_type()?;
+                    Ok(quote!(#vis #type_token #ident #generics #eq_token #alias;))
+                })
+            }
+        };
+        tokens.extend(code.unwrap_or_else(|err| err.to_compile_error()));
+    }
+}
+
+#[derive(Debug)]
 pub enum ImportName {
     Name(syn::UseName),
     Rename(syn::UseRename),
@@ -229,6 +856,35 @@ impl Parse for ImportName {
     }
 }
 
+impl ImportName {
+    pub fn ident(&self) -> Option<&Ident> {
+        match self {
+            Self::Name(name) => Some(&name.ident),
+            Self::Rename(rename) => Some(&rename.ident),
+            Self::Glob(_) => None,
+        }
+    }
+
+    pub fn local_ident(&self) -> Option<&Ident> {
+        match self {
+            Self::Name(name) => Some(&name.ident),
+            Self::Rename(rename) => Some(&rename.rename),
+            Self::Glob(_) => None,
+        }
+    }
+}
+
+impl ToRustCode for ImportName {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Name(name) => name.to_tokens(tokens),
+            Self::Rename(rename) => rename.to_tokens(tokens),
+            Self::Glob(glob) => glob.to_tokens(tokens),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ItemImport {
     pub exsport_token: Visibility,
     pub import_token: Token![import],
@@ -246,8 +902,19 @@ impl Parse for ItemImport {
             import_token: input.parse()?,
             brace: braced!(content in input),
             items: Punctuated::parse_terminated(&content)?,
-            from_token: content.parse()?,
+            from_token: input.parse()?,
             path: input.parse()?,
         })
     }
 }
+
+// Scripts are lowered into sibling modules, so an import path is relative to the parent module.
+impl ToRustCode for ItemImport {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let vis = self.exsport_token.to_rust_token_stream();
+        let path = &self.path;
+        let items = self.items.to_rust_token_stream();
+
+        tokens.extend(quote!(#vis use super::#path::{#items};));
+    }
+}
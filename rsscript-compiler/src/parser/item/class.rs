@@ -1,18 +1,32 @@
+use std::collections::HashSet;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
     braced, parenthesized,
     parse::Parse,
+    parse_quote,
     punctuated::Punctuated,
+    spanned::Spanned,
     token::{Brace, Paren},
-    Ident, Path, TypeParamBound,
+    FnArg, GenericParam, Ident, Lifetime, Path, Signature, TraitBoundModifier, Type,
+    TypeParamBound,
 };
 
 use crate::{
+    encode::{
+        self,
+        context::{self, ThrowTarget},
+        scope::{self, Accessor, Interface, ParamKind, ThrowsOn},
+        ToRustCode,
+    },
     enum_impl,
     parser::{
-        expr::object::{ObjectIdent, ObjectMethod, ObjectStmt},
+        expr::object::{ObjectAccessor, ObjectIdent, ObjectMethod},
         generics::Generics,
-        item::InitVar,
+        item::{frame_code, generator_code, yield_type, InitVar, TypeAnnotation, UNTHROWN},
         restrinction::{ClassVisibility, Visibility},
+        stmt::Block,
         token::IdentPeeker,
     },
     Token,
@@ -20,15 +34,68 @@ use crate::{
 
 use super::ItemTypeAlias;
 
+#[derive(Debug)]
 pub struct ClassField {
+    pub static_token: Option<Token![static]>,
     pub ident: ObjectIdent,
+    pub ty: Option<TypeAnnotation>,
     pub init: Option<InitVar>,
 }
 
+impl ClassField {
+    // Static fields are lazily initialized statics, read and written through accessors.
+    fn to_static_code(
+        &self,
+        ty: &TokenStream,
+        vis: &TokenStream,
+        init_call: &TokenStream,
+        tokens: &mut TokenStream,
+    ) {
+        let name = &self.ident.ident;
+        let setter = format_ident!("set_{}", name);
+        let cell = encode::private_ident(&format!("static_{}", name));
+        let static_value = Ident::new("VALUE", Span::mixed_site());
+        let value = encode::private_ident("value");
+        let init = match &self.init {
+            Some(init) => init.expr.to_rust_token_stream(),
+            None => quote!(::std::default::Default::default()),
+        };
+        let lock = quote! {
+            ::std::sync::Mutex::lock(Self::#cell())
+                .unwrap_or_else(::std::sync::PoisonError::into_inner)
+        };
+
+        tokens.extend(quote! {
+            #[doc(hidden)]
+            fn #cell() -> &'static ::std::sync::Mutex<#ty> {
+                static #static_value: ::std::sync::LazyLock<::std::sync::Mutex<#ty>> =
+                    ::std::sync::LazyLock::new(|| ::std::sync::Mutex::new(#init));
+                &#static_value
+            }
+
+            #vis fn #name() -> #ty {
+                #init_call
+                ::std::clone::Clone::clone(&*#lock)
+            }
+
+            #vis fn #setter(#value: #ty) {
+                #init_call
+                *#lock = #value;
+            }
+        });
+    }
+}
+
 impl Parse for ClassField {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         Ok(Self {
+            static_token: input.parse()?,
             ident: input.parse()?,
+            ty: if input.peek(Token![:]) {
+                Some(input.parse()?)
+            } else {
+                None
+            },
             init: if input.peek(Token![=]) {
                 Some(input.parse()?)
             } else {
@@ -38,6 +105,7 @@ impl Parse for ClassField {
     }
 }
 
+#[derive(Debug)]
 pub struct ClassMethod {
     pub static_token: Option<Token![static]>,
     pub method: ObjectMethod,
@@ -52,6 +120,309 @@ impl Parse for ClassMethod {
     }
 }
 
+impl ClassMethod {
+    pub(crate) fn is_constructor(&self) -> bool {
+        self.static_token.is_none() && self.method.ident.ident == "constructor"
+    }
+
+    fn to_method_code(
+        &self,
+        class: &Ident,
+        vis: &TokenStream,
+        prelude: &TokenStream,
+        tokens: &mut TokenStream,
+    ) {
+        method_code(
+            class,
+            &self.method.ident.ident,
+            self.static_token.is_some(),
+            self.is_constructor(),
+            &self.method,
+            vis,
+            prelude,
+            tokens,
+        );
+    }
+
+    fn check_conformance(&self, interface: &Interface) -> syn::Result<()> {
+        let method = &self.method;
+        let ident = &method.ident.ident;
+        let Some(f) = interface.fns.iter().find(|f| f.ident == *ident) else {
+            return Ok(());
+        };
+        let mut errors: Vec<syn::Error> = Vec::new();
+
+        if let Some(static_token) = &self.static_token {
+            errors.push(syn::Error::new(
+                static_token.span,
+                format!(
+                    "`{}` cannot be static because interface `{}` requires an instance method",
+                    ident, interface.ident
+                ),
+            ));
+        }
+
+        let expected = &f.inputs;
+        let found = method.inputs.rust_types();
+
+        if expected.len() != found.len() {
+            errors.push(syn::Error::new(
+                method.inputs.paren_token.span.join(),
+                format!(
+                    "`{}` takes {} argument(s) but interface `{}` expects {}",
+                    ident,
+                    found.len(),
+                    interface.ident,
+                    expected.len()
+                ),
+            ));
+        } else {
+            for (expected, found) in expected.iter().zip(&found) {
+                if !same_type(expected, found) {
+                    errors.push(syn::Error::new_spanned(
+                        found,
+                        format!(
+                            "expected `{}` as declared by interface `{}`, found `{}`",
+                            expected.to_token_stream(),
+                            interface.ident,
+                            found.to_token_stream()
+                        ),
+                    ));
+                }
+            }
+        }
+
+        match (&f.output, &method.outputs) {
+            (Some(expected), Some(found)) if !same_type(expected, &found.ty) => {
+                errors.push(syn::Error::new_spanned(
+                    &found.ty,
+                    format!(
+                        "expected return type `{}` as declared by interface `{}`, found `{}`",
+                        expected.to_token_stream(),
+                        interface.ident,
+                        found.ty.to_token_stream()
+                    ),
+                ))
+            }
+            (Some(expected), None) => errors.push(syn::Error::new(
+                ident.span(),
+                format!(
+                    "`{}` must return `{}` as declared by interface `{}`",
+                    ident,
+                    expected.to_token_stream(),
+                    interface.ident
+                ),
+            )),
+            (None, Some(found)) => errors.push(syn::Error::new_spanned(
+                &found.ty,
+                format!(
+                    "interface `{}` declares no return type for `{}`",
+                    interface.ident, ident
+                ),
+            )),
+            _ => {}
+        }
+
+        errors
+            .into_iter()
+            .reduce(|mut error, next| {
+                error.combine(next);
+                error
+            })
+            .map_or(Ok(()), Err)
+    }
+}
+
+fn method_throws(class: &Ident, ident: &Ident) -> bool {
+    scope::is_throwing_fn(&format!("{}.{}", class, ident))
+}
+
+// `fn name(inputs) -> output` of a method as lowered. A generator method borrows the instance for
+// as long as the generator it returns lives, and a method that throws returns a `Result`.
+fn method_signature(
+    class: &Ident,
+    ident: &Ident,
+    is_static: bool,
+    is_constructor: bool,
+    method: &ObjectMethod,
+) -> syn::Result<TokenStream> {
+    let runtime = encode::runtime();
+    let generics = method.generics.to_rust_token_stream();
+    let inputs = if is_static {
+        let params = method.inputs.to_params_code();
+        quote!((#params))
+    } else {
+        method.inputs.to_method_inputs_code()
+    };
+
+    let output = if let Some(star_token) = &method.star_token {
+        if is_constructor {
+            return Err(syn::Error::new(
+                star_token.spans[0],
+                "a constructor cannot be a generator",
+            ));
+        }
+        let yield_ty = yield_type(method.outputs.as_ref(), star_token.spans[0])?;
+        let lifetime = if is_static {
+            quote!('static)
+        } else {
+            quote!('_)
+        };
+        Some(quote!(-> #runtime::generator::Generator<#lifetime, #yield_ty>))
+    } else if method_throws(class, ident) {
+        let output = method
+            .outputs
+            .as_ref()
+            .map_or_else(|| quote!(()), ToRustCode::to_rust_token_stream);
+        Some(quote!(-> ::std::result::Result<#output, #runtime::error::Error>))
+    } else {
+        method.outputs.as_ref().map(|output| {
+            let ty = output.to_rust_token_stream();
+            quote!(-> #ty)
+        })
+    };
+
+    Ok(quote!(fn #ident #generics #inputs #output))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn method_code(
+    class: &Ident,
+    ident: &Ident,
+    is_static: bool,
+    is_constructor: bool,
+    method: &ObjectMethod,
+    vis: &TokenStream,
+    prelude: &TokenStream,
+    tokens: &mut TokenStream,
+) {
+    let signature = match method_signature(class, ident, is_static, is_constructor, method) {
+        Ok(signature) => signature,
+        Err(err) => {
+            tokens.extend(err.to_compile_error());
+            return;
+        }
+    };
+
+    if let Some(star_token) = &method.star_token {
+        let yield_ty = yield_type(method.outputs.as_ref(), star_token.spans[0]).unwrap();
+        let body = generator_code(&method.inputs, &method.body, &yield_ty);
+        tokens.extend(quote! {
+            #vis #signature {
+                #prelude
+                #body
+            }
+        });
+        return;
+    }
+
+    // Accessors are left out of the walk, as member accesses cannot throw on their errors.
+    let throws = method_throws(class, ident);
+    let is_accessor = scope::accessor(&format!("{}.{}", class, method.ident.ident)).is_some();
+    let target = match (throws, is_accessor) {
+        (true, _) => ThrowTarget::Return,
+        (false, true) => ThrowTarget::Nowhere(
+            "errors cannot be thrown out of an accessor, catch them in its body",
+        ),
+        (false, false) => ThrowTarget::Nowhere(UNTHROWN),
+    };
+    let frame = frame_code(&format!("{}.{}", class, method.ident.ident));
+    let (body, _) = context::with_throw_target(target, || {
+        let mut body = TokenStream::new();
+        if is_constructor {
+            method
+                .inputs
+                .to_constructor_body_code(&method.body, &mut body);
+        } else {
+            method.inputs.to_body_code(&method.body, &mut body);
+        }
+        encode::unbraced(body)
+    });
+    let body = match throws {
+        true => quote!(::std::result::Result::Ok(#body)),
+        false => body,
+    };
+
+    tokens.extend(quote! {
+        #vis #signature {
+            #frame
+            #prelude
+            #body
+        }
+    });
+}
+
+// Accessors become a method named after the property for the getter, and `set_` followed by the
+// name for the setter. Member accesses are rewritten to call them.
+#[derive(Debug)]
+pub struct ClassAccessor {
+    pub static_token: Option<Token![static]>,
+    pub accessor: ObjectAccessor,
+}
+
+impl Parse for ClassAccessor {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            static_token: input.parse()?,
+            accessor: input.parse()?,
+        })
+    }
+}
+
+impl ClassAccessor {
+    fn to_method_code(
+        &self,
+        class: &Ident,
+        vis: &TokenStream,
+        prelude: &TokenStream,
+        tokens: &mut TokenStream,
+    ) {
+        if let Err(err) = self.accessor.check() {
+            tokens.extend(err.to_compile_error());
+            return;
+        }
+
+        let method = &self.accessor.method;
+        let name = &method.ident.ident;
+        let ident = if self.accessor.is_getter() {
+            name.clone()
+        } else {
+            format_ident!("set_{}", name)
+        };
+        method_code(
+            class,
+            &ident,
+            self.static_token.is_some(),
+            false,
+            method,
+            vis,
+            prelude,
+            tokens,
+        );
+    }
+}
+
+// `static { ... }` runs once, before the class is first used.
+#[derive(Debug)]
+pub struct ClassStaticBlock {
+    pub static_token: Token![static],
+    pub block: Block,
+}
+
+impl Parse for ClassStaticBlock {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            static_token: input.parse()?,
+            block: input.parse()?,
+        })
+    }
+}
+
+fn same_type(a: &Type, b: &Type) -> bool {
+    a.to_token_stream().to_string() == b.to_token_stream().to_string()
+}
+
+#[derive(Debug)]
 pub struct ClassTypeAlias {
     pub alias: ItemTypeAlias,
 }
@@ -64,6 +435,7 @@ impl Parse for ClassTypeAlias {
     }
 }
 
+#[derive(Debug)]
 pub struct ImplRestriction {
     pub impl_token: Token![impl],
     pub paren_token: Paren,
@@ -81,18 +453,28 @@ impl Parse for ImplRestriction {
     }
 }
 
+impl ToRustCode for ImplRestriction {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let restrict = &self.restrict;
+        tokens.extend(quote!(pub(in #restrict)));
+    }
+}
+
 enum_impl! {
     pub enum ClassStmtValue {
-        Field(ClassField),
+        StaticBlock(ClassStaticBlock),
+        Accessor(ClassAccessor),
         Method(ClassMethod),
+        Field(ClassField),
         TypeAlias(ClassTypeAlias),
     }
 }
 
+#[derive(Debug)]
 pub struct ClassStmt {
     pub restrict: Option<ImplRestriction>,
     pub vis: ClassVisibility,
-    pub stmt: ObjectStmt,
+    pub stmt: ClassStmtValue,
     pub semi_colon_token: Option<Token![;]>,
 }
 
@@ -111,14 +493,55 @@ impl Parse for ClassStmt {
     }
 }
 
+impl ClassStmt {
+    fn ident(&self) -> Option<&ObjectIdent> {
+        match &self.stmt {
+            ClassStmtValue::Method(method) => Some(&method.method.ident),
+            ClassStmtValue::Accessor(accessor) => Some(&accessor.accessor.method.ident),
+            ClassStmtValue::Field(field) => Some(&field.ident),
+            ClassStmtValue::StaticBlock(_) | ClassStmtValue::TypeAlias(_) => None,
+        }
+    }
+
+    // `#name` members are always private to the generated module, whatever else is written.
+    fn to_vis_code(&self) -> syn::Result<TokenStream> {
+        let is_private_name = self
+            .ident()
+            .is_some_and(|ident| ident.sharp_token.is_some());
+
+        if is_private_name {
+            return match (&self.restrict, &self.vis) {
+                (None, ClassVisibility::Private(_) | ClassVisibility::Inherited) => {
+                    Ok(TokenStream::new())
+                }
+                _ => Err(syn::Error::new(
+                    self.ident().unwrap().ident.span(),
+                    "an access modifier cannot be used with a private name",
+                )),
+            };
+        }
+
+        match (&self.restrict, &self.vis) {
+            (Some(restrict), ClassVisibility::Inherited) => Ok(restrict.to_rust_token_stream()),
+            (Some(restrict), _) => Err(syn::Error::new(
+                restrict.impl_token.span,
+                "`impl(..)` cannot be combined with an access modifier",
+            )),
+            (None, vis) => Ok(vis.to_rust_token_stream()),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ItemClass {
     pub vis: Visibility,
     pub class_token: Token![class],
     pub ident: Ident,
     pub generics: Generics,
     pub bounds: Option<(Token![extends], Punctuated<TypeParamBound, Token![,]>)>,
+    pub implements: Option<(Token![implements], Punctuated<Path, Token![,]>)>,
     pub brace: Brace,
-    pub stmts: Punctuated<ObjectStmt, Option<Token![;]>>,
+    pub stmts: Vec<ClassStmt>,
 }
 
 impl Parse for ItemClass {
@@ -132,10 +555,10 @@ impl Parse for ItemClass {
             let extends_token = input.parse()?;
             let mut bounds = Punctuated::new();
 
-            while !input.peek(Brace) {
+            while !input.peek(Brace) && !input.ipeek::<Token![implements]>() {
                 let next = input.parse()?;
                 bounds.push_value(next);
-                if input.peek(Brace) {
+                if input.peek(Brace) || input.ipeek::<Token![implements]>() {
                     break;
                 }
                 let punct = input.parse()?;
@@ -147,26 +570,32 @@ impl Parse for ItemClass {
             None
         };
 
+        let implements = if input.ipeek::<Token![implements]>() {
+            let implements_token = input.parse()?;
+            let mut interfaces = Punctuated::new();
+
+            while !input.peek(Brace) {
+                let next = input.parse()?;
+                interfaces.push_value(next);
+                if input.peek(Brace) {
+                    break;
+                }
+                let punct = input.parse()?;
+                interfaces.push_punct(punct);
+            }
+
+            Some((implements_token, interfaces))
+        } else {
+            None
+        };
+
         let content;
         let brace = braced!(content in input);
-        let mut stmts = Punctuated::new();
+        let mut stmts = Vec::new();
 
         while !content.is_empty() {
-            let next = input.parse()?;
+            let next = content.parse()?;
             stmts.push(next);
-
-            if content.is_empty() {
-                break;
-            }
-
-            let punct = if content.peek(Token![;]) {
-                let punct = input.parse()?;
-                Some(punct)
-            } else {
-                None
-            };
-
-            stmts.push_punct(punct);
         }
 
         Ok(Self {
@@ -175,8 +604,1116 @@ impl Parse for ItemClass {
             ident,
             generics,
             bounds,
+            implements,
             brace,
             stmts,
         })
     }
 }
+
+impl ItemClass {
+    pub(crate) fn super_class(&self) -> syn::Result<Option<&Path>> {
+        let Some((extends_token, bounds)) = &self.bounds else {
+            return Ok(None);
+        };
+
+        let mut bounds = bounds.iter();
+        let super_class = match bounds.next() {
+            Some(TypeParamBound::Trait(bound))
+                if bound.lifetimes.is_none()
+                    && matches!(bound.modifier, TraitBoundModifier::None) =>
+            {
+                &bound.path
+            }
+            Some(bound) => return Err(syn::Error::new(bound.span(), "expected a class")),
+            None => return Err(syn::Error::new(extends_token.span, "expected a class")),
+        };
+
+        if let Some(bound) = bounds.next() {
+            return Err(syn::Error::new(
+                bound.span(),
+                "a class can only extend a single class",
+            ));
+        }
+
+        Ok(Some(super_class))
+    }
+
+    fn has_method(&self, ident: &Ident) -> bool {
+        self.stmts.iter().any(|stmt| match &stmt.stmt {
+            ClassStmtValue::Method(method) => method.method.ident.ident == *ident,
+            _ => false,
+        })
+    }
+
+    fn has_type(&self, ident: &Ident) -> bool {
+        self.stmts.iter().any(|stmt| match &stmt.stmt {
+            ClassStmtValue::TypeAlias(alias) => alias.alias.ident == *ident,
+            _ => false,
+        })
+    }
+
+    pub fn accessors(&self) -> Vec<(String, Accessor)> {
+        self.stmts
+            .iter()
+            .filter_map(|stmt| {
+                let (name, accessor) = match &stmt.stmt {
+                    ClassStmtValue::Accessor(accessor) => (
+                        &accessor.accessor.method.ident.ident,
+                        Accessor {
+                            getter: accessor.accessor.is_getter(),
+                            setter: !accessor.accessor.is_getter(),
+                            is_static: accessor.static_token.is_some(),
+                        },
+                    ),
+                    ClassStmtValue::Field(field) if field.static_token.is_some() => (
+                        &field.ident.ident,
+                        Accessor {
+                            getter: true,
+                            setter: true,
+                            is_static: true,
+                        },
+                    ),
+                    _ => return None,
+                };
+                Some((format!("{}.{}", self.ident, name), accessor))
+            })
+            .collect()
+    }
+
+    // Methods are known as `Class.name`, and the constructor as `Class.constructor`, which is what
+    // `new` calls.
+    pub fn signatures(&self) -> Vec<(String, Vec<ParamKind>)> {
+        self.stmts
+            .iter()
+            .filter_map(|stmt| {
+                let ClassStmtValue::Method(method) = &stmt.stmt else {
+                    return None;
+                };
+                let kinds = method.method.inputs.signature()?;
+                Some((
+                    format!("{}.{}", self.ident, method.method.ident.ident),
+                    kinds,
+                ))
+            })
+            .collect()
+    }
+
+    // The names of the members of its objects: instance methods, accessors and fields, along with
+    // parameter properties.
+    pub(crate) fn members(&self) -> Vec<String> {
+        let mut members: Vec<String> = self
+            .stmts
+            .iter()
+            .filter(|stmt| match &stmt.stmt {
+                ClassStmtValue::Method(method) => {
+                    method.static_token.is_none() && !method.is_constructor()
+                }
+                ClassStmtValue::Accessor(accessor) => accessor.static_token.is_none(),
+                ClassStmtValue::Field(field) => field.static_token.is_none(),
+                ClassStmtValue::StaticBlock(_) | ClassStmtValue::TypeAlias(_) => false,
+            })
+            .filter_map(|stmt| Some(stmt.ident()?.ident.to_string()))
+            .collect();
+        if let Some(constructor) = self.constructor() {
+            let properties = constructor.method.inputs.properties();
+            members.extend(properties.iter().map(|property| property.ident.to_string()));
+        }
+        members
+    }
+
+    // The types of the fields, getters and methods declared with one, static ones included.
+    pub(crate) fn member_types(&self) -> Vec<(String, Type)> {
+        let types = self.stmts.iter().filter_map(|stmt| {
+            let (ident, ty) = match &stmt.stmt {
+                ClassStmtValue::Field(field) => (&field.ident, field.ty.as_ref()?),
+                ClassStmtValue::Method(method) if method.method.star_token.is_none() => {
+                    (&method.method.ident, method.method.outputs.as_ref()?)
+                }
+                ClassStmtValue::Accessor(accessor) if accessor.accessor.is_getter() => (
+                    &accessor.accessor.method.ident,
+                    accessor.accessor.method.outputs.as_ref()?,
+                ),
+                _ => return None,
+            };
+            Some((ident.ident.to_string(), ty.ty.clone()))
+        });
+        let properties = self.constructor().into_iter().flat_map(|constructor| {
+            constructor
+                .method
+                .inputs
+                .properties()
+                .into_iter()
+                .map(|property| (property.ident.to_string(), property.ty))
+        });
+        types.chain(properties).collect()
+    }
+
+    pub(crate) fn constructor(&self) -> Option<&ClassMethod> {
+        self.stmts.iter().find_map(|stmt| match &stmt.stmt {
+            ClassStmtValue::Method(method) if method.is_constructor() => Some(method),
+            _ => None,
+        })
+    }
+
+    fn is_interface_method(&self, ident: &Ident) -> bool {
+        self.implements
+            .iter()
+            .flat_map(|(_, paths)| paths)
+            .filter_map(|path| scope::interface(&path.segments.last().unwrap().ident))
+            .any(|interface| interface.fns.iter().any(|f| f.ident == *ident))
+    }
+
+    // Instance methods taking the object by reference, other than the constructor, private ones
+    // and those of implemented interfaces. Their parameters are renamed so that calls to them can
+    // be forwarded.
+    pub(crate) fn overridable_methods(&self) -> Vec<Signature> {
+        self.stmts
+            .iter()
+            .filter_map(|stmt| {
+                let ClassStmtValue::Method(method) = &stmt.stmt else {
+                    return None;
+                };
+                let ident = &method.method.ident;
+                if method.static_token.is_some()
+                    || method.is_constructor()
+                    || ident.sharp_token.is_some()
+                    || matches!(stmt.vis, ClassVisibility::Private(_))
+                    || self.is_interface_method(&ident.ident)
+                {
+                    return None;
+                }
+
+                let signature =
+                    method_signature(&self.ident, &ident.ident, false, false, &method.method);
+                let mut signature: Signature = syn::parse2(signature.ok()?).ok()?;
+                signature.receiver()?.reference.as_ref()?;
+                let inputs = signature.inputs.iter_mut().filter_map(|input| match input {
+                    FnArg::Typed(input) => Some(input),
+                    FnArg::Receiver(_) => None,
+                });
+                for (i, input) in inputs.enumerate() {
+                    let arg = encode::private_ident(&format!("arg{}", i));
+                    *input.pat = parse_quote!(#arg);
+                }
+                Some(signature)
+            })
+            .collect()
+    }
+
+    // The classes in scope this one extends, nearest first, instantiated with the generic
+    // arguments they are extended with.
+    fn ancestors(&self) -> Vec<(Path, scope::Class)> {
+        let mut ancestors: Vec<(Path, scope::Class)> = Vec::new();
+        let mut next = self.super_class().ok().flatten().cloned();
+        while let Some(path) = next {
+            let ident = &path.segments.last().unwrap().ident;
+            let seen =
+                *ident == self.ident || ancestors.iter().any(|(_, class)| class.ident == *ident);
+            let Some(class) = scope::class(ident).filter(|_| !seen) else {
+                break;
+            };
+            let class = class.instantiate(&path);
+            next = class.super_class.clone();
+            ancestors.push((path, class));
+        }
+        ancestors
+    }
+}
+
+// The trait of the overridable methods of a class is named after it, as in `ShapeMethods`.
+fn methods_trait(path: &Path) -> Path {
+    let mut path = path.clone();
+    let last = path.segments.last_mut().unwrap();
+    last.ident = format_ident!("{}Methods", last.ident, span = last.ident.span());
+    path
+}
+
+// Calls the method `signature` declares on `receiver`, with the parameters it is declared with.
+fn forward_code(signature: &Signature, callee: TokenStream, receiver: TokenStream) -> TokenStream {
+    let ident = &signature.ident;
+    let params: Vec<_> = signature
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            GenericParam::Const(param) => Some(&param.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    let turbofish = (!params.is_empty()).then(|| quote!(::<#(#params),*>));
+    let args = signature.inputs.iter().filter_map(|input| match input {
+        FnArg::Typed(input) => Some(&input.pat),
+        FnArg::Receiver(_) => None,
+    });
+    quote! {
+        #signature {
+            #callee::#ident #turbofish(#receiver #(, #args)*)
+        }
+    }
+}
+
+// A class becomes a struct holding its fields and an inherent impl holding its methods.
+// Objects are allocated with every field initializer evaluated, then `constructor` runs on them.
+// Fields without an initializer start out as the default of their type, which for a class is an
+// object allocated without running its constructor.
+//
+// A subclass stores its base class in a hidden field and dereferences to it, so inherited
+// members are reachable from the subclass while its own methods shadow the overridden ones.
+impl ToRustCode for ItemClass {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        // `super(...)` throws if the constructor of the base class does.
+        let super_throws = match self.super_class().ok().flatten() {
+            Some(path) => {
+                let class = &path.segments.last().unwrap().ident;
+                scope::throws_on(&format!("{}.constructor", class))
+            }
+            None => ThrowsOn::Never,
+        };
+        // `this` is an object of the class.
+        let ident = &self.ident;
+        let this = (Ident::new("this", ident.span()), Some(parse_quote!(#ident)));
+        scope::with_throwing_fns([("super".to_string(), super_throws)], || {
+            scope::with_variables([this], || {
+                scope::with_this(None, || self.to_class_code(tokens))
+            })
+        });
+    }
+}
+
+impl ItemClass {
+    fn to_class_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let vis = self.vis.to_rust_token_stream();
+        let ident = &self.ident;
+        let generics = self.generics.to_rust_token_stream();
+        let mut impl_generics = TokenStream::new();
+        self.generics.to_impl_generics_code(&mut impl_generics);
+        let mut type_generics = TokenStream::new();
+        self.generics.to_type_generics_code(&mut type_generics);
+        let alloc = encode::private_ident("alloc");
+        let super_field = encode::private_ident("super");
+        let rest = encode::private_ident(&format!("{}Rest", ident));
+        // Bounds on the types of fields are higher-ranked, so that a field which cannot be cloned
+        // leaves the class without a rest instead of failing to compile.
+        let rest_lifetime = Lifetime::new("'__rsscript_rest", Span::mixed_site());
+
+        let mut errors = Vec::new();
+        let mut fields = TokenStream::new();
+        let mut inits = TokenStream::new();
+        let mut defaults = TokenStream::new();
+        let mut methods = TokenStream::new();
+        let mut rest_fields = TokenStream::new();
+        let mut rest_inits = TokenStream::new();
+        let mut rest_updates = TokenStream::new();
+        let mut rest_bounds = TokenStream::new();
+        let rest_value = encode::private_ident("rest");
+        let field_value = encode::private_ident("value");
+
+        let super_class = self.super_class().unwrap_or_else(|err| {
+            errors.push(err);
+            None
+        });
+        // The rest of a subclass holds the rest of its base class, like the object itself does.
+        let super_rest = super_class.map(|super_class| {
+            let mut super_rest = super_class.clone();
+            let last = super_rest.segments.last_mut().unwrap();
+            last.ident = encode::private_ident(&format!("{}Rest", last.ident));
+            super_rest
+        });
+        if let (Some(super_class), Some(super_rest)) = (super_class, &super_rest) {
+            fields.extend(quote!(#super_field: #super_class,));
+            inits.extend(quote!(#super_field: <#super_class>::#alloc(),));
+            rest_fields.extend(quote!(#super_field: #super_rest,));
+            rest_inits
+                .extend(quote!(#super_field: #runtime::class::Rest::rest(&self.#super_field),));
+            rest_updates.extend(quote! {
+                #runtime::class::Rest::update(&mut self.#super_field, #rest_value.#super_field);
+            });
+            rest_bounds.extend(
+                quote!(for<#rest_lifetime> #super_class: #runtime::class::Rest<Rest = #super_rest>,),
+            );
+        }
+        let mut rest_field = |vis: &TokenStream, name: &Ident, ty: &TokenStream| {
+            rest_fields.extend(quote!(#vis #name: ::std::option::Option<#ty>,));
+            rest_inits.extend(quote! {
+                #name: ::std::option::Option::Some(::std::clone::Clone::clone(&self.#name)),
+            });
+            rest_updates.extend(quote! {
+                if let ::std::option::Option::Some(#field_value) = #rest_value.#name {
+                    self.#name = #field_value;
+                }
+            });
+            rest_bounds.extend(quote!(for<#rest_lifetime> #ty: ::std::clone::Clone,));
+        };
+
+        // Members named after a member of an implemented interface go to that interface's impl.
+        let interfaces: Vec<_> = self
+            .implements
+            .iter()
+            .flat_map(|(_, paths)| paths)
+            .map(|path| {
+                let ident = &path.segments.last().unwrap().ident;
+                (
+                    path,
+                    scope::interface(ident).map(|interface| interface.instantiate(path)),
+                )
+            })
+            .collect();
+        let mut impls = vec![TokenStream::new(); interfaces.len()];
+        let fn_owner = |name: &Ident| {
+            interfaces.iter().position(|(_, interface)| {
+                interface
+                    .as_ref()
+                    .is_some_and(|interface| interface.fns.iter().any(|f| f.ident == *name))
+            })
+        };
+        let type_owner = |name: &Ident| {
+            interfaces.iter().position(|(_, interface)| {
+                interface
+                    .as_ref()
+                    .is_some_and(|interface| interface.types.iter().any(|t| t.ident == *name))
+            })
+        };
+
+        // Static blocks run before static members are used and before the first object is made.
+        let static_init = encode::private_ident("static_init");
+        let mut static_blocks = Vec::new();
+        let has_static_blocks = self
+            .stmts
+            .iter()
+            .any(|stmt| matches!(stmt.stmt, ClassStmtValue::StaticBlock(_)));
+        let init_call = if has_static_blocks {
+            quote!(Self::#static_init();)
+        } else {
+            TokenStream::new()
+        };
+
+        for stmt in &self.stmts {
+            let member_vis = match stmt.to_vis_code() {
+                Ok(vis) => vis,
+                Err(err) => {
+                    errors.push(err);
+                    continue;
+                }
+            };
+
+            match &stmt.stmt {
+                ClassStmtValue::Field(field) => {
+                    let name = &field.ident.ident;
+                    let Some(ty) = &field.ty else {
+                        errors.push(syn::Error::new(
+                            name.span(),
+                            format!("class field `{}` needs a type annotation", name),
+                        ));
+                        continue;
+                    };
+                    let ty = ty.to_rust_token_stream();
+
+                    if let Some(static_token) = &field.static_token {
+                        if !self.generics.params.is_empty() {
+                            errors.push(syn::Error::new(
+                                static_token.span,
+                                "a generic class cannot have static fields",
+                            ));
+                            continue;
+                        }
+                        field.to_static_code(&ty, &member_vis, &init_call, &mut methods);
+                        continue;
+                    }
+
+                    let init = match &field.init {
+                        Some(init) => init.expr.to_rust_token_stream(),
+                        None => {
+                            defaults.extend(quote!(#ty: ::std::default::Default,));
+                            quote!(::std::default::Default::default())
+                        }
+                    };
+
+                    fields.extend(quote!(#member_vis #name: #ty,));
+                    inits.extend(quote!(#name: #init,));
+                    if field.ident.sharp_token.is_none() {
+                        rest_field(&member_vis, name, &ty);
+                    }
+                }
+                ClassStmtValue::Method(method) => match fn_owner(&method.method.ident.ident) {
+                    Some(i) => {
+                        let interface = interfaces[i].1.as_ref().unwrap();
+                        if let Err(err) = method.check_conformance(interface) {
+                            errors.push(err);
+                        }
+                        method.to_method_code(
+                            &self.ident,
+                            &TokenStream::new(),
+                            &TokenStream::new(),
+                            &mut impls[i],
+                        );
+                    }
+                    None => {
+                        let prelude = match method.static_token {
+                            Some(_) => init_call.clone(),
+                            None => TokenStream::new(),
+                        };
+                        method.to_method_code(&self.ident, &member_vis, &prelude, &mut methods);
+                    }
+                },
+                ClassStmtValue::Accessor(accessor) => {
+                    let prelude = match accessor.static_token {
+                        Some(_) => init_call.clone(),
+                        None => TokenStream::new(),
+                    };
+                    accessor.to_method_code(&self.ident, &member_vis, &prelude, &mut methods);
+                }
+                ClassStmtValue::StaticBlock(block) => static_blocks.push(&block.block),
+                ClassStmtValue::TypeAlias(alias) => match type_owner(&alias.alias.ident) {
+                    Some(i) => {
+                        let alias = &alias.alias;
+                        let type_token = &alias.type_token;
+                        let ident = &alias.ident;
+                        let generics = alias.generics.to_rust_token_stream();
+                        match alias.alias.rust_type() {
+                            Ok(ty) => impls[i].extend(quote!(#type_token #ident #generics = #ty;)),
+                            Err(err) => errors.push(err),
+                        }
+                    }
+                    None => errors.push(syn::Error::new(
+                        alias.alias.type_token.span,
+                        "type aliases cannot be declared inside a class",
+                    )),
+                },
+            }
+        }
+
+        if !static_blocks.is_empty() {
+            let started = Ident::new("STARTED", Span::mixed_site());
+            let frame = frame_code(&format!("{}.static", self.ident));
+            let target = ThrowTarget::Nowhere(
+                "errors cannot be thrown out of a static block, catch them in the block",
+            );
+            let (blocks, _) = context::with_throw_target(target, || {
+                let mut blocks = TokenStream::new();
+                for block in &static_blocks {
+                    block.to_unit_code(&mut blocks);
+                }
+                blocks
+            });
+
+            // A static block using the class calls back in here, so this is not a `Once`.
+            methods.extend(quote! {
+                #[doc(hidden)]
+                fn #static_init() {
+                    static #started: ::std::sync::atomic::AtomicBool =
+                        ::std::sync::atomic::AtomicBool::new(false);
+                    if !#started.swap(true, ::std::sync::atomic::Ordering::SeqCst) {
+                        #frame
+                        #blocks
+                    }
+                }
+            });
+        }
+
+        for ((_, interface), body) in interfaces.iter().zip(&mut impls) {
+            let Some(interface) = interface else {
+                continue;
+            };
+
+            for f in &interface.fns {
+                if !f.has_default && !self.has_method(&f.ident) {
+                    errors.push(syn::Error::new(
+                        self.ident.span(),
+                        format!(
+                            "class `{}` is missing `{}` required by interface `{}`",
+                            self.ident, f.ident, interface.ident
+                        ),
+                    ));
+                }
+            }
+
+            for t in &interface.types {
+                if self.has_type(&t.ident) {
+                    continue;
+                }
+                match &t.default {
+                    Some(default) => {
+                        let ident = &t.ident;
+                        body.extend(quote!(type #ident = #default;));
+                    }
+                    None => errors.push(syn::Error::new(
+                        self.ident.span(),
+                        format!(
+                            "class `{}` is missing type `{}` required by interface `{}`",
+                            self.ident, t.ident, interface.ident
+                        ),
+                    )),
+                }
+            }
+        }
+
+        let throws = scope::is_throwing_fn(&format!("{}.constructor", self.ident));
+        let result = quote!(::std::result::Result);
+        let param_tys: Vec<_> = match self.constructor() {
+            Some(constructor) => {
+                for property in constructor.method.inputs.properties() {
+                    let name = &property.ident;
+                    let ty = &property.ty;
+                    let vis = property.vis.to_rust_token_stream();
+                    defaults.extend(quote!(#ty: ::std::default::Default,));
+                    fields.extend(quote!(#vis #name: #ty,));
+                    inits.extend(quote!(#name: ::std::default::Default::default(),));
+                    rest_field(&vis, name, &ty.to_token_stream());
+                }
+
+                constructor.method.inputs.rust_types()
+            }
+            None => {
+                // Like JavaScript's implicit constructor, but arguments are not forwarded.
+                let body = super_class.map(|_| {
+                    quote! {
+                        <<Self as #runtime::class::Extends>::Super>::constructor(
+                            ::std::ops::DerefMut::deref_mut(self),
+                        )
+                    }
+                });
+                methods.extend(match (throws, body) {
+                    (true, body) => quote! {
+                        pub fn constructor(&mut self) -> #result<(), #runtime::error::Error> {
+                            #body
+                        }
+                    },
+                    (false, Some(body)) => quote! {
+                        pub fn constructor(&mut self) {
+                            #body;
+                        }
+                    },
+                    (false, None) => quote! {
+                        pub fn constructor(&mut self) {}
+                    },
+                });
+                Vec::new()
+            }
+        };
+        let args: Vec<_> = (0..param_tys.len())
+            .map(|i| encode::private_ident(&format!("arg{}", i)))
+            .collect();
+        // A constructor that throws makes `new` give a `Result` of the object.
+        let (output, construct) = match throws {
+            true => (
+                quote!(#result<Self, #runtime::error::Error>),
+                quote! {
+                    this.constructor(#(#args),*)?;
+                    #result::Ok(this)
+                },
+            ),
+            false => (
+                quote!(Self),
+                quote! {
+                    this.constructor(#(#args),*);
+                    this
+                },
+            ),
+        };
+
+        for err in errors {
+            tokens.extend(err.to_compile_error());
+        }
+
+        let struct_token = Token![struct](self.class_token.span);
+        tokens.extend(quote! {
+            #vis #struct_token #ident #generics {
+                #fields
+            }
+
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #vis struct #rest #generics {
+                #rest_fields
+            }
+
+            impl #impl_generics #runtime::class::Rest for #ident #type_generics
+            where
+                #rest_bounds
+            {
+                type Rest = #rest #type_generics;
+
+                fn rest(&self) -> Self::Rest {
+                    #rest {
+                        #rest_inits
+                    }
+                }
+
+                #[allow(unused_variables)]
+                fn update(&mut self, #rest_value: Self::Rest) {
+                    #rest_updates
+                }
+            }
+
+            impl #impl_generics #ident #type_generics {
+                pub fn new(#(#args: #param_tys),*) -> #output
+                where
+                    #defaults
+                {
+                    #init_call
+                    let mut this = Self::#alloc();
+                    #construct
+                }
+
+                #[doc(hidden)]
+                pub fn #alloc() -> Self
+                where
+                    #defaults
+                {
+                    Self {
+                        #inits
+                    }
+                }
+
+                #methods
+            }
+
+            impl #impl_generics #runtime::Constructor<fn(#(#param_tys),*) -> #output>
+                for #ident #type_generics
+            where
+                #defaults
+            {
+                const constructor: fn(#(#param_tys),*) -> #output = Self::new;
+            }
+
+            impl #impl_generics ::std::default::Default for #ident #type_generics
+            where
+                #defaults
+            {
+                fn default() -> Self {
+                    Self::#alloc()
+                }
+            }
+        });
+
+        for ((path, _), body) in interfaces.iter().zip(impls) {
+            tokens.extend(quote! {
+                impl #impl_generics #path for #ident #type_generics {
+                    #body
+                }
+            });
+        }
+
+        // The overridable methods are also declared by a trait named after the class, which the
+        // classes extending it implement too, calling their own overrides. A subclass can then be
+        // used where the class is expected, as `impl ShapeMethods` or `dyn ShapeMethods`.
+        let own_methods = self.overridable_methods();
+        let overrides = |ident: &Ident| own_methods.iter().any(|method| method.ident == *ident);
+        let ancestors = self.ancestors();
+        let mut inherited = HashSet::new();
+        for (path, class) in ancestors.iter().rev() {
+            let Some(super_class) = super_class else {
+                break;
+            };
+            let methods_trait = methods_trait(path);
+            let forwards = class
+                .methods
+                .iter()
+                .filter(|method| inherited.insert(method.ident.to_string()))
+                .map(|method| {
+                    if overrides(&method.ident) {
+                        return forward_code(method, quote!(Self), quote!(self));
+                    }
+                    let receiver = match method.receiver().unwrap().mutability {
+                        Some(_) => quote!(::std::ops::DerefMut::deref_mut(self)),
+                        None => quote!(::std::ops::Deref::deref(self)),
+                    };
+                    forward_code(method, quote!(<#super_class as #methods_trait>), receiver)
+                });
+            tokens.extend(quote! {
+                impl #impl_generics #methods_trait for #ident #type_generics {
+                    #(#forwards)*
+                }
+            });
+        }
+
+        let own_trait = format_ident!("{}Methods", ident, span = ident.span());
+        let supertrait = ancestors.first().map(|(path, _)| {
+            let path = methods_trait(path);
+            quote!(: #path)
+        });
+        let own_methods: Vec<_> = own_methods
+            .iter()
+            .filter(|method| !inherited.contains(&method.ident.to_string()))
+            .collect();
+        let forwards = own_methods
+            .iter()
+            .map(|method| forward_code(method, quote!(Self), quote!(self)));
+        tokens.extend(quote! {
+            #[allow(dead_code)]
+            #vis trait #own_trait #generics #supertrait {
+                #(#own_methods;)*
+            }
+
+            impl #impl_generics #own_trait #type_generics for #ident #type_generics {
+                #(#forwards)*
+            }
+        });
+
+        if let (Some(super_class), Some(super_rest)) = (super_class, super_rest) {
+            tokens.extend(quote! {
+                impl #impl_generics ::std::ops::Deref for #ident #type_generics {
+                    type Target = #super_class;
+
+                    fn deref(&self) -> &Self::Target {
+                        &self.#super_field
+                    }
+                }
+
+                impl #impl_generics ::std::ops::DerefMut for #ident #type_generics {
+                    fn deref_mut(&mut self) -> &mut Self::Target {
+                        &mut self.#super_field
+                    }
+                }
+
+                impl #impl_generics ::std::ops::Deref for #rest #type_generics {
+                    type Target = #super_rest;
+
+                    fn deref(&self) -> &Self::Target {
+                        &self.#super_field
+                    }
+                }
+
+                impl #impl_generics ::std::ops::DerefMut for #rest #type_generics {
+                    fn deref_mut(&mut self) -> &mut Self::Target {
+                        &mut self.#super_field
+                    }
+                }
+
+                impl #impl_generics #runtime::class::Extends for #ident #type_generics {
+                    type Super = #super_class;
+
+                    fn into_super(self) -> Self::Super {
+                        self.#super_field
+                    }
+                }
+
+                impl #impl_generics ::std::convert::From<#ident #type_generics> for #super_class {
+                    fn from(value: #ident #type_generics) -> Self {
+                        value.#super_field
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::stmt::Block;
+
+    #[test]
+    fn class_to_rust_code() {
+        let class: ItemClass = syn::parse_str(
+            "export class Point<T> {
+                x: Number;
+                #y: Number;
+                protected tag: T
+                private scale(factor: Number): Number {}
+                static origin() {}
+            }",
+        )
+        .unwrap();
+        let frame = |name: &str| quote!(let __rsscript_frame = ::rsscript::types::error::Frame::enter(#name););
+        let (scale, origin) = (frame("Point.scale"), frame("Point.origin"));
+
+        assert_eq!(
+            class.to_rust_token_stream().to_string(),
+            quote! {
+                pub struct Point<T> {
+                    pub x: Number,
+                    y: Number,
+                    pub(crate) tag: T,
+                }
+
+                #[doc(hidden)]
+                #[allow(non_camel_case_types)]
+                pub struct __rsscript_PointRest<T> {
+                    pub x: ::std::option::Option<Number>,
+                    pub(crate) tag: ::std::option::Option<T>,
+                }
+
+                impl<T> ::rsscript::types::class::Rest for Point<T>
+                where
+                    for<'__rsscript_rest> Number: ::std::clone::Clone,
+                    for<'__rsscript_rest> T: ::std::clone::Clone,
+                {
+                    type Rest = __rsscript_PointRest<T>;
+
+                    fn rest(&self) -> Self::Rest {
+                        __rsscript_PointRest {
+                            x: ::std::option::Option::Some(::std::clone::Clone::clone(&self.x)),
+                            tag: ::std::option::Option::Some(::std::clone::Clone::clone(&self.tag)),
+                        }
+                    }
+
+                    #[allow(unused_variables)]
+                    fn update(&mut self, __rsscript_rest: Self::Rest) {
+                        if let ::std::option::Option::Some(__rsscript_value) = __rsscript_rest.x {
+                            self.x = __rsscript_value;
+                        }
+                        if let ::std::option::Option::Some(__rsscript_value) = __rsscript_rest.tag {
+                            self.tag = __rsscript_value;
+                        }
+                    }
+                }
+
+                impl<T> Point<T> {
+                    pub fn new() -> Self
+                    where
+                        Number: ::std::default::Default,
+                        Number: ::std::default::Default,
+                        T: ::std::default::Default,
+                    {
+                        let mut this = Self::__rsscript_alloc();
+                        this.constructor();
+                        this
+                    }
+
+                    #[doc(hidden)]
+                    pub fn __rsscript_alloc() -> Self
+                    where
+                        Number: ::std::default::Default,
+                        Number: ::std::default::Default,
+                        T: ::std::default::Default,
+                    {
+                        Self {
+                            x: ::std::default::Default::default(),
+                            y: ::std::default::Default::default(),
+                            tag: ::std::default::Default::default(),
+                        }
+                    }
+
+                    fn scale(&mut self, factor: Number) -> Number { #scale {} }
+                    pub fn origin() { #origin {} }
+                    pub fn constructor(&mut self) {}
+                }
+
+                impl<T> ::rsscript::types::Constructor<fn() -> Self> for Point<T>
+                where
+                    Number: ::std::default::Default,
+                    Number: ::std::default::Default,
+                    T: ::std::default::Default,
+                {
+                    const constructor: fn() -> Self = Self::new;
+                }
+
+                impl<T> ::std::default::Default for Point<T>
+                where
+                    Number: ::std::default::Default,
+                    Number: ::std::default::Default,
+                    T: ::std::default::Default,
+                {
+                    fn default() -> Self {
+                        Self::__rsscript_alloc()
+                    }
+                }
+
+                #[allow(dead_code)]
+                pub trait PointMethods<T> {}
+
+                impl<T> PointMethods<T> for Point<T> {}
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn class_with_super_class() {
+        let class: ItemClass = syn::parse_str(
+            "class Dog extends Animal {
+                constructor(name: JsString) {}
+                speak(): JsString {}
+            }",
+        )
+        .unwrap();
+        let code = class.to_rust_token_stream().to_string();
+
+        assert!(code.contains(
+            &quote!(
+                struct Dog {
+                    __rsscript_super: Animal,
+                }
+            )
+            .to_string()
+        ));
+        assert!(code.contains(&quote!(pub fn new(__rsscript_arg0: JsString) -> Self).to_string()));
+        assert!(code.contains(&quote!(__rsscript_super: <Animal>::__rsscript_alloc(),).to_string()));
+        assert!(code.contains(
+            &quote! {
+                impl ::std::ops::Deref for Dog {
+                    type Target = Animal;
+
+                    fn deref(&self) -> &Self::Target {
+                        &self.__rsscript_super
+                    }
+                }
+            }
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote! {
+                impl ::rsscript::types::class::Extends for Dog {
+                    type Super = Animal;
+
+                    fn into_super(self) -> Self::Super {
+                        self.__rsscript_super
+                    }
+                }
+            }
+            .to_string()
+        ));
+    }
+
+    #[test]
+    fn class_with_several_super_classes() {
+        let class: ItemClass = syn::parse_str("class Bat extends Mammal, Bird {}").unwrap();
+
+        assert!(class
+            .to_rust_token_stream()
+            .to_string()
+            .contains("a class can only extend a single class"));
+    }
+
+    #[test]
+    fn private_name_with_modifier() {
+        let class: ItemClass = syn::parse_str("class Counter { public #count: Number }").unwrap();
+
+        assert!(class
+            .to_rust_token_stream()
+            .to_string()
+            .contains("an access modifier cannot be used with a private name"));
+    }
+
+    #[test]
+    fn overridable_methods() {
+        let block: Block = syn::parse_str(
+            "{
+                class Animal { speak(times: Number): JsString {} private hide() {} }
+                class Dog extends Animal { fetch() {} }
+                class Puppy extends Dog { speak(times: Number): JsString {} }
+            }",
+        )
+        .unwrap();
+        let code = block.to_rust_token_stream().to_string();
+
+        assert!(code.contains(
+            &quote!(
+                trait AnimalMethods {
+                    fn speak(&mut self, __rsscript_arg0: Number) -> JsString;
+                }
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote!(
+                trait DogMethods: AnimalMethods {
+                    fn fetch(&mut self);
+                }
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote!(
+                impl AnimalMethods for Dog {
+                    fn speak(&mut self, __rsscript_arg0: Number) -> JsString {
+                        <Animal as AnimalMethods>::speak(
+                            ::std::ops::DerefMut::deref_mut(self),
+                            __rsscript_arg0
+                        )
+                    }
+                }
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote!(
+                impl AnimalMethods for Puppy {
+                    fn speak(&mut self, __rsscript_arg0: Number) -> JsString {
+                        Self::speak(self, __rsscript_arg0)
+                    }
+                }
+            )
+            .to_string()
+        ));
+        assert!(code.contains(
+            &quote!(
+                impl DogMethods for Puppy {
+                    fn fetch(&mut self) {
+                        <Dog as DogMethods>::fetch(::std::ops::DerefMut::deref_mut(self))
+                    }
+                }
+            )
+            .to_string()
+        ));
+    }
+
+    #[test]
+    fn accessors_and_statics() {
+        let class: ItemClass = syn::parse_str(
+            "class Temperature {
+                static unit: JsString = \"C\";
+                get celsius(): Number {}
+                set celsius(value: Number) {}
+                get kelvin(offset: Number): Number {}
+            }",
+        )
+        .unwrap();
+        let code = class.to_rust_token_stream().to_string();
+
+        assert!(code.contains(&quote!(pub fn celsius(&mut self) -> Number).to_string()));
+        assert!(code.contains(&quote!(pub fn set_celsius(&mut self, value: Number)).to_string()));
+        assert!(code.contains(&quote!(pub fn unit() -> JsString).to_string()));
+        assert!(code.contains(&quote!(pub fn set_unit(__rsscript_value: JsString)).to_string()));
+        assert!(code.contains("a getter cannot have parameters"));
+
+        let generic: ItemClass =
+            syn::parse_str("class Cell<T> { static count: Number = 0; }").unwrap();
+        assert!(generic
+            .to_rust_token_stream()
+            .to_string()
+            .contains("a generic class cannot have static fields"));
+    }
+
+    #[test]
+    fn single_expression_bodies() {
+        let class: ItemClass = syn::parse_str(
+            "class Point {
+                x: Number = 0;
+                get left(): Number { this.x }
+                moved(): Number { (this.x) }
+            }",
+        )
+        .unwrap();
+        let code = class.to_rust_token_stream().to_string();
+
+        // The body is not nested in a block of its own.
+        let body = |name: &str| {
+            let body = quote!(let __rsscript_frame = ::rsscript::types::error::Frame::enter(#name); self.x);
+            format!("{{ {} }}", body)
+        };
+        assert!(code.contains(&body("Point.left")));
+        assert!(code.contains(&body("Point.moved")));
+    }
+
+    #[test]
+    fn generic_interface_conformance() {
+        let file: crate::parser::File = syn::parse_str(
+            "interface Baz<T> { function get(): T; function put(value: T, n: Number); }
+            class Good implements Baz<Number> { get(): Number {} put(value: Number, n: Number) {} }
+            class Bad implements Baz<JsString> { get(): Number {} put(value: JsString) {} }",
+        )
+        .unwrap();
+        let code = file.to_rust_token_stream().to_string();
+
+        assert!(code.contains(&quote!(impl Baz<Number> for Good).to_string()));
+        assert!(code.contains(
+            "expected return type `JsString` as declared by interface `Baz`, found `Number`"
+        ));
+        assert!(code.contains("`put` takes 1 argument(s) but interface `Baz` expects 2"));
+    }
+}
@@ -0,0 +1,264 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{braced, parse::Parse, punctuated::Punctuated, token::Brace, Ident, Lit};
+
+use crate::{
+    encode::{
+        self,
+        scope::{Enum, EnumValue},
+        ToRustCode,
+    },
+    parser::{
+        expr::{Expr, ExprLit, ExprUnary, UnOp},
+        restrinction::Visibility,
+    },
+    Token,
+};
+
+#[derive(Debug)]
+pub struct ItemEnum {
+    pub vis: Visibility,
+    pub const_token: Option<Token![const]>,
+    pub enum_token: Token![enum],
+    pub ident: Ident,
+    pub brace_token: Brace,
+    pub members: Punctuated<EnumMember, Token![,]>,
+}
+
+#[derive(Debug)]
+pub struct EnumMember {
+    pub ident: Ident,
+    pub init: Option<(Token![=], Expr)>,
+}
+
+impl Parse for EnumMember {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let ident = input.parse()?;
+        let init = if input.peek(Token![=]) {
+            Some((input.parse()?, input.parse()?))
+        } else {
+            None
+        };
+
+        Ok(Self { ident, init })
+    }
+}
+
+impl Parse for ItemEnum {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+
+        Ok(Self {
+            vis: input.parse()?,
+            const_token: input.parse()?,
+            enum_token: input.parse()?,
+            ident: input.parse()?,
+            brace_token: braced!(content in input),
+            members: content.parse_terminated(EnumMember::parse, Token![,])?,
+        })
+    }
+}
+
+impl ItemEnum {
+    // Members without an initializer count up from the previous numeric member, or from zero.
+    pub fn values(&self) -> syn::Result<Vec<(&Ident, EnumValue)>> {
+        let mut values: Vec<(&Ident, EnumValue)> = Vec::new();
+        let mut next = Some(0f64);
+
+        for member in &self.members {
+            if values.iter().any(|(ident, _)| *ident == &member.ident) {
+                return Err(syn::Error::new(
+                    member.ident.span(),
+                    format!("duplicate enum member `{}`", member.ident),
+                ));
+            }
+
+            let value = match (&member.init, next) {
+                (Some((_, init)), _) => literal_value(&member.ident, init)?,
+                (None, Some(next)) => EnumValue::Number(next),
+                (None, None) => {
+                    return Err(syn::Error::new(
+                        member.ident.span(),
+                        "enum member must have an initializer",
+                    ))
+                }
+            };
+            next = match value {
+                EnumValue::Number(value) => Some(value + 1.0),
+                EnumValue::String(_) => None,
+            };
+            values.push((&member.ident, value));
+        }
+
+        let strings = values
+            .iter()
+            .filter(|(_, value)| matches!(value, EnumValue::String(_)))
+            .count();
+        if strings != 0 && strings != values.len() {
+            return Err(syn::Error::new(
+                self.ident.span(),
+                "an enum cannot mix number and string members",
+            ));
+        }
+
+        Ok(values)
+    }
+
+    pub fn to_scope(&self) -> (Ident, Enum) {
+        let members = self
+            .values()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(ident, value)| (ident.to_string(), value))
+            .collect();
+
+        (
+            self.ident.clone(),
+            Enum {
+                is_const: self.const_token.is_some(),
+                members,
+            },
+        )
+    }
+}
+
+fn literal_value(ident: &Ident, expr: &Expr) -> syn::Result<EnumValue> {
+    let number = |lit: &Lit| match lit {
+        Lit::Int(lit) => lit.base10_parse().map(Some),
+        Lit::Float(lit) => lit.base10_parse().map(Some),
+        _ => Ok(None),
+    };
+
+    let value = match expr {
+        Expr::Lit(ExprLit { lit: Lit::Str(lit) }) => Some(EnumValue::String(lit.value())),
+        Expr::Lit(ExprLit { lit }) => number(lit)?.map(EnumValue::Number),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+        }) => match &**expr {
+            Expr::Lit(ExprLit { lit }) => number(lit)?.map(|value: f64| EnumValue::Number(-value)),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    value.ok_or_else(|| {
+        syn::Error::new(
+            ident.span(),
+            "enum member initializer must be a number or string literal",
+        )
+    })
+}
+
+// The same code a literal of the value lowers to.
+pub fn value_code(value: &EnumValue) -> TokenStream {
+    let runtime = encode::runtime();
+
+    match value {
+        EnumValue::Number(value) => quote!(#runtime::number::Number::from(#value)),
+        EnumValue::String(value) => {
+            quote!(#runtime::string::JsString::new(::std::string::String::from(#value)))
+        }
+    }
+}
+
+// A `const enum` leaves nothing but a type alias, since its members are inlined. Other enums
+// become a Rust enum converting to and from the type of their values. Numeric ones also get the
+// reverse mapping from a value to a member name, which is `None` where JavaScript gives
+// `undefined`.
+impl ToRustCode for ItemEnum {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let values = match self.values() {
+            Ok(values) => values,
+            Err(err) => {
+                tokens.extend(err.to_compile_error());
+                return;
+            }
+        };
+        let runtime = encode::runtime();
+        let vis = self.vis.to_rust_token_stream();
+        let ident = &self.ident;
+        let numeric = !values
+            .iter()
+            .any(|(_, value)| matches!(value, EnumValue::String(_)));
+        let value_ty = if numeric {
+            quote!(#runtime::number::Number)
+        } else {
+            quote!(#runtime::string::JsString)
+        };
+
+        if self.const_token.is_some() {
+            tokens.extend(quote!(#vis type #ident = #value_ty;));
+            return;
+        }
+
+        let enum_token = &self.enum_token;
+        let members: Vec<&Ident> = values.iter().map(|(ident, _)| *ident).collect();
+        let names = members.iter().map(ToString::to_string);
+        let value_codes: Vec<TokenStream> =
+            values.iter().map(|(_, value)| value_code(value)).collect();
+        let value = encode::private_ident("value");
+        let key = encode::private_ident("key");
+        let message = format!("{{}} is not a member of enum `{}`", ident);
+
+        tokens.extend(quote! {
+            #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+            #[allow(non_camel_case_types)]
+            #vis #enum_token #ident {
+                #(#members),*
+            }
+
+            impl ::std::convert::From<#ident> for #value_ty {
+                fn from(#value: #ident) -> Self {
+                    match #value {
+                        #(#ident::#members => #value_codes),*
+                    }
+                }
+            }
+
+            impl ::std::convert::TryFrom<#value_ty> for #ident {
+                type Error = #runtime::error::RangeError;
+
+                fn try_from(#value: #value_ty) -> ::std::result::Result<Self, Self::Error> {
+                    #(
+                        if #value == #value_codes {
+                            return ::std::result::Result::Ok(Self::#members);
+                        }
+                    )*
+                    ::std::result::Result::Err(#runtime::error::RangeError(::std::format!(
+                        #message,
+                        #value
+                    )))
+                }
+            }
+
+            impl ::std::fmt::Display for #ident {
+                fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                    ::std::fmt::Display::fmt(&<#value_ty>::from(*self), f)
+                }
+            }
+        });
+
+        if numeric {
+            tokens.extend(quote! {
+                impl #ident {
+                    #[doc(hidden)]
+                    #vis fn #key(
+                        #value: #runtime::number::Number,
+                    ) -> ::std::option::Option<#runtime::string::JsString> {
+                        #(
+                            if #value == #value_codes {
+                                return ::std::option::Option::Some(
+                                    #runtime::string::JsString::new(
+                                        ::std::string::String::from(#names),
+                                    ),
+                                );
+                            }
+                        )*
+                        ::std::option::Option::None
+                    }
+                }
+            });
+        }
+    }
+}
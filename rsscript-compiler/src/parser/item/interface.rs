@@ -1,14 +1,24 @@
+use proc_macro2::TokenStream;
+use quote::quote;
 use syn::{
-    braced, parse::Parse, punctuated::Punctuated, token::Brace, Ident, Type, TypeParamBound,
+    braced, parse::Parse, parse_quote, punctuated::Punctuated, token::Brace, Ident, Type,
+    TypeParamBound,
 };
 
 use crate::{
+    encode::{
+        self,
+        context::{self, ThrowTarget},
+        scope::{self, ParamKind},
+        ToRustCode,
+    },
     parser::{generics::Generics, restrinction::Visibility, stmt::Block, token::IdentPeeker},
     Token,
 };
 
-use super::{FnArgs, TypeAnnotation};
+use super::{FnArgs, TypeAnnotation, UNTHROWN};
 
+#[derive(Debug)]
 pub struct ItemInterface {
     pub vis: Visibility,
     pub interface_token: Token![interface],
@@ -55,7 +65,7 @@ impl Parse for ItemInterface {
         let mut items = Vec::new();
 
         while !content.is_empty() {
-            let item = input.parse()?;
+            let item = content.parse()?;
             items.push(item);
         }
 
@@ -71,6 +81,50 @@ impl Parse for ItemInterface {
     }
 }
 
+impl ItemInterface {
+    pub fn signatures(&self) -> Vec<(String, Vec<ParamKind>)> {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                InterfaceItem::Function(f) => {
+                    Some((format!("{}.{}", self.ident, f.ident), f.inputs.signature()?))
+                }
+                InterfaceItem::Type(_) => None,
+            })
+            .collect()
+    }
+}
+
+impl ToRustCode for ItemInterface {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let vis = self.vis.to_rust_token_stream();
+        let trait_token = Token![trait](self.interface_token.span);
+        let ident = &self.ident;
+        let generics = self.generics.to_rust_token_stream();
+        let supertraits = self.bounds.as_ref().map(|(_, bounds)| quote!(: #bounds));
+        // `this` in a default method is an object implementing the interface.
+        let this = (Ident::new("this", ident.span()), Some(parse_quote!(#ident)));
+        let mut items = TokenStream::new();
+        scope::with_variables([this], || {
+            scope::with_this(None, || {
+                for item in &self.items {
+                    match item {
+                        InterfaceItem::Function(item) => item.to_trait_code(ident, &mut items),
+                        InterfaceItem::Type(item) => item.to_rust_code(&mut items),
+                    }
+                }
+            })
+        });
+
+        tokens.extend(quote! {
+            #vis #trait_token #ident #generics #supertraits {
+                #items
+            }
+        });
+    }
+}
+
+#[derive(Debug)]
 pub enum InterfaceItem {
     Function(InterfaceItemFn),
     Type(InterfaceItemType),
@@ -88,6 +142,7 @@ impl Parse for InterfaceItem {
     }
 }
 
+#[derive(Debug)]
 pub struct InterfaceItemFn {
     pub function_token: Token![function],
     pub ident: Ident,
@@ -104,7 +159,7 @@ impl Parse for InterfaceItemFn {
         let ident = input.parse()?;
         let generics = input.parse()?;
         let inputs = input.parse()?;
-        let output = if input.peek(Token![=]) {
+        let output = if input.peek(Token![:]) {
             Some(input.parse()?)
         } else {
             None
@@ -129,6 +184,56 @@ impl Parse for InterfaceItemFn {
     }
 }
 
+impl InterfaceItemFn {
+    // Like methods, it is known as `Interface.name`, and the implementations of a method that
+    // throws return a `Result` too.
+    fn to_trait_code(&self, interface: &Ident, tokens: &mut TokenStream) {
+        let fn_token = Token![fn](self.function_token.span);
+        let ident = &self.ident;
+        let generics = self.generics.to_rust_token_stream();
+        let inputs = self.inputs.to_method_inputs_code();
+        let throws = scope::is_throwing_fn(&format!("{}.{}", interface, ident));
+        let output = match (throws, &self.output) {
+            (true, output) => {
+                let runtime = encode::runtime();
+                let ty = output
+                    .as_ref()
+                    .map_or_else(|| quote!(()), ToRustCode::to_rust_token_stream);
+                Some(quote!(-> ::std::result::Result<#ty, #runtime::error::Error>))
+            }
+            (false, Some(output)) => {
+                let ty = output.to_rust_token_stream();
+                Some(quote!(-> #ty))
+            }
+            (false, None) => None,
+        };
+        let target = match throws {
+            true => ThrowTarget::Return,
+            false => ThrowTarget::Nowhere(UNTHROWN),
+        };
+        let body = match &self.default {
+            Some(block) => {
+                let (body, _) = context::with_throw_target(target, || {
+                    let mut body = TokenStream::new();
+                    self.inputs.to_body_code(block, &mut body);
+                    body
+                });
+                match throws {
+                    true => {
+                        let body = encode::unbraced(body);
+                        quote!({ ::std::result::Result::Ok(#body) })
+                    }
+                    false => body,
+                }
+            }
+            None => quote!(;),
+        };
+
+        tokens.extend(quote!(#fn_token #ident #generics #inputs #output #body));
+    }
+}
+
+#[derive(Debug)]
 pub struct InterfaceItemType {
     pub type_token: Token![type],
     pub ident: Ident,
@@ -147,11 +252,16 @@ impl Parse for InterfaceItemType {
             let extends_token = input.parse()?;
             let mut bounds = Punctuated::new();
 
-            let check_next = || input.peek(Token![;]) || input.ipeek::<Token![function]>();
+            let check_next = || {
+                input.is_empty()
+                    || input.peek(Token![;])
+                    || input.peek(Token![=])
+                    || input.ipeek::<Token![function]>()
+            };
 
             loop {
                 let next = input.parse()?;
-                bounds.push_punct(next);
+                bounds.push_value(next);
 
                 if check_next() {
                     break;
@@ -191,3 +301,15 @@ impl Parse for InterfaceItemType {
         })
     }
 }
+
+// Associated type defaults are unstable in Rust, so they are filled in by implementing classes.
+impl ToRustCode for InterfaceItemType {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let type_token = &self.type_token;
+        let ident = &self.ident;
+        let generics = self.generics.to_rust_token_stream();
+        let bounds = self.bounds.as_ref().map(|(_, bounds)| quote!(: #bounds));
+
+        tokens.extend(quote!(#type_token #ident #generics #bounds;));
+    }
+}
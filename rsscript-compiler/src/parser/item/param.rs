@@ -0,0 +1,98 @@
+use syn::{bracketed, parse::Parse, parse_quote, token::Bracket, Ident, Type};
+
+use crate::{
+    encode::{self, scope::ParamKind},
+    parser::{expr::Expr, pat::Pattern, restrinction::ClassVisibility},
+    Token,
+};
+
+use super::TypeAnnotation;
+
+// `x: T`, `x?: T`, `x: T = value` or `...rest: T[]`, where `x` may also be a destructuring pattern.
+// In a constructor, a parameter marked `public`, `private` or `protected` is a parameter property.
+#[derive(Debug)]
+pub struct Param {
+    pub vis: ClassVisibility,
+    pub rest_token: Option<Token![...]>,
+    pub pat: Pattern,
+    pub question_token: Option<Token![?]>,
+    pub ty: TypeAnnotation,
+    pub default: Option<(Token![=], Expr)>,
+}
+
+impl Parse for Param {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let vis = input.parse()?;
+        let rest_token: Option<Token![...]> = input.parse()?;
+        let pat = input.parse()?;
+        let question_token = input.parse()?;
+        let mut ty: TypeAnnotation = input.parse()?;
+        if rest_token.is_some() && input.peek(Bracket) {
+            let brackets;
+            bracketed!(brackets in input);
+            if !brackets.is_empty() {
+                return Err(brackets.error("expected `[]`"));
+            }
+            let elem = &ty.ty;
+            ty.ty = parse_quote!(::std::vec::Vec<#elem>);
+        }
+        let default = if input.peek(Token![=]) {
+            Some((input.parse()?, input.parse()?))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            vis,
+            rest_token,
+            pat,
+            question_token,
+            ty,
+            default,
+        })
+    }
+}
+
+impl Param {
+    pub fn kind(&self) -> ParamKind {
+        if self.rest_token.is_some() {
+            ParamKind::Rest
+        } else if self.question_token.is_some() || self.default.is_some() {
+            ParamKind::Optional
+        } else {
+            ParamKind::Required
+        }
+    }
+
+    // The name the argument is passed as. Destructured parameters are passed as a plain argument
+    // and bound at the start of the body.
+    pub fn ident(&self, i: usize) -> Ident {
+        match &self.pat {
+            Pattern::Ident(ident) if ident != "_" => ident.clone(),
+            _ => encode::private_ident(&format!("arg{}", i)),
+        }
+    }
+
+    pub fn is_destructured(&self) -> bool {
+        !matches!(&self.pat, Pattern::Ident(ident) if ident != "_")
+    }
+
+    // The type the argument is passed as. Optional parameters take an `Option`, and so do those
+    // with a default, which the function falls back to on `None`.
+    pub fn rust_type(&self) -> Type {
+        let ty = &self.ty.ty;
+        match self.kind() {
+            ParamKind::Optional => parse_quote!(::std::option::Option<#ty>),
+            _ => ty.clone(),
+        }
+    }
+
+    // The type the parameter has in the body, once a default has been applied.
+    pub fn body_type(&self) -> Type {
+        if self.question_token.is_some() {
+            self.rust_type()
+        } else {
+            self.ty.ty.clone()
+        }
+    }
+}
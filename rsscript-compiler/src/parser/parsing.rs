@@ -26,6 +26,7 @@ macro_rules! enum_impl {
             $($variant:ident($type:ty),)+
         }
     ) => {
+        #[derive(Debug)]
         $vis enum $enum_name {
             $(
                 $variant($type)
@@ -0,0 +1,415 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{
+    braced, bracketed,
+    parse::Parse,
+    punctuated::Punctuated,
+    token::{Brace, Bracket},
+    Ident,
+};
+
+use crate::{
+    encode::{self, ToRustCode},
+    parser::Expr,
+    Token,
+};
+
+// JavaScript binding patterns, as found in declarations, parameters and `for` headers.
+#[derive(Debug)]
+pub enum Pattern {
+    Ident(Ident),
+    Object(ObjectPattern),
+    Array(ArrayPattern),
+}
+
+impl Parse for Pattern {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.peek(Brace) {
+            Ok(Self::Object(input.parse()?))
+        } else if input.peek(Bracket) {
+            Ok(Self::Array(input.parse()?))
+        } else if input.peek(Token![_]) {
+            let underscore: Token![_] = input.parse()?;
+            Ok(Self::Ident(Ident::new("_", underscore.span)))
+        } else {
+            Ok(Self::Ident(input.parse()?))
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PatternDefault {
+    pub eq_token: Token![=],
+    pub expr: Box<Expr>,
+}
+
+impl Parse for PatternDefault {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            eq_token: input.parse()?,
+            expr: input.parse()?,
+        })
+    }
+}
+
+impl PatternDefault {
+    fn parse_optional(input: syn::parse::ParseStream) -> syn::Result<Option<Self>> {
+        if input.peek(Token![=]) {
+            Ok(Some(input.parse()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RestPattern {
+    pub dot3_token: Token![...],
+    pub pat: Box<Pattern>,
+}
+
+impl Parse for RestPattern {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            dot3_token: input.parse()?,
+            pat: input.parse()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PropPattern {
+    pub key: Ident,
+    pub value: Option<(Token![:], Pattern)>,
+    pub default: Option<PatternDefault>,
+}
+
+impl Parse for PropPattern {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            key: input.parse()?,
+            value: if input.peek(Token![:]) {
+                Some((input.parse()?, input.parse()?))
+            } else {
+                None
+            },
+            default: PatternDefault::parse_optional(input)?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ObjectPattern {
+    pub brace_token: Brace,
+    pub props: Punctuated<PropPattern, Token![,]>,
+    pub rest: Option<RestPattern>,
+}
+
+impl Parse for ObjectPattern {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        let brace_token = braced!(content in input);
+        let mut props = Punctuated::new();
+        let mut rest = None;
+
+        while !content.is_empty() {
+            if content.peek(Token![...]) {
+                rest = Some(content.parse()?);
+                break;
+            }
+            props.push_value(content.parse()?);
+            if content.is_empty() {
+                break;
+            }
+            props.push_punct(content.parse()?);
+        }
+        if !content.is_empty() {
+            return Err(content.error("a rest pattern must be the last one"));
+        }
+
+        Ok(Self {
+            brace_token,
+            props,
+            rest,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ElemPattern {
+    pub pat: Pattern,
+    pub default: Option<PatternDefault>,
+}
+
+impl Parse for ElemPattern {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            pat: input.parse()?,
+            default: PatternDefault::parse_optional(input)?,
+        })
+    }
+}
+
+// `None` stands for a hole, as in `[a, , b]`.
+#[derive(Debug)]
+pub struct ArrayPattern {
+    pub bracket_token: Bracket,
+    pub elems: Punctuated<Option<ElemPattern>, Token![,]>,
+    pub rest: Option<RestPattern>,
+}
+
+impl Parse for ArrayPattern {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        let bracket_token = bracketed!(content in input);
+        let mut elems = Punctuated::new();
+        let mut rest = None;
+
+        while !content.is_empty() {
+            if content.peek(Token![...]) {
+                rest = Some(content.parse()?);
+                break;
+            }
+            if content.peek(Token![,]) {
+                elems.push_value(None);
+            } else {
+                elems.push_value(Some(content.parse()?));
+                if content.is_empty() {
+                    break;
+                }
+            }
+            elems.push_punct(content.parse()?);
+        }
+        if !content.is_empty() {
+            return Err(content.error("a rest pattern must be the last one"));
+        }
+
+        Ok(Self {
+            bracket_token,
+            elems,
+            rest,
+        })
+    }
+}
+
+// Only an identifier can be declared without being initialized.
+impl ToRustCode for Pattern {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Ident(ident) => ident.to_tokens(tokens),
+            Self::Object(pat) => tokens.extend(
+                syn::Error::new(
+                    pat.brace_token.span.join(),
+                    "a destructuring declaration must be initialized",
+                )
+                .to_compile_error(),
+            ),
+            Self::Array(pat) => tokens.extend(
+                syn::Error::new(
+                    pat.bracket_token.span.join(),
+                    "a destructuring declaration must be initialized",
+                )
+                .to_compile_error(),
+            ),
+        }
+    }
+}
+
+impl Pattern {
+    pub fn is_ident(&self) -> bool {
+        matches!(self, Self::Ident(_))
+    }
+
+    // Declares every name in the pattern as a local bound to the matching part of `value`. Parts
+    // are cloned out of a borrow of the value, which is left usable like in JavaScript.
+    pub fn to_binding_code(
+        &self,
+        value: TokenStream,
+        ty: Option<TokenStream>,
+        mutable: bool,
+        tokens: &mut TokenStream,
+    ) {
+        self.bind(value, ty, mutable, &mut 0, tokens);
+    }
+
+    fn bind(
+        &self,
+        value: TokenStream,
+        ty: Option<TokenStream>,
+        mutable: bool,
+        temps: &mut usize,
+        tokens: &mut TokenStream,
+    ) {
+        match self {
+            Self::Ident(ident) => {
+                let ty = ty.map(|ty| quote!(: #ty));
+                if ident == "_" {
+                    tokens.extend(quote!(let _ #ty = #value;));
+                } else if mutable {
+                    tokens.extend(quote! {
+                        #[allow(unused_mut)]
+                        let mut #ident #ty = #value;
+                    });
+                } else {
+                    tokens.extend(quote!(let #ident #ty = #value;));
+                }
+            }
+            Self::Object(pat) => {
+                let temp = encode::private_ident(&format!("value{}", temps));
+                *temps += 1;
+                let ty = ty.map(|ty| quote!(: &#ty));
+                tokens.extend(quote!(let #temp #ty = &(#value);));
+
+                for prop in &pat.props {
+                    let key = &prop.key;
+                    let value = with_default(
+                        quote!(::std::clone::Clone::clone(&#temp.#key)),
+                        &prop.default,
+                    );
+                    match &prop.value {
+                        Some((_, pat)) => pat.bind(value, None, mutable, temps, tokens),
+                        None => Self::Ident(key.clone()).bind(value, None, mutable, temps, tokens),
+                    }
+                }
+
+                // The rest is a copy of the whole object, without the fields taken out before.
+                if let Some(rest) = &pat.rest {
+                    let runtime = encode::runtime();
+                    let rest_temp = encode::private_ident(&format!("rest{}", temps));
+                    *temps += 1;
+                    let keys = pat.props.iter().map(|prop| &prop.key);
+                    let mutability = (!pat.props.is_empty()).then(|| quote!(mut));
+                    tokens.extend(quote! {
+                        let #mutability #rest_temp = #runtime::class::Rest::rest(#temp);
+                        #(#rest_temp.#keys = ::std::option::Option::None;)*
+                    });
+                    rest.pat
+                        .bind(quote!(#rest_temp), None, mutable, temps, tokens);
+                }
+            }
+            Self::Array(pat) => {
+                let temp = encode::private_ident(&format!("value{}", temps));
+                let iter = encode::private_ident(&format!("iter{}", temps));
+                *temps += 1;
+                let ty = ty.map(|ty| quote!(: &#ty));
+                tokens.extend(quote! {
+                    let #temp #ty = &(#value);
+                    let mut #iter = ::std::iter::Iterator::cloned(
+                        ::std::iter::IntoIterator::into_iter(#temp)
+                    );
+                });
+
+                for (i, elem) in pat.elems.iter().enumerate() {
+                    let next = quote!(::std::iter::Iterator::next(&mut #iter));
+                    let Some(elem) = elem else {
+                        tokens.extend(quote!(#next;));
+                        continue;
+                    };
+                    // A missing element takes the default, and without one it has no value to
+                    // bind, which is a `TypeError` ending the program.
+                    let value = match &elem.default {
+                        Some(_) => with_default(next, &elem.default),
+                        None => {
+                            let runtime = encode::runtime();
+                            let message = format!("missing element {} to destructure", i);
+                            quote! {
+                                ::std::option::Option::unwrap_or_else(#next, || {
+                                    ::std::panic!(
+                                        "Uncaught {}",
+                                        #runtime::error::TypeError(#message)
+                                    )
+                                })
+                            }
+                        }
+                    };
+                    elem.pat.bind(value, None, mutable, temps, tokens);
+                }
+
+                if let Some(rest) = &pat.rest {
+                    let value = quote!(::std::iter::Iterator::collect::<::std::vec::Vec<_>>(#iter));
+                    rest.pat.bind(value, None, mutable, temps, tokens);
+                }
+            }
+        }
+    }
+}
+
+// A default is used when the value is `undefined`, which is `None` in Rust.
+fn with_default(value: TokenStream, default: &Option<PatternDefault>) -> TokenStream {
+    match default {
+        Some(default) => {
+            let expr = default.expr.to_rust_token_stream();
+            quote!(::std::option::Option::unwrap_or_else(#value, || #expr))
+        }
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::item::Local;
+
+    #[test]
+    fn object_pattern() {
+        let local: Local = syn::parse_str("const { a, b: { c }, d = 1 } = obj").unwrap();
+        assert_eq!(
+            local.to_rust_token_stream().to_string(),
+            quote! {
+                let __rsscript_value0 = &(obj);
+                let a = ::std::clone::Clone::clone(&__rsscript_value0.a);
+                let __rsscript_value1 = &(::std::clone::Clone::clone(&__rsscript_value0.b));
+                let c = ::std::clone::Clone::clone(&__rsscript_value1.c);
+                let d = ::std::option::Option::unwrap_or_else(
+                    ::std::clone::Clone::clone(&__rsscript_value0.d),
+                    || ::rsscript::types::number::Number::from(1f64)
+                );
+            }
+            .to_string()
+        );
+
+        let local: Local = syn::parse_str("const { a, b: renamed, ...rest } = obj").unwrap();
+        assert_eq!(
+            local.to_rust_token_stream().to_string(),
+            quote! {
+                let __rsscript_value0 = &(obj);
+                let a = ::std::clone::Clone::clone(&__rsscript_value0.a);
+                let renamed = ::std::clone::Clone::clone(&__rsscript_value0.b);
+                let mut __rsscript_rest1 = ::rsscript::types::class::Rest::rest(__rsscript_value0);
+                __rsscript_rest1.a = ::std::option::Option::None;
+                __rsscript_rest1.b = ::std::option::Option::None;
+                let rest = __rsscript_rest1;
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn array_pattern() {
+        let local: Local =
+            syn::parse_str("const [a, , b = 1, ...rest]: Vec<Number> = arr").unwrap();
+        let next = quote!(::std::iter::Iterator::next(&mut __rsscript_iter0));
+        assert_eq!(
+            local.to_rust_token_stream().to_string(),
+            quote! {
+                let __rsscript_value0: &Vec<Number> = &(arr);
+                let mut __rsscript_iter0 = ::std::iter::Iterator::cloned(
+                    ::std::iter::IntoIterator::into_iter(__rsscript_value0)
+                );
+                let a = ::std::option::Option::unwrap_or_else(#next, || {
+                    ::std::panic!(
+                        "Uncaught {}",
+                        ::rsscript::types::error::TypeError("missing element 0 to destructure")
+                    )
+                });
+                #next;
+                let b = ::std::option::Option::unwrap_or_else(
+                    #next,
+                    || ::rsscript::types::number::Number::from(1f64)
+                );
+                let rest = ::std::iter::Iterator::collect::<::std::vec::Vec<_>>(__rsscript_iter0);
+            }
+            .to_string()
+        );
+    }
+}
@@ -1,7 +1,10 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
 use syn::parse::Parse;
 
-use crate::{parser::token::IdentPeeker, Token};
+use crate::{encode::ToRustCode, parser::token::IdentPeeker, Token};
 
+#[derive(Debug)]
 pub struct Visibility {
     pub export_token: Option<Token![export]>,
 }
@@ -18,6 +21,15 @@ impl Parse for Visibility {
     }
 }
 
+impl ToRustCode for Visibility {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        if let Some(export_token) = &self.export_token {
+            tokens.extend(quote_spanned!(export_token.span=> pub));
+        }
+    }
+}
+
+#[derive(Debug)]
 pub enum ClassVisibility {
     Private(Token![private]),
     Public(Token![public]),
@@ -38,3 +50,16 @@ impl Parse for ClassVisibility {
         }
     }
 }
+
+impl ToRustCode for ClassVisibility {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Private(_) => {}
+            Self::Public(public_token) => tokens.extend(quote_spanned!(public_token.span=> pub)),
+            Self::Protected(protected_token) => {
+                tokens.extend(quote_spanned!(protected_token.span=> pub(crate)))
+            }
+            Self::Inherited => tokens.extend(quote!(pub)),
+        }
+    }
+}
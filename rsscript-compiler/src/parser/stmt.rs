@@ -1,16 +1,33 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
 use syn::{
     braced, parenthesized,
     parse::Parse,
+    spanned::Spanned,
     token::{Brace, Paren},
-    Ident, Pat,
+    Ident, Member, Pat,
 };
 
 use crate::{
+    encode::{
+        self,
+        context::{self, Exit, Jump, JumpKind, ThrowTarget},
+        narrow::{self, Narrowing},
+        scope::{self, Union, VariantKind},
+        throws, ToRustCode,
+    },
     enum_impl,
-    parser::{expr::ExprParen, item::DeclarationKeyword, token::IdentPeeker, Expr, Item},
+    parser::{
+        expr::{ExprField, ExprLit, ExprParen, ExprPath},
+        item::{DeclarationKeyword, Local},
+        pat::Pattern,
+        token::IdentPeeker,
+        ty, Expr, Item,
+    },
     Token,
 };
 
+#[derive(Debug)]
 pub struct Stmt {
     pub stmt: StmtValue,
     pub punct: Option<Token![;]>,
@@ -25,6 +42,23 @@ impl Parse for Stmt {
     }
 }
 
+impl Stmt {
+    fn to_body_code(&self, tokens: &mut TokenStream) {
+        if let StmtValue::Block(block) = &self.stmt {
+            block.to_unit_code(tokens);
+        } else {
+            let stmt = self.to_rust_token_stream();
+            tokens.extend(quote!({ #stmt }));
+        }
+    }
+}
+
+impl ToRustCode for Stmt {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.stmt.to_stmt_code(tokens);
+    }
+}
+
 enum_impl! {
     pub enum StmtValue {
         Block(Block),
@@ -33,11 +67,61 @@ enum_impl! {
         For(StmtFor),
         While(StmtWhile),
         DoWhile(StmtDoWhile),
+        Break(StmtBreak),
+        Continue(StmtContinue),
+        Return(StmtReturn),
+        Throw(StmtThrow),
+        Try(StmtTry),
+        Labeled(StmtLabeled),
         Item(Item),
         Expr(Expr),
     }
 }
 
+impl StmtValue {
+    fn to_stmt_code(&self, tokens: &mut TokenStream) {
+        self.to_rust_code(tokens);
+        if let Self::Expr(_)
+        | Self::Break(_)
+        | Self::Continue(_)
+        | Self::Return(_)
+        | Self::Throw(_) = self
+        {
+            tokens.extend(quote!(;));
+        }
+    }
+}
+
+impl ToRustCode for StmtValue {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Block(block) => block.to_unit_code(tokens),
+            Self::If(stmt) => stmt.to_rust_code(tokens),
+            Self::Switch(stmt) => stmt.to_rust_code(tokens),
+            Self::For(stmt) => stmt.to_rust_code(tokens),
+            Self::While(stmt) => stmt.to_rust_code(tokens),
+            Self::DoWhile(stmt) => stmt.to_rust_code(tokens),
+            Self::Break(stmt) => stmt.to_rust_code(tokens),
+            Self::Continue(stmt) => stmt.to_rust_code(tokens),
+            Self::Return(stmt) => stmt.to_rust_code(tokens),
+            Self::Throw(stmt) => stmt.to_rust_code(tokens),
+            Self::Try(stmt) => stmt.to_rust_code(tokens),
+            Self::Labeled(stmt) => stmt.to_rust_code(tokens),
+            Self::Item(item) => item.to_rust_code(tokens),
+            Self::Expr(expr) => expr.to_rust_code(tokens),
+        }
+    }
+}
+
+fn else_code(else_block: &Option<(Token![else], Box<Stmt>)>) -> TokenStream {
+    let mut tokens = TokenStream::new();
+    if let Some((_, stmt)) = else_block {
+        stmt.to_body_code(&mut tokens);
+    }
+    tokens
+}
+
+#[derive(Debug)]
 pub struct StmtIf {
     pub if_token: Token![if],
     pub cond: ExprParen,
@@ -69,6 +153,126 @@ impl Parse for StmtIf {
     }
 }
 
+impl StmtIf {
+    fn else_code(else_branch: &Stmt) -> TokenStream {
+        match &else_branch.stmt {
+            StmtValue::If(stmt) => stmt.to_rust_token_stream(),
+            _ => {
+                let mut tokens = TokenStream::new();
+                else_branch.to_body_code(&mut tokens);
+                tokens
+            }
+        }
+    }
+}
+
+impl StmtIf {
+    // When one branch of a narrowing `if` always exits, the statements after it only run after the
+    // other one, so they are lowered into it to see the variable narrowed as it is there. Returns
+    // whether the `if` was lowered along with them.
+    fn to_narrowing_code(&self, rest: &[Stmt], tail: bool, tokens: &mut TokenStream) -> bool {
+        if rest.is_empty() {
+            return false;
+        }
+        let narrowing =
+            Narrowing::of(&self.cond.expr).filter(|narrowing| !narrow::is_narrowed(narrowing.var));
+        let Some(narrowing) = narrowing else {
+            return false;
+        };
+        let then_exits = always_exits(&self.then_branch);
+        let else_exits = self
+            .else_branch
+            .as_ref()
+            .is_some_and(|(_, else_branch)| always_exits(else_branch));
+        // Declarations are hoisted in JavaScript, so they are kept where the whole block sees them.
+        let declares = rest.iter().any(
+            |stmt| matches!(&stmt.stmt, StmtValue::Item(item) if !matches!(item, Item::Local(_))),
+        );
+        if then_exits == else_exits || declares {
+            return false;
+        }
+
+        // The exiting branch ends the block when the rest gives its value, where a `return` is
+        // needless to clippy.
+        let exiting = |branch: TokenStream| match tail {
+            true => quote!(#[allow(clippy::needless_return)] #branch),
+            false => branch,
+        };
+        let then = || {
+            let mut tokens = TokenStream::new();
+            self.then_branch.to_body_code(&mut tokens);
+            if then_exits {
+                return exiting(tokens);
+            }
+            Block::to_stmts_code(rest, tail, &mut tokens);
+            tokens
+        };
+        let otherwise = || {
+            let mut tokens = match &self.else_branch {
+                Some((_, else_branch)) => Self::else_code(else_branch),
+                None => TokenStream::new(),
+            };
+            if !then_exits {
+                return exiting(tokens);
+            }
+            Block::to_stmts_code(rest, tail, &mut tokens);
+            tokens
+        };
+        tokens.extend(narrowing.to_if_code(then, Some(otherwise)));
+        true
+    }
+}
+
+// Whether a statement never completes normally, so that what follows it is skipped.
+fn always_exits(stmt: &Stmt) -> bool {
+    match &stmt.stmt {
+        StmtValue::Return(_)
+        | StmtValue::Throw(_)
+        | StmtValue::Break(_)
+        | StmtValue::Continue(_) => true,
+        StmtValue::Block(block) => block.stmts.iter().any(always_exits),
+        StmtValue::If(stmt) => {
+            always_exits(&stmt.then_branch)
+                && stmt
+                    .else_branch
+                    .as_ref()
+                    .is_some_and(|(_, else_branch)| always_exits(else_branch))
+        }
+        _ => false,
+    }
+}
+
+// A condition that narrows a variable of a union type makes the `if` a `match` on the variable.
+impl ToRustCode for StmtIf {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let narrowing =
+            Narrowing::of(&self.cond.expr).filter(|narrowing| !narrow::is_narrowed(narrowing.var));
+        if let Some(narrowing) = narrowing {
+            let then = || {
+                let mut tokens = TokenStream::new();
+                self.then_branch.to_body_code(&mut tokens);
+                tokens
+            };
+            let otherwise = self
+                .else_branch
+                .as_ref()
+                .map(|(_, else_branch)| || Self::else_code(else_branch));
+            tokens.extend(narrowing.to_if_code(then, otherwise));
+            return;
+        }
+
+        self.if_token.to_tokens(tokens);
+        self.cond.expr.to_rust_code(tokens);
+        self.then_branch.to_body_code(tokens);
+
+        if let Some((else_token, else_branch)) = &self.else_branch {
+            else_token.to_tokens(tokens);
+            tokens.extend(Self::else_code(else_branch));
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct CaseDecl {
     pub case_token: Token![case],
     pub pat: Pat,
@@ -85,6 +289,38 @@ impl Parse for CaseDecl {
     }
 }
 
+impl CaseDecl {
+    // Case values are compared with `==`, like JavaScript's strict equality.
+    fn to_cond_code(&self, value: &Ident, tokens: &mut TokenStream) {
+        fn pat_cond(pat: &Pat, value: &Ident) -> TokenStream {
+            match pat {
+                Pat::Lit(pat) => {
+                    let lit = ExprLit {
+                        lit: pat.lit.clone(),
+                    }
+                    .into_rust_token_stream();
+                    quote!(#value == #lit)
+                }
+                Pat::Ident(pat) if pat.subpat.is_none() => {
+                    let ident = &pat.ident;
+                    quote!(#value == #ident)
+                }
+                Pat::Path(pat) => quote!(#value == #pat),
+                Pat::Paren(pat) => pat_cond(&pat.pat, value),
+                Pat::Or(pat) => {
+                    let cases = pat.cases.iter().map(|case| pat_cond(case, value));
+                    quote!(#((#cases))||*)
+                }
+                Pat::Wild(_) => quote!(true),
+                pat => syn::Error::new(pat.span(), "unsupported `case` pattern").to_compile_error(),
+            }
+        }
+
+        tokens.extend(pat_cond(&self.pat, value));
+    }
+}
+
+#[derive(Debug)]
 pub struct DefaultDecl {
     pub default_token: Token![default],
     pub colon_token: Token![:],
@@ -99,6 +335,7 @@ impl Parse for DefaultDecl {
     }
 }
 
+#[derive(Debug)]
 pub enum ArmDecl {
     Case(CaseDecl),
     Default(DefaultDecl),
@@ -116,6 +353,7 @@ impl Parse for ArmDecl {
     }
 }
 
+#[derive(Debug)]
 pub struct Arm {
     pub decl: ArmDecl,
     pub stmts: Vec<Stmt>,
@@ -135,6 +373,7 @@ impl Parse for Arm {
     }
 }
 
+#[derive(Debug)]
 pub struct StmtSwitch {
     pub switch_token: Token![switch],
     pub expr: Expr,
@@ -152,7 +391,7 @@ impl Parse for StmtSwitch {
             arms: {
                 let mut arms = Vec::new();
                 while !content.is_empty() {
-                    let next = input.parse()?;
+                    let next = content.parse()?;
                     arms.push(next);
                 }
                 arms
@@ -161,6 +400,661 @@ impl Parse for StmtSwitch {
     }
 }
 
+impl Arm {
+    fn is_default(&self) -> bool {
+        matches!(self.decl, ArmDecl::Default(_))
+    }
+
+    fn ends_with_break(&self) -> bool {
+        matches!(
+            self.stmts.last(),
+            Some(Stmt {
+                stmt: StmtValue::Break(StmtBreak { label: None, .. }),
+                ..
+            })
+        )
+    }
+
+    // Whether the arm cannot fall through into the next one.
+    fn ends(&self) -> bool {
+        self.ends_with_break()
+            || matches!(
+                self.stmts.last(),
+                Some(Stmt {
+                    stmt: StmtValue::Return(_) | StmtValue::Throw(_) | StmtValue::Continue(_),
+                    ..
+                })
+            )
+    }
+
+    // The statements of the arm without the `break` ending it.
+    fn body(&self) -> &[Stmt] {
+        if self.ends_with_break() {
+            &self.stmts[..self.stmts.len() - 1]
+        } else {
+            &self.stmts[..]
+        }
+    }
+}
+
+// Whether `stmts` contain a `break` that leaves the enclosing `switch`.
+fn breaks_out(stmts: &[Stmt]) -> bool {
+    fn stmt_breaks_out(stmt: &Stmt) -> bool {
+        match &stmt.stmt {
+            StmtValue::Break(stmt) => stmt.label.is_none(),
+            StmtValue::Block(block) => breaks_out(&block.stmts),
+            StmtValue::If(stmt) => {
+                stmt_breaks_out(&stmt.then_branch)
+                    || stmt
+                        .else_branch
+                        .as_ref()
+                        .is_some_and(|(_, stmt)| stmt_breaks_out(stmt))
+            }
+            _ => false,
+        }
+    }
+
+    stmts.iter().any(stmt_breaks_out)
+}
+
+impl StmtSwitch {
+    fn subject_code(&self) -> TokenStream {
+        match &self.expr {
+            Expr::Paren(expr) => expr.expr.to_rust_token_stream(),
+            expr => expr.to_rust_token_stream(),
+        }
+    }
+
+    // Arms without statements fall through into the next one, so they are grouped with it.
+    fn groups(&self) -> Vec<&[Arm]> {
+        let mut groups = Vec::new();
+        let mut start = 0;
+
+        for (i, arm) in self.arms.iter().enumerate() {
+            if !arm.stmts.is_empty() || i + 1 == self.arms.len() {
+                groups.push(&self.arms[start..=i]);
+                start = i + 1;
+            }
+        }
+
+        groups
+    }
+
+    fn is_matchable(&self) -> bool {
+        let groups = self.groups();
+        groups.iter().enumerate().all(|(i, group)| {
+            let arm = group.last().unwrap();
+            if !arm.ends() && i + 1 != groups.len() {
+                return false;
+            }
+
+            !breaks_out(arm.body())
+        })
+    }
+
+    fn to_match_code(&self, label: Option<&Ident>, tokens: &mut TokenStream) {
+        let value = encode::private_ident("value");
+        let subject = self.subject_code();
+        let (code, lifetime) = context::with_jump_target(JumpKind::Switch, label, false, || {
+            self.match_arms_code(&value)
+        });
+
+        // Only a labeled `break` leaves the `match`, through a labeled block around it.
+        match lifetime {
+            Some(lifetime) => tokens.extend(quote!(#lifetime: { match #subject { #code } })),
+            None => tokens.extend(quote!(match #subject { #code })),
+        }
+    }
+
+    // A `switch` on the discriminant of a variable of a union type, which is not narrowed yet.
+    fn narrowed_subject(&self) -> Option<(&Ident, Union)> {
+        let expr = match &self.expr {
+            Expr::Paren(expr) => &*expr.expr,
+            expr => expr,
+        };
+        let Expr::Field(ExprField {
+            base,
+            optional: None,
+            member: Member::Named(member),
+            ..
+        }) = expr
+        else {
+            return None;
+        };
+        let Expr::Path(ExprPath { path }) = &**base else {
+            return None;
+        };
+        let var = path.get_ident()?;
+        let union = scope::variable_union(var)?;
+
+        (union.tag.as_ref() == Some(member) && !narrow::is_narrowed(var)).then_some((var, union))
+    }
+
+    // The variants each group of arms takes, with the default group last since it only takes the
+    // variants no case names. Cases other than literals cannot be matched with variants.
+    fn narrowed_groups(&self, union: &Union) -> Option<Vec<(Vec<usize>, &[Arm])>> {
+        let mut groups = Vec::new();
+        let mut default = None;
+
+        for group in self.groups() {
+            if group.iter().any(Arm::is_default) {
+                default = Some(group);
+                continue;
+            }
+
+            let mut variants = Vec::new();
+            for arm in group {
+                let ArmDecl::Case(CaseDecl {
+                    pat: Pat::Lit(pat), ..
+                }) = &arm.decl
+                else {
+                    return None;
+                };
+                variants.extend(union.variants.iter().enumerate().filter_map(|(i, variant)| {
+                    matches!(&variant.kind, VariantKind::Object { tag: Some(tag), .. } if ty::same_lit(tag, &pat.lit))
+                        .then_some(i)
+                }));
+            }
+            groups.push((variants, group));
+        }
+
+        if let Some(group) = default {
+            groups.push(((0..union.variants.len()).collect(), group));
+        }
+        Some(groups)
+    }
+
+    fn to_narrowed_match_code(
+        &self,
+        var: &Ident,
+        union: &Union,
+        groups: Vec<(Vec<usize>, &[Arm])>,
+        label: Option<&Ident>,
+        tokens: &mut TokenStream,
+    ) {
+        let (code, lifetime) = context::with_jump_target(JumpKind::Switch, label, false, || {
+            let arms = groups
+                .into_iter()
+                .map(|(variants, group)| -> narrow::Arm {
+                    let body = group.last().unwrap().body();
+                    (variants, Box::new(move || body.to_rust_token_stream()))
+                })
+                .collect();
+            narrow::match_code(var, union, arms)
+        });
+
+        match lifetime {
+            Some(lifetime) => tokens.extend(quote!(#lifetime: { #code })),
+            None => tokens.extend(code),
+        }
+    }
+
+    fn match_arms_code(&self, value: &Ident) -> TokenStream {
+        let mut arms = TokenStream::new();
+        let mut default = None;
+
+        for group in self.groups() {
+            let body = group.last().unwrap().body().to_rust_token_stream();
+            let body = quote!({ #body });
+
+            if group.iter().any(Arm::is_default) {
+                default = Some(body);
+                continue;
+            }
+
+            let mut cond = TokenStream::new();
+            for (i, arm) in group.iter().enumerate() {
+                if let ArmDecl::Case(case) = &arm.decl {
+                    if i > 0 {
+                        cond.extend(quote!(||));
+                    }
+                    let mut case_cond = TokenStream::new();
+                    case.to_cond_code(value, &mut case_cond);
+                    cond.extend(quote!((#case_cond)));
+                }
+            }
+            arms.extend(quote!(#value if #cond => #body));
+        }
+
+        let default = default.unwrap_or_else(|| quote!({}));
+        quote! {
+            #arms
+            _ => #default
+        }
+    }
+
+    // Falling through arms are encoded as a state machine: the matching arm index is computed
+    // first and every arm from there on runs until a `break` leaves the surrounding `loop`.
+    fn to_fall_through_code(&self, label: Option<&Ident>, tokens: &mut TokenStream) {
+        let value = encode::private_ident("value");
+        let entry = encode::private_ident("entry");
+        let subject = self.subject_code();
+        let fallback = self
+            .arms
+            .iter()
+            .position(Arm::is_default)
+            .unwrap_or(self.arms.len());
+
+        let mut select = TokenStream::new();
+        for (i, arm) in self.arms.iter().enumerate() {
+            if let ArmDecl::Case(case) = &arm.decl {
+                let mut cond = TokenStream::new();
+                case.to_cond_code(&value, &mut cond);
+                select.extend(quote!(if #cond { #i } else));
+            }
+        }
+
+        let (arms, lifetime) = context::with_jump_target(JumpKind::Switch, label, true, || {
+            let mut arms = TokenStream::new();
+            for (i, arm) in self.arms.iter().enumerate() {
+                if arm.stmts.is_empty() {
+                    continue;
+                }
+                let stmts = arm.stmts.to_rust_token_stream();
+                if i == 0 {
+                    arms.extend(quote!(if #entry == 0 { #stmts }));
+                } else {
+                    arms.extend(quote!(if #entry <= #i { #stmts }));
+                }
+            }
+            arms
+        });
+        let lifetime = lifetime.map(|lifetime| quote!(#lifetime:));
+
+        tokens.extend(quote! {
+            #lifetime loop {
+                let #value = #subject;
+                let #entry: usize = #select { #fallback };
+                #arms
+                break;
+            }
+        });
+    }
+}
+
+impl StmtSwitch {
+    fn to_labeled_code(&self, label: Option<&Ident>, tokens: &mut TokenStream) {
+        if self.is_matchable() {
+            if let Some((var, union)) = self.narrowed_subject() {
+                if let Some(groups) = self.narrowed_groups(&union) {
+                    self.to_narrowed_match_code(var, &union, groups, label, tokens);
+                    return;
+                }
+            }
+
+            self.to_match_code(label, tokens);
+        } else {
+            self.to_fall_through_code(label, tokens);
+        }
+    }
+}
+
+impl ToRustCode for StmtSwitch {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.to_labeled_code(None, tokens);
+    }
+}
+
+#[derive(Debug)]
+pub struct StmtBreak {
+    pub break_token: Token![break],
+    pub label: Option<Ident>,
+}
+
+impl Parse for StmtBreak {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            break_token: input.parse()?,
+            label: parse_label(input)?,
+        })
+    }
+}
+
+impl ToRustCode for StmtBreak {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        tokens.extend(context::jump(
+            Jump::Break,
+            self.break_token.span,
+            self.label.as_ref(),
+        ));
+    }
+}
+
+#[derive(Debug)]
+pub struct StmtContinue {
+    pub continue_token: Token![continue],
+    pub label: Option<Ident>,
+}
+
+impl Parse for StmtContinue {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            continue_token: input.parse()?,
+            label: parse_label(input)?,
+        })
+    }
+}
+
+impl ToRustCode for StmtContinue {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        tokens.extend(context::jump(
+            Jump::Continue,
+            self.continue_token.span,
+            self.label.as_ref(),
+        ));
+    }
+}
+
+// Statements may end without a `;`, so an identifier after `break` or `continue` is only taken as
+// their label when the statement ends right after it.
+fn parse_label(input: syn::parse::ParseStream) -> syn::Result<Option<Ident>> {
+    let fork = input.fork();
+    let is_label = fork.parse::<Ident>().is_ok()
+        && (fork.is_empty()
+            || fork.peek(Token![;])
+            || fork.ipeek::<Token![case]>()
+            || fork.peek(Token![default]));
+
+    if is_label {
+        Ok(Some(input.parse()?))
+    } else {
+        Ok(None)
+    }
+}
+
+#[derive(Debug)]
+pub struct StmtReturn {
+    pub return_token: Token![return],
+    pub expr: Option<Expr>,
+}
+
+impl Parse for StmtReturn {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            return_token: input.parse()?,
+            expr: if input.is_empty() || input.peek(Token![;]) {
+                None
+            } else {
+                Some(input.parse()?)
+            },
+        })
+    }
+}
+
+impl ToRustCode for StmtReturn {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let expr = self.expr.as_ref().map(ToRustCode::to_rust_token_stream);
+        tokens.extend(context::return_value(self.return_token.span, expr));
+    }
+}
+
+#[derive(Debug)]
+pub struct StmtLabeled {
+    pub label: Ident,
+    pub colon_token: Token![:],
+    pub body: Box<Stmt>,
+}
+
+impl Parse for StmtLabeled {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            label: input.parse()?,
+            colon_token: input.parse()?,
+            body: input.parse()?,
+        })
+    }
+}
+
+// A labeled loop or `switch` takes the label itself. Any other statement is put in a block that a
+// `break` with the label leaves.
+impl ToRustCode for StmtLabeled {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let label = Some(&self.label);
+        match &self.body.stmt {
+            StmtValue::For(stmt) => stmt.to_labeled_code(label, tokens),
+            StmtValue::While(stmt) => stmt.to_labeled_code(label, tokens),
+            StmtValue::DoWhile(stmt) => stmt.to_labeled_code(label, tokens),
+            StmtValue::Switch(stmt) => stmt.to_labeled_code(label, tokens),
+            _ => {
+                let (body, lifetime) =
+                    context::with_jump_target(JumpKind::Block, label, false, || {
+                        let mut body = TokenStream::new();
+                        self.body.to_body_code(&mut body);
+                        body
+                    });
+                let lifetime = lifetime.map(|lifetime| quote!(#lifetime:));
+                tokens.extend(quote!(#lifetime #body));
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StmtThrow {
+    pub throw_token: Token![throw],
+    pub expr: Expr,
+}
+
+impl Parse for StmtThrow {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            throw_token: input.parse()?,
+            expr: input.parse()?,
+        })
+    }
+}
+
+impl ToRustCode for StmtThrow {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let runtime = encode::runtime();
+        let expr = self.expr.to_rust_token_stream();
+        tokens.extend(context::propagate(
+            self.throw_token.span,
+            quote!(::std::convert::Into::<#runtime::error::Error>::into(#expr)),
+        ));
+    }
+}
+
+#[derive(Debug)]
+pub struct CatchClause {
+    pub catch_token: Token![catch],
+    pub param: Option<(Paren, Pattern)>,
+    pub block: Block,
+}
+
+impl Parse for CatchClause {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let catch_token = input.parse()?;
+        let param = if input.peek(Paren) {
+            let content;
+            let paren_token = parenthesized!(content in input);
+            Some((paren_token, content.parse()?))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            catch_token,
+            param,
+            block: input.parse()?,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct StmtTry {
+    pub try_token: Token![try],
+    pub block: Block,
+    pub catch: Option<CatchClause>,
+    pub finally: Option<(Token![finally], Block)>,
+}
+
+impl Parse for StmtTry {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let try_token: Token![try] = input.parse()?;
+        let block = input.parse()?;
+        let catch = if input.ipeek::<Token![catch]>() {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        let finally = if input.ipeek::<Token![finally]>() {
+            Some((input.parse()?, input.parse()?))
+        } else {
+            None
+        };
+        if catch.is_none() && finally.is_none() {
+            return Err(syn::Error::new(
+                try_token.span,
+                "a `try` block needs a `catch` or `finally` clause",
+            ));
+        }
+
+        Ok(Self {
+            try_token,
+            block,
+            catch,
+            finally,
+        })
+    }
+}
+
+// The `try` block is lowered to a labeled block evaluating to a `Result`, which errors thrown in
+// it break out of. The `finally` block runs before an error left uncaught is thrown on.
+//
+// With a `finally` block, the `try` and `catch` blocks are nested in another labeled block, which
+// `return`, `break` and `continue` out of them break out of with a runtime `Completion` instead,
+// so that they are only taken once `finally` has run.
+impl ToRustCode for StmtTry {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        let Some((_, finally)) = &self.finally else {
+            let (mut code, uncaught) = self.to_catch_code();
+            code.extend(uncaught);
+            tokens.extend(quote!({ #code }));
+            return;
+        };
+
+        let runtime = encode::runtime();
+        let label = context::finally_label();
+        let ((mut code, uncaught), exits) =
+            context::with_finally(label.clone(), || self.to_catch_code());
+
+        if exits.is_empty() {
+            finally.to_unit_code(&mut code);
+            code.extend(uncaught);
+            tokens.extend(quote!({ #code }));
+            return;
+        }
+
+        let completion = encode::private_ident("completion");
+        let result = encode::private_ident("result");
+        let value = encode::private_ident("value");
+        let error = encode::private_ident("error");
+        let completion_ty = match exits.iter().any(|exit| matches!(exit, Exit::Return(_))) {
+            true => quote!(#runtime::error::Completion<_>),
+            false => quote!(#runtime::error::Completion<()>),
+        };
+        let left = match uncaught {
+            Some(_) => quote! {
+                match #result {
+                    ::std::result::Result::Ok(()) => #runtime::error::Completion::Normal,
+                    ::std::result::Result::Err(#error) => #runtime::error::Completion::Throw(#error),
+                }
+            },
+            None => quote!(#runtime::error::Completion::Normal),
+        };
+
+        let mut arms = quote!(#runtime::error::Completion::Normal => {});
+        if uncaught.is_some() {
+            let propagate = context::propagate(self.try_token.span, quote!(#error));
+            arms.extend(quote!(#runtime::error::Completion::Throw(#error) => #propagate,));
+        }
+        for (i, exit) in exits.iter().enumerate() {
+            let code = context::resume(exit, quote!(#value));
+            arms.extend(match exit {
+                Exit::Return(_) => quote!(#runtime::error::Completion::Return(#value) => #code,),
+                Exit::Jump(..) => quote!(#runtime::error::Completion::Jump(#i) => #code,),
+            });
+        }
+
+        let mut finally_code = TokenStream::new();
+        finally.to_unit_code(&mut finally_code);
+        tokens.extend(quote! {{
+            #[allow(unused_labels, unreachable_code)]
+            let #completion: #completion_ty = #label: { #code #left };
+            #finally_code
+            #[allow(unreachable_patterns)]
+            match #completion {
+                #arms
+                _ => ::std::unreachable!(),
+            }
+        }});
+    }
+}
+
+impl StmtTry {
+    // Lowers the `try` and `catch` blocks to statements leaving their `Result` in a variable.
+    // Also returns the code throwing an error left uncaught on, if one can be.
+    fn to_catch_code(&self) -> (TokenStream, Option<TokenStream>) {
+        let runtime = encode::runtime();
+        let result = encode::private_ident("result");
+        let error = encode::private_ident("error");
+        let label = context::try_label();
+        let result_ty = quote!(::std::result::Result<(), #runtime::error::Error>);
+        let ok = quote!(::std::result::Result::Ok(()));
+
+        let (body, thrown) = context::with_throw_target(ThrowTarget::Label(label.clone()), || {
+            let mut body = TokenStream::new();
+            self.block.to_unit_code(&mut body);
+            body
+        });
+        if !thrown && self.catch.is_none() {
+            return (body, None);
+        }
+        let mut code = quote! {
+            #[allow(unused_labels, unreachable_code)]
+            let #result: #result_ty = #label: { #body #ok };
+        };
+
+        if let Some(catch) = &self.catch {
+            let (handler, thrown) =
+                context::with_throw_target(ThrowTarget::Label(label.clone()), || {
+                    let mut handler = TokenStream::new();
+                    if let Some((_, pat)) = &catch.param {
+                        pat.to_binding_code(quote!(#error), None, true, &mut handler);
+                    }
+                    catch.block.to_unit_code(&mut handler);
+                    handler
+                });
+            let binding = match &catch.param {
+                Some(_) => quote!(#error),
+                None => quote!(_),
+            };
+
+            if !thrown {
+                code.extend(quote! {
+                    if let ::std::result::Result::Err(#binding) = #result { #handler }
+                });
+                return (code, None);
+            }
+            code.extend(quote! {
+                #[allow(unused_labels, unreachable_code)]
+                let #result: #result_ty = match #result {
+                    ::std::result::Result::Ok(()) => #ok,
+                    ::std::result::Result::Err(#binding) => #label: { #handler #ok },
+                };
+            });
+        }
+
+        let propagate = context::propagate(self.try_token.span, quote!(#error));
+        let uncaught = quote! {
+            if let ::std::result::Result::Err(#error) = #result { #propagate }
+        };
+        (code, Some(uncaught))
+    }
+}
+
+#[derive(Debug)]
 pub struct StmtWhile {
     pub while_token: Token![while],
     pub cond: ExprParen,
@@ -185,6 +1079,53 @@ impl Parse for StmtWhile {
     }
 }
 
+// The body of a loop, with the label it needs for the jumps in it.
+fn loop_body_code(body: &Stmt, label: Option<&Ident>) -> (TokenStream, Option<TokenStream>) {
+    let (body, lifetime) = context::with_jump_target(JumpKind::Loop, label, true, || {
+        let mut tokens = TokenStream::new();
+        body.to_body_code(&mut tokens);
+        tokens
+    });
+
+    (body, lifetime.map(|lifetime| quote!(#lifetime:)))
+}
+
+// A loop `else` is lowered inside the generated loop, but jumps in it leave the loops around it.
+fn loop_else_code(else_block: &Option<(Token![else], Box<Stmt>)>) -> TokenStream {
+    context::with_opaque(|| else_code(else_block))
+}
+
+// A loop `else` runs only when the condition turns false, so a `break` in the body skips it.
+impl StmtWhile {
+    fn to_labeled_code(&self, label: Option<&Ident>, tokens: &mut TokenStream) {
+        let cond = self.cond.expr.to_rust_token_stream();
+        let (body, lifetime) = loop_body_code(&self.body, label);
+
+        if self.else_block.is_none() {
+            tokens.extend(quote!(#lifetime while #cond #body));
+            return;
+        }
+
+        let else_block = loop_else_code(&self.else_block);
+        tokens.extend(quote! {
+            #lifetime loop {
+                if !(#cond) {
+                    #else_block
+                    break;
+                }
+                #body
+            }
+        });
+    }
+}
+
+impl ToRustCode for StmtWhile {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.to_labeled_code(None, tokens);
+    }
+}
+
+#[derive(Debug)]
 pub struct StmtDoWhile {
     pub do_token: Token![do],
     pub body: Box<Stmt>,
@@ -211,6 +1152,36 @@ impl Parse for StmtDoWhile {
     }
 }
 
+impl StmtDoWhile {
+    fn to_labeled_code(&self, label: Option<&Ident>, tokens: &mut TokenStream) {
+        let first = encode::private_ident("first");
+        let cond = self.cond.expr.to_rust_token_stream();
+        let (body, lifetime) = loop_body_code(&self.body, label);
+        let else_block = loop_else_code(&self.else_block);
+
+        tokens.extend(quote! {
+            {
+                let mut #first = true;
+                #lifetime loop {
+                    if !#first && !(#cond) {
+                        #else_block
+                        break;
+                    }
+                    #first = false;
+                    #body
+                }
+            }
+        });
+    }
+}
+
+impl ToRustCode for StmtDoWhile {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.to_labeled_code(None, tokens);
+    }
+}
+
+#[derive(Debug)]
 pub struct StmtFor {
     pub for_token: Token![for],
     pub header: ForArgs,
@@ -241,6 +1212,99 @@ impl Parse for StmtFor {
     }
 }
 
+impl StmtFor {
+    fn to_labeled_code(&self, label: Option<&Ident>, tokens: &mut TokenStream) {
+        let (body, lifetime) = loop_body_code(&self.body, label);
+        let else_block = loop_else_code(&self.else_block);
+
+        match &self.header {
+            ForArgs::In(header) => {
+                let iterable = header.iterable.to_rust_token_stream();
+                let (pat, body) = match &header.pat {
+                    Pattern::Ident(ident) => (ident.clone(), body),
+                    pat => {
+                        let item = encode::private_ident("item");
+                        let mutable = matches!(header.keyword, DeclarationKeyword::Let);
+                        let mut bindings = TokenStream::new();
+                        pat.to_binding_code(quote!(#item), None, mutable, &mut bindings);
+                        (item, quote!({ #bindings #body }))
+                    }
+                };
+
+                if self.else_block.is_none() {
+                    tokens.extend(quote!(#lifetime for #pat in #iterable #body));
+                    return;
+                }
+
+                let iter = encode::private_ident("iter");
+                tokens.extend(quote! {
+                    {
+                        let mut #iter = ::std::iter::IntoIterator::into_iter(#iterable);
+                        #lifetime loop {
+                            let ::std::option::Option::Some(#pat) =
+                                ::std::iter::Iterator::next(&mut #iter)
+                            else {
+                                #else_block
+                                break;
+                            };
+                            #body
+                        }
+                    }
+                });
+            }
+            ForArgs::Header(header) => {
+                let first = encode::private_ident("first");
+                let init = header.init.as_ref().map(|init| {
+                    let mut tokens = TokenStream::new();
+                    init.to_stmt_code(&mut tokens);
+                    tokens
+                });
+                let after = header.after.as_ref().map(|after| {
+                    let mut tokens = TokenStream::new();
+                    after.to_stmt_code(&mut tokens);
+                    quote! {
+                        if !#first {
+                            #tokens
+                        }
+                        #first = false;
+                    }
+                });
+                let first_init = header
+                    .after
+                    .as_ref()
+                    .map(|_| quote!(let mut #first = true;));
+                let cond = header.cond.as_ref().map(|cond| {
+                    let cond = cond.to_rust_token_stream();
+                    quote! {
+                        if !(#cond) {
+                            #else_block
+                            break;
+                        }
+                    }
+                });
+
+                tokens.extend(quote! {
+                    {
+                        #init
+                        #first_init
+                        #lifetime loop {
+                            #after
+                            #cond
+                            #body
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
+
+impl ToRustCode for StmtFor {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.to_labeled_code(None, tokens);
+    }
+}
+
 enum_impl! {
     pub enum ForArgs {
         In(ForInHeader),
@@ -248,12 +1312,33 @@ enum_impl! {
     }
 }
 
+// `for (x in xs)` keeps the meaning it had before `of` was added, going over the values of `xs`
+// like a Rust `for` loop rather than over its keys like in JavaScript. `for (x of xs)` is the
+// JavaScript spelling of the same loop, so both work on anything iterable, like an array or a
+// generator.
+#[derive(Debug)]
+pub enum ForInToken {
+    In(Token![in]),
+    Of(Token![of]),
+}
+
+impl Parse for ForInToken {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if input.ipeek::<Token![of]>() {
+            Ok(Self::Of(input.parse()?))
+        } else {
+            Ok(Self::In(input.parse()?))
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct ForInHeader {
     pub paren_token: Paren,
     pub keyword: DeclarationKeyword,
-    pub pat: Pat,
-    pub in_token: Token![in],
-    pub iterable: Ident,
+    pub pat: Pattern,
+    pub in_token: ForInToken,
+    pub iterable: Expr,
 }
 
 impl Parse for ForInHeader {
@@ -262,13 +1347,14 @@ impl Parse for ForInHeader {
         Ok(Self {
             paren_token: parenthesized!(content in input),
             keyword: content.parse()?,
-            pat: Pat::parse_multi(input)?,
-            in_token: input.parse()?,
-            iterable: input.parse()?,
+            pat: content.parse()?,
+            in_token: content.parse()?,
+            iterable: content.parse()?,
         })
     }
 }
 
+#[derive(Debug)]
 pub struct ForHeader {
     pub paren_token: Paren,
     pub init: Option<Box<StmtValue>>,
@@ -304,6 +1390,7 @@ impl Parse for ForHeader {
     }
 }
 
+#[derive(Debug)]
 pub struct Block {
     pub brace_token: Brace,
     pub stmts: Vec<Stmt>,
@@ -323,3 +1410,376 @@ impl Parse for Block {
         Ok(Self { brace_token, stmts })
     }
 }
+
+impl Block {
+    fn interfaces(&self) -> impl Iterator<Item = scope::Interface> + '_ {
+        self.stmts.iter().filter_map(|stmt| match &stmt.stmt {
+            StmtValue::Item(Item::Interface(item)) => Some(scope::Interface::new(item)),
+            _ => None,
+        })
+    }
+
+    // Locals hiding the parameter properties of a constructor, which are otherwise used in place.
+    fn shadowing_locals(&self) -> Vec<(Ident, bool)> {
+        self.items()
+            .filter_map(|item| match item {
+                Item::Local(Local {
+                    pat: Pattern::Ident(ident),
+                    ..
+                }) => Some((ident.clone(), false)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn items(&self) -> impl Iterator<Item = &Item> + '_ {
+        self.stmts.iter().filter_map(|stmt| match &stmt.stmt {
+            StmtValue::Item(item) => Some(item),
+            _ => None,
+        })
+    }
+
+    fn with_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        scope::with_interfaces(self.interfaces(), || {
+            scope::with_signatures(self.items().flat_map(Item::signatures), || {
+                scope::with_accessors(self.items().flat_map(Item::accessors), || {
+                    scope::with_enums(self.items().filter_map(Item::enumeration), || {
+                        scope::with_unions(self.items().filter_map(Item::union), || {
+                            scope::with_field_params(self.shadowing_locals(), || {
+                                throws::Throwing::of(self.items()).scope(|| {
+                                    let classes = self.items().filter_map(Item::class);
+                                    scope::with_classes(classes, || {
+                                        let variables = Item::variables(self.items());
+                                        scope::with_variables(variables, f)
+                                    })
+                                })
+                            })
+                        })
+                    })
+                })
+            })
+        })
+    }
+
+    pub fn to_unit_code(&self, tokens: &mut TokenStream) {
+        self.with_scope(|| {
+            self.brace_token.surround(tokens, |tokens| {
+                Self::to_stmts_code(&self.stmts, false, tokens)
+            });
+        });
+    }
+
+    // With `tail`, a last expression without a semicolon is the value of the statements.
+    fn to_stmts_code(stmts: &[Stmt], tail: bool, tokens: &mut TokenStream) {
+        for (i, stmt) in stmts.iter().enumerate() {
+            let rest = &stmts[i + 1..];
+            match &stmt.stmt {
+                StmtValue::If(if_stmt) if if_stmt.to_narrowing_code(rest, tail, tokens) => return,
+                StmtValue::Expr(expr) if tail && rest.is_empty() && stmt.punct.is_none() => {
+                    expr.to_rust_code(tokens)
+                }
+                _ => stmt.to_rust_code(tokens),
+            }
+        }
+    }
+}
+
+impl ToRustCode for Block {
+    fn to_rust_code(&self, tokens: &mut TokenStream) {
+        self.with_scope(|| {
+            self.brace_token.surround(tokens, |tokens| {
+                Self::to_stmts_code(&self.stmts, true, tokens)
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::expr::{ExprInfer, RustCode};
+
+    // The conditions are built by hand, with `_` for the expression.
+    fn infer() -> Expr {
+        Expr::Infer(ExprInfer {
+            underscore_token: Default::default(),
+        })
+    }
+
+    fn cond() -> ExprParen {
+        ExprParen {
+            paren_token: Default::default(),
+            expr: Box::new(infer()),
+        }
+    }
+
+    fn block() -> Box<Stmt> {
+        Box::new(syn::parse_str("{}").unwrap())
+    }
+
+    #[test]
+    fn loops_with_else() {
+        // The `else` block runs when the condition fails, so a `break` out of the body skips it.
+        let stmt = StmtWhile {
+            while_token: Default::default(),
+            cond: cond(),
+            body: block(),
+            else_block: Some((Default::default(), block())),
+        };
+        assert_eq!(
+            stmt.to_rust_token_stream().to_string(),
+            quote! {
+                loop {
+                    if !(_) {
+                        {}
+                        break;
+                    }
+                    {}
+                }
+            }
+            .to_string()
+        );
+
+        let stmt = StmtDoWhile {
+            do_token: Default::default(),
+            body: block(),
+            while_token: Default::default(),
+            cond: cond(),
+            else_block: Some((Default::default(), block())),
+        };
+        assert_eq!(
+            stmt.to_rust_token_stream().to_string(),
+            quote! {{
+                let mut __rsscript_first = true;
+                loop {
+                    if !__rsscript_first && !(_) {
+                        {}
+                        break;
+                    }
+                    __rsscript_first = false;
+                    {}
+                }
+            }}
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn for_to_scoped_loop() {
+        let stmt = StmtFor {
+            for_token: Default::default(),
+            header: ForArgs::Header(ForHeader {
+                paren_token: Default::default(),
+                init: None,
+                semi_token: Default::default(),
+                cond: Some(infer()),
+                semi_token2: Default::default(),
+                after: Some(Box::new(StmtValue::Expr(infer()))),
+            }),
+            body: block(),
+            else_block: None,
+        };
+
+        // The update runs before every check but the first, so `continue` goes through it.
+        assert_eq!(
+            stmt.to_rust_token_stream().to_string(),
+            quote! {{
+                let mut __rsscript_first = true;
+                loop {
+                    if !__rsscript_first {
+                        _;
+                    }
+                    __rsscript_first = false;
+                    if !(_) { break; }
+                    {}
+                }
+            }}
+            .to_string()
+        );
+    }
+
+    fn rust_stmt(code: TokenStream) -> Stmt {
+        Stmt {
+            stmt: StmtValue::Expr(Expr::Rust(
+                syn::parse2::<RustCode>(quote!(rust { #code })).unwrap(),
+            )),
+            punct: Some(Default::default()),
+        }
+    }
+
+    fn break_stmt() -> Stmt {
+        syn::parse2(quote!(break;)).unwrap()
+    }
+
+    fn arm(decl: TokenStream, stmts: Vec<Stmt>) -> Arm {
+        Arm {
+            decl: syn::parse2(decl).unwrap(),
+            stmts,
+        }
+    }
+
+    fn switch(arms: Vec<Arm>) -> StmtSwitch {
+        StmtSwitch {
+            switch_token: syn::parse2(quote!(switch)).unwrap(),
+            expr: Expr::Rust(syn::parse2(quote!(rust { x })).unwrap()),
+            brace_token: Default::default(),
+            arms,
+        }
+    }
+
+    #[test]
+    fn switch_to_match() {
+        let stmt = switch(vec![
+            arm(quote!(case 1:), vec![]),
+            arm(quote!(case 2:), vec![rust_stmt(quote!(a())), break_stmt()]),
+            arm(quote!(default:), vec![rust_stmt(quote!(d())), break_stmt()]),
+            arm(quote!(case "c":), vec![rust_stmt(quote!(c()))]),
+        ]);
+
+        let number = quote!(::rsscript::types::number::Number);
+        let string = quote!(::rsscript::types::string::JsString);
+        assert_eq!(
+            stmt.to_rust_token_stream().to_string(),
+            quote! {
+                match { x } {
+                    __rsscript_value if (__rsscript_value == #number::from(1f64))
+                        || (__rsscript_value == #number::from(2f64)) => { { a() }; }
+                    __rsscript_value if (__rsscript_value
+                        == #string::new(::std::string::String::from("c"))) => { { c() }; }
+                    _ => { { d() }; }
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn switch_with_fall_through() {
+        let stmt = switch(vec![
+            arm(quote!(case 1:), vec![rust_stmt(quote!(a()))]),
+            arm(quote!(default:), vec![rust_stmt(quote!(d()))]),
+            arm(
+                quote!(case 2 | 3:),
+                vec![rust_stmt(quote!(b())), break_stmt()],
+            ),
+        ]);
+
+        let number = quote!(::rsscript::types::number::Number);
+        assert_eq!(
+            stmt.to_rust_token_stream().to_string(),
+            quote! {
+                loop {
+                    let __rsscript_value = { x };
+                    let __rsscript_entry: usize = if __rsscript_value == #number::from(1f64) {
+                        0usize
+                    } else if (__rsscript_value == #number::from(2f64))
+                        || (__rsscript_value == #number::from(3f64))
+                    {
+                        2usize
+                    } else {
+                        1usize
+                    };
+                    if __rsscript_entry == 0 { { a() }; }
+                    if __rsscript_entry <= 1usize { { d() }; }
+                    if __rsscript_entry <= 2usize { { b() }; break; }
+                    break;
+                }
+            }
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn switch_with_nested_break() {
+        let nested = Stmt {
+            stmt: StmtValue::Block(Block {
+                brace_token: Default::default(),
+                stmts: vec![break_stmt()],
+            }),
+            punct: None,
+        };
+        let stmt = switch(vec![arm(
+            quote!(case 1:),
+            vec![nested, rust_stmt(quote!(a())), break_stmt()],
+        )]);
+
+        assert!(!stmt.is_matchable());
+        assert!(stmt
+            .to_rust_token_stream()
+            .to_string()
+            .starts_with("loop {"));
+    }
+
+    #[test]
+    fn try_finally_rethrows() {
+        let stmt: Stmt = syn::parse_str("try { throw error; } finally { f(); }").unwrap();
+        let error_ty = quote!(::rsscript::types::error::Error);
+
+        assert_eq!(
+            stmt.to_rust_token_stream().to_string(),
+            quote! {{
+                #[allow(unused_labels, unreachable_code)]
+                let __rsscript_result: ::std::result::Result<(), #error_ty> = '__rsscript_try1: {
+                    {
+                        break '__rsscript_try1 ::std::result::Result::Err(
+                            ::std::convert::Into::<#error_ty>::into(error)
+                        );
+                    }
+                    ::std::result::Result::Ok(())
+                };
+                { f(); }
+                if let ::std::result::Result::Err(__rsscript_error) = __rsscript_result {
+                    ::std::panic!("Uncaught {}", __rsscript_error)
+                }
+            }}
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn labeled_jumps() {
+        let lower = |source: &str| {
+            let stmt: Stmt = syn::parse_str(source).unwrap();
+            stmt.to_rust_token_stream().to_string()
+        };
+
+        assert_eq!(
+            lower("outer: while (a) { while (b) { continue outer; } break; }"),
+            quote! {
+                '__rsscript_label0: while a {
+                    while b { continue '__rsscript_label0; }
+                    break;
+                }
+            }
+            .to_string()
+        );
+        assert_eq!(
+            lower("block: { if (a) { break block; } }"),
+            quote!('__rsscript_label0: {
+                if a {
+                    break '__rsscript_label0;
+                }
+            })
+            .to_string()
+        );
+
+        assert!(lower("break;").contains("`break` outside of a loop or `switch`"));
+        assert!(lower("while (a) { break inner; }").contains("use of undeclared label `inner`"));
+        assert!(lower("block: { continue block; }").contains("`continue` can only jump to a loop"));
+    }
+
+    #[test]
+    fn for_in_goes_over_values() {
+        let lower = |source: &str| {
+            let stmt: Stmt = syn::parse_str(source).unwrap();
+            stmt.to_rust_token_stream().to_string()
+        };
+
+        let expected = quote!(for x in xs {
+            f(x);
+        })
+        .to_string();
+        assert_eq!(lower("for (const x in xs) { f(x); }"), expected);
+        assert_eq!(lower("for (const x of xs) { f(x); }"), expected);
+    }
+}
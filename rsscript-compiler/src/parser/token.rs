@@ -73,6 +73,42 @@ macro_rules! Token {
     [from] => {
         $crate::parser::token::From
     };
+    [this] => {
+        $crate::parser::token::This
+    };
+    [implements] => {
+        $crate::parser::token::Implements
+    };
+    [void] => {
+        $crate::parser::token::Void
+    };
+    [delete] => {
+        $crate::parser::token::Delete
+    };
+    [throw] => {
+        $crate::parser::token::Throw
+    };
+    [catch] => {
+        $crate::parser::token::Catch
+    };
+    [finally] => {
+        $crate::parser::token::Finally
+    };
+    [new] => {
+        $crate::parser::token::New
+    };
+    [get] => {
+        $crate::parser::token::Get
+    };
+    [set] => {
+        $crate::parser::token::Set
+    };
+    [instanceof] => {
+        $crate::parser::token::InstanceOf
+    };
+    [of] => {
+        $crate::parser::token::Of
+    };
     [$token:tt] => {
         syn::Token![$token]
     };
@@ -82,6 +118,7 @@ macro_rules! Token {
 macro_rules! define_keyword {
     ($($keyword:tt, $struct:ident);+ $(;)?) => {
         $(
+            #[derive(Debug)]
             pub struct $struct {
                 pub span: Span,
             }
@@ -117,6 +154,18 @@ define_keyword!(
     interface, Interface;
     import, Import;
     from, From;
+    this, This;
+    implements, Implements;
+    void, Void;
+    delete, Delete;
+    throw, Throw;
+    catch, Catch;
+    finally, Finally;
+    new, New;
+    get, Get;
+    set, Set;
+    instanceof, InstanceOf;
+    of, Of;
 );
 
 #[cfg(test)]
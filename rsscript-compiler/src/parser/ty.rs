@@ -0,0 +1,605 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{
+    braced, parenthesized, parse::Parse, parse_quote, spanned::Spanned, token::Brace,
+    GenericArgument, Ident, Lit, PathArguments, Type,
+};
+
+use crate::{
+    encode::{
+        self,
+        scope::{Union, Variant, VariantKind},
+        ToRustCode,
+    },
+    parser::{expr::ExprLit, generics::Generics},
+    Token,
+};
+
+// TypeScript types that have no Rust syntax: unions, intersections, literal types, `null`,
+// `undefined` and object types. Anything else is a Rust type.
+#[derive(Debug)]
+pub enum ScriptType {
+    Rust(Type),
+    Lit(Lit),
+    Null(Span),
+    Undefined(Span),
+    Object(TypeObject),
+    Union(Vec<ScriptType>),
+    Intersection(Vec<ScriptType>),
+}
+
+#[derive(Debug)]
+pub struct TypeObject {
+    pub brace_token: Brace,
+    pub fields: Vec<TypeField>,
+}
+
+#[derive(Debug)]
+pub struct TypeField {
+    pub ident: Ident,
+    pub optional: Option<Token![?]>,
+    pub colon_token: Token![:],
+    pub ty: ScriptType,
+}
+
+impl Parse for TypeField {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            ident: input.parse()?,
+            optional: input.parse()?,
+            colon_token: input.parse()?,
+            ty: input.parse()?,
+        })
+    }
+}
+
+impl Parse for TypeObject {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let content;
+        let brace_token = braced!(content in input);
+        let mut fields = Vec::new();
+
+        while !content.is_empty() {
+            fields.push(content.parse()?);
+            if content.is_empty() {
+                break;
+            }
+            if content.peek(Token![;]) {
+                content.parse::<Token![;]>()?;
+            } else {
+                content.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(Self {
+            brace_token,
+            fields,
+        })
+    }
+}
+
+fn peek_bar(input: syn::parse::ParseStream) -> bool {
+    input.peek(Token![|]) && !input.peek(Token![||]) && !input.peek(Token![|=])
+}
+
+fn peek_ampersand(input: syn::parse::ParseStream) -> bool {
+    input.peek(Token![&]) && !input.peek(Token![&&]) && !input.peek(Token![&=])
+}
+
+fn peek_keyword(input: syn::parse::ParseStream, keyword: &str) -> bool {
+    input.peek(Ident)
+        && input
+            .fork()
+            .parse::<Ident>()
+            .is_ok_and(|ident| ident == keyword)
+}
+
+fn parse_primary(input: syn::parse::ParseStream) -> syn::Result<ScriptType> {
+    if input.peek(Lit) {
+        Ok(ScriptType::Lit(input.parse()?))
+    } else if peek_keyword(input, "null") {
+        Ok(ScriptType::Null(input.parse::<Ident>()?.span()))
+    } else if peek_keyword(input, "undefined") {
+        Ok(ScriptType::Undefined(input.parse::<Ident>()?.span()))
+    } else if input.peek(Brace) {
+        Ok(ScriptType::Object(input.parse()?))
+    } else if input.peek(syn::token::Paren) {
+        // A parenthesized type groups a union, anything else is left to Rust (e.g. tuples).
+        let fork = input.fork();
+        let grouped = (|| {
+            let content;
+            parenthesized!(content in fork);
+            let ty: ScriptType = content.parse()?;
+            if !content.is_empty() {
+                return Err(content.error("expected `)`"));
+            }
+            Ok(ty)
+        })();
+        match grouped {
+            Ok(ScriptType::Rust(_)) | Err(_) => Ok(ScriptType::Rust(input.parse()?)),
+            Ok(ty) => {
+                let content;
+                parenthesized!(content in input);
+                let _: TokenStream = content.parse()?;
+                Ok(ty)
+            }
+        }
+    } else {
+        Ok(ScriptType::Rust(input.parse()?))
+    }
+}
+
+fn parse_intersection(input: syn::parse::ParseStream) -> syn::Result<ScriptType> {
+    let mut members = vec![parse_primary(input)?];
+    while peek_ampersand(input) {
+        input.parse::<Token![&]>()?;
+        members.push(parse_primary(input)?);
+    }
+
+    Ok(match members.len() {
+        1 => members.pop().unwrap(),
+        _ => ScriptType::Intersection(members),
+    })
+}
+
+impl Parse for ScriptType {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        if peek_bar(input) {
+            input.parse::<Token![|]>()?;
+        }
+        let mut members = vec![parse_intersection(input)?];
+        while peek_bar(input) {
+            input.parse::<Token![|]>()?;
+            members.push(parse_intersection(input)?);
+        }
+
+        Ok(match members.len() {
+            1 => members.pop().unwrap(),
+            _ => Self::Union(members),
+        })
+    }
+}
+
+fn lit_type(lit: &Lit) -> syn::Result<Type> {
+    let runtime = encode::runtime();
+    match lit {
+        Lit::Str(_) => Ok(parse_quote!(#runtime::string::JsString)),
+        Lit::Int(_) | Lit::Float(_) => Ok(parse_quote!(#runtime::number::Number)),
+        Lit::Bool(_) => Ok(parse_quote!(bool)),
+        lit => Err(syn::Error::new(lit.span(), "unsupported literal type")),
+    }
+}
+
+// Literals are compared by value, so that `1` and `1.0` are the same type.
+pub fn same_lit(a: &Lit, b: &Lit) -> bool {
+    match (a, b) {
+        (Lit::Str(a), Lit::Str(b)) => a.value() == b.value(),
+        (Lit::Bool(a), Lit::Bool(b)) => a.value == b.value,
+        (Lit::Int(_) | Lit::Float(_), Lit::Int(_) | Lit::Float(_)) => {
+            lit_number(a).is_some() && lit_number(a) == lit_number(b)
+        }
+        _ => false,
+    }
+}
+
+fn lit_number(lit: &Lit) -> Option<f64> {
+    match lit {
+        Lit::Int(lit) => lit.base10_parse().ok(),
+        Lit::Float(lit) => lit.base10_parse().ok(),
+        _ => None,
+    }
+}
+
+pub fn lit_code(lit: &Lit) -> TokenStream {
+    ExprLit { lit: lit.clone() }.into_rust_token_stream()
+}
+
+impl ScriptType {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Rust(ty) => ty.span(),
+            Self::Lit(lit) => lit.span(),
+            Self::Null(span) | Self::Undefined(span) => *span,
+            Self::Object(ty) => ty.brace_token.span.join(),
+            Self::Union(members) | Self::Intersection(members) => members[0].span(),
+        }
+    }
+
+    fn is_nullish(&self) -> bool {
+        matches!(self, Self::Null(_) | Self::Undefined(_))
+    }
+
+    // The Rust type this type stands for wherever no item can be generated for it: literal types
+    // widen to their primitive, and a union with `null` or `undefined` becomes an `Option`.
+    pub fn rust_type(&self) -> syn::Result<Type> {
+        match self {
+            Self::Rust(ty) => Ok(ty.clone()),
+            Self::Lit(lit) => lit_type(lit),
+            Self::Null(span) | Self::Undefined(span) => Err(syn::Error::new(
+                *span,
+                "`null` and `undefined` can only be used in a union",
+            )),
+            Self::Object(_) | Self::Intersection(_) => Err(syn::Error::new(
+                self.span(),
+                "object types must be named with a `type` alias",
+            )),
+            Self::Union(members) => {
+                let values: Vec<&Self> = members.iter().filter(|ty| !ty.is_nullish()).collect();
+                let ty = match values.as_slice() {
+                    [ty] => ty.rust_type()?,
+                    values
+                        if !values.is_empty()
+                            && values.iter().all(|ty| matches!(ty, Self::Lit(_))) =>
+                    {
+                        let tys = values
+                            .iter()
+                            .map(|ty| ty.rust_type())
+                            .collect::<syn::Result<Vec<_>>>()?;
+                        let first = tys[0].to_token_stream().to_string();
+                        if tys
+                            .iter()
+                            .any(|ty| ty.to_token_stream().to_string() != first)
+                        {
+                            return Err(union_error(self.span()));
+                        }
+                        tys.into_iter().next().unwrap()
+                    }
+                    _ => return Err(union_error(self.span())),
+                };
+
+                if values.len() == members.len() {
+                    Ok(ty)
+                } else {
+                    Ok(parse_quote!(::std::option::Option<#ty>))
+                }
+            }
+        }
+    }
+
+    // The fields of an object type, or of an intersection of object types.
+    fn object_fields(&self) -> Option<syn::Result<Vec<&TypeField>>> {
+        match self {
+            Self::Object(ty) => Some(Ok(ty.fields.iter().collect())),
+            Self::Intersection(members) => {
+                let mut fields: Vec<&TypeField> = Vec::new();
+                for member in members {
+                    let Some(member_fields) = member.object_fields() else {
+                        return Some(Err(syn::Error::new(
+                            member.span(),
+                            "intersection types can only combine object types",
+                        )));
+                    };
+                    let member_fields = match member_fields {
+                        Ok(member_fields) => member_fields,
+                        Err(err) => return Some(Err(err)),
+                    };
+                    for field in member_fields {
+                        if fields.iter().any(|other| other.ident == field.ident) {
+                            return Some(Err(syn::Error::new(
+                                field.ident.span(),
+                                format!("duplicate field `{}` in intersection", field.ident),
+                            )));
+                        }
+                        fields.push(field);
+                    }
+                }
+                Some(Ok(fields))
+            }
+            _ => None,
+        }
+    }
+}
+
+fn union_error(span: Span) -> syn::Error {
+    syn::Error::new(span, "union types must be named with a `type` alias")
+}
+
+fn field_type(field: &TypeField) -> syn::Result<Type> {
+    let ty = field.ty.rust_type()?;
+    if field.optional.is_some() {
+        Ok(parse_quote!(::std::option::Option<#ty>))
+    } else {
+        Ok(ty)
+    }
+}
+
+// Turns a literal into a variant name, e.g. `"circle"` into `Circle` and `"in-progress"` into
+// `InProgress`.
+fn lit_variant_name(lit: &Lit) -> Option<String> {
+    let text = match lit {
+        Lit::Str(lit) => lit.value(),
+        Lit::Bool(lit) => lit.value.to_string(),
+        lit => format!("V{}", lit_number(lit)?)
+            .replace('-', "Neg")
+            .replace('.', "_"),
+    };
+    let name: String = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            let first = chars.next().unwrap().to_uppercase();
+            first.chain(chars).collect::<String>()
+        })
+        .collect();
+
+    match name.chars().next() {
+        Some(first) if !first.is_ascii_digit() && name.is_ascii() => Some(name),
+        Some(_) => Some(format!("V{}", name)),
+        None => None,
+    }
+}
+
+fn type_variant_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(ty) => ty
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        _ => None,
+    }
+}
+
+// A union with a literal typed field in every member, telling them apart, is a discriminated
+// union. Its members become struct variants named after that field.
+fn discriminant(members: &[Vec<&TypeField>]) -> Option<Ident> {
+    let first = members.first()?;
+
+    first.iter().find_map(|field| {
+        let mut tags: Vec<&Lit> = Vec::new();
+        for member in members {
+            let ScriptType::Lit(lit) = &member
+                .iter()
+                .find(|other| other.ident == field.ident && other.optional.is_none())?
+                .ty
+            else {
+                return None;
+            };
+            if tags.iter().any(|tag| same_lit(tag, lit)) {
+                return None;
+            }
+            tags.push(lit);
+        }
+        Some(field.ident.clone())
+    })
+}
+
+pub fn to_union(ident: &Ident, generics: &Generics, members: &[ScriptType]) -> syn::Result<Union> {
+    let objects: Option<Vec<Vec<&TypeField>>> = members
+        .iter()
+        .map(|member| member.object_fields())
+        .collect::<Option<Vec<_>>>()
+        .map(|objects| objects.into_iter().collect::<syn::Result<_>>())
+        .transpose()?;
+    let tag = objects.as_deref().and_then(discriminant);
+
+    let mut variants: Vec<Variant> = Vec::new();
+    for (i, member) in members.iter().enumerate() {
+        let (name, kind) = match member {
+            ScriptType::Null(_) => (Some("Null".to_string()), VariantKind::Null),
+            ScriptType::Undefined(_) => (Some("Undefined".to_string()), VariantKind::Undefined),
+            ScriptType::Lit(lit) => {
+                lit_type(lit)?;
+                (lit_variant_name(lit), VariantKind::Lit(lit.clone()))
+            }
+            ScriptType::Rust(ty) => (type_variant_name(ty), VariantKind::Type(ty.clone())),
+            ScriptType::Union(_) => {
+                let ty = member.rust_type()?;
+                (type_variant_name(&ty), VariantKind::Type(ty))
+            }
+            ScriptType::Object(_) | ScriptType::Intersection(_) => {
+                let fields = member.object_fields().unwrap()?;
+                let mut tag_value = None;
+                let mut variant_fields = Vec::new();
+                for field in fields {
+                    match (&tag, &field.ty) {
+                        (Some(tag), ScriptType::Lit(lit)) if field.ident == *tag => {
+                            tag_value = Some(lit.clone())
+                        }
+                        _ => variant_fields.push((field.ident.clone(), field_type(field)?)),
+                    }
+                }
+                let name = tag_value.as_ref().and_then(lit_variant_name);
+                let kind = VariantKind::Object {
+                    tag: tag_value,
+                    fields: variant_fields,
+                };
+                (name, kind)
+            }
+        };
+
+        let mut name = name.unwrap_or_else(|| format!("V{}", i));
+        if variants.iter().any(|variant| variant.ident == name) {
+            name = format!("{}{}", name, i);
+        }
+        variants.push(Variant {
+            ident: Ident::new(&name, member.span()),
+            kind,
+        });
+    }
+
+    Ok(Union {
+        ident: ident.clone(),
+        tag,
+        params: type_params(generics).into_iter().cloned().collect(),
+        variants,
+    })
+}
+
+// Only unions of types known to implement them derive the usual traits, since classes do not.
+fn is_plain(ty: &Type, params: &[&Ident]) -> bool {
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() => {
+            let Some(segment) = ty.path.segments.last() else {
+                return false;
+            };
+            let args_plain = match &segment.arguments {
+                PathArguments::None => true,
+                PathArguments::AngleBracketed(args) => args.args.iter().all(|arg| match arg {
+                    GenericArgument::Type(ty) => is_plain(ty, params),
+                    _ => false,
+                }),
+                PathArguments::Parenthesized(_) => false,
+            };
+            let name = segment.ident.to_string();
+            args_plain
+                && (params.iter().any(|param| segment.ident == **param)
+                    || [
+                        "Number", "JsString", "bool", "String", "f64", "Option", "Vec",
+                    ]
+                    .contains(&name.as_str()))
+        }
+        Type::Tuple(ty) => ty.elems.iter().all(|ty| is_plain(ty, params)),
+        _ => false,
+    }
+}
+
+fn type_params(generics: &Generics) -> Vec<&Ident> {
+    generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            crate::parser::generics::GenericsParam::Type(param) => Some(&param.ident),
+            _ => None,
+        })
+        .collect()
+}
+
+fn derives(plain: bool) -> TokenStream {
+    if plain {
+        quote!(#[derive(::std::clone::Clone, ::std::fmt::Debug, ::std::cmp::PartialEq)])
+    } else {
+        TokenStream::new()
+    }
+}
+
+// An object type becomes a struct with public fields.
+pub fn struct_code(
+    vis: &TokenStream,
+    ident: &Ident,
+    generics: &Generics,
+    ty: &ScriptType,
+) -> Option<syn::Result<TokenStream>> {
+    let fields = match ty.object_fields()? {
+        Ok(fields) => fields,
+        Err(err) => return Some(Err(err)),
+    };
+    let tys = match fields
+        .iter()
+        .map(|field| field_type(field))
+        .collect::<syn::Result<Vec<_>>>()
+    {
+        Ok(tys) => tys,
+        Err(err) => return Some(Err(err)),
+    };
+    let idents = fields.iter().map(|field| &field.ident);
+    let params = type_params(generics);
+    let derives = derives(tys.iter().all(|ty| is_plain(ty, &params)));
+    let generics = generics.to_rust_token_stream();
+
+    Some(Ok(quote! {
+        #derives
+        #vis struct #ident #generics {
+            #(pub #idents: #tys,)*
+        }
+    }))
+}
+
+// A union becomes an enum with a variant per member. Discriminated unions also get a method
+// reading the discriminant, and every union implements `typeof`.
+pub fn union_code(vis: &TokenStream, generics: &Generics, union: &Union) -> TokenStream {
+    let runtime = encode::runtime();
+    let ident = &union.ident;
+    let params = type_params(generics);
+    let plain = union.variants.iter().all(|variant| match &variant.kind {
+        VariantKind::Type(ty) => is_plain(ty, &params),
+        VariantKind::Object { fields, .. } => fields.iter().all(|(_, ty)| is_plain(ty, &params)),
+        _ => true,
+    });
+    let derives = derives(plain);
+    let mut impl_generics = TokenStream::new();
+    generics.to_impl_generics_code(&mut impl_generics);
+    let mut type_generics = TokenStream::new();
+    generics.to_type_generics_code(&mut type_generics);
+    let generics = generics.to_rust_token_stream();
+
+    let variants = union.variants.iter().map(|variant| {
+        let name = &variant.ident;
+        match &variant.kind {
+            VariantKind::Type(ty) => quote!(#name(#ty)),
+            VariantKind::Object { fields, .. } => {
+                let (idents, tys): (Vec<_>, Vec<_>) = fields.iter().cloned().unzip();
+                quote!(#name { #(#idents: #tys),* })
+            }
+            _ => quote!(#name),
+        }
+    });
+
+    let type_ofs = union.variants.iter().map(|variant| {
+        let pattern = variant.pattern(ident, None);
+        let value = encode::private_ident("value");
+        let type_of = match union.type_of(variant) {
+            Some(name) => {
+                quote!(#runtime::string::JsString::new(::std::string::String::from(#name)))
+            }
+            None => quote!(#runtime::ops::TypeOf::type_of(#value)),
+        };
+        let pattern = match &variant.kind {
+            VariantKind::Type(_) if union.type_of(variant).is_none() => {
+                let name = &variant.ident;
+                quote!(#ident::#name(#value))
+            }
+            _ => pattern,
+        };
+        quote!(#pattern => #type_of)
+    });
+
+    let tag = union.tag.as_ref().map(|tag| {
+        let arms = union.variants.iter().filter_map(|variant| {
+            let VariantKind::Object { tag: Some(lit), .. } = &variant.kind else {
+                return None;
+            };
+            let pattern = variant.pattern(ident, None);
+            let value = lit_code(lit);
+            Some(quote!(#pattern => #value))
+        });
+        let ty = union
+            .variants
+            .iter()
+            .find_map(|variant| match &variant.kind {
+                VariantKind::Object { tag: Some(lit), .. } => lit_type(lit).ok(),
+                _ => None,
+            })
+            .into_iter();
+        quote! {
+            impl #impl_generics #ident #type_generics {
+                #vis fn #tag(&self) -> #(#ty)* {
+                    match self {
+                        #(#arms),*
+                    }
+                }
+            }
+        }
+    });
+
+    quote! {
+        #derives
+        #vis enum #ident #generics {
+            #(#variants,)*
+        }
+
+        #tag
+
+        impl #impl_generics #runtime::ops::TypeOf for #ident #type_generics
+        where
+            #(#params: #runtime::ops::TypeOf,)*
+        {
+            fn type_of(&self) -> #runtime::string::JsString {
+                match self {
+                    #(#type_ofs),*
+                }
+            }
+        }
+    }
+}
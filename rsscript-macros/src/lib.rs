@@ -1,14 +1,112 @@
-pub fn add(left: u64, right: u64) -> u64 {
-    left + right
+use proc_macro::TokenStream;
+use quote::quote;
+use rsscript_compiler::{
+    encode::ToRustCode,
+    parser::{
+        stmt::{Block, Stmt, StmtValue},
+        File,
+    },
+};
+use script::ScriptFile;
+use syn::{parse::Parse, parse_macro_input, token::Brace, ItemMod, LitStr};
+
+mod script;
+
+struct Script {
+    stmts: Vec<Stmt>,
+}
+
+impl Parse for Script {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut stmts = Vec::new();
+
+        while !input.is_empty() {
+            let next = input.parse()?;
+            stmts.push(next);
+        }
+
+        Ok(Self { stmts })
+    }
+}
+
+impl Script {
+    // A script made only of declarations expands to items, anything else to a block expression.
+    fn into_file(self) -> Result<File, Self> {
+        if !self
+            .stmts
+            .iter()
+            .all(|stmt| matches!(stmt.stmt, StmtValue::Item(_)))
+        {
+            return Err(self);
+        }
+
+        let items = self
+            .stmts
+            .into_iter()
+            .filter_map(|stmt| match stmt.stmt {
+                StmtValue::Item(item) => Some(item),
+                _ => None,
+            })
+            .collect();
+
+        Ok(File { items })
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+#[proc_macro]
+pub fn rsscript(input: TokenStream) -> TokenStream {
+    let script = parse_macro_input!(input as Script);
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+    match script.into_file() {
+        Ok(file) => file.into_rust_token_stream().into(),
+        Err(script) => Block {
+            brace_token: Brace::default(),
+            stmts: script.stmts,
+        }
+        .into_rust_token_stream()
+        .into(),
     }
 }
+
+#[proc_macro]
+pub fn include_rss(input: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(input as LitStr);
+
+    let expand = || {
+        let file = ScriptFile::new(&path)?;
+        let ident = file.module_name(path.span())?;
+        let items = file.expand(path.span())?;
+
+        Ok(quote!(mod #ident { #items }))
+    };
+
+    expand()
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_attribute]
+pub fn module(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let path = parse_macro_input!(attr as LitStr);
+    let module = parse_macro_input!(item as ItemMod);
+
+    let expand = || {
+        let Some((_, content)) = &module.content else {
+            return Err(syn::Error::new_spanned(
+                &module,
+                "`#[module]` needs an inline module, like `mod name {}`",
+            ));
+        };
+        let items = ScriptFile::new(&path)?.expand(path.span())?;
+
+        let attrs = &module.attrs;
+        let vis = &module.vis;
+        let mod_token = &module.mod_token;
+        let ident = &module.ident;
+        Ok(quote!(#(#attrs)* #vis #mod_token #ident { #(#content)* #items }))
+    };
+
+    expand()
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
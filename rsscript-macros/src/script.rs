@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use rsscript_compiler::build;
+use syn::LitStr;
+
+// The fallback implementation of proc-macro2 keeps line and column information for tokens which
+// did not come from the macro input, which is what diagnostics point into the script with. It is
+// only forced while the guard lives, so that it is switched back even if lowering panics.
+struct ForcedFallback;
+
+impl ForcedFallback {
+    fn new() -> Self {
+        proc_macro2::fallback::force();
+        Self
+    }
+}
+
+impl Drop for ForcedFallback {
+    fn drop(&mut self) {
+        proc_macro2::fallback::unforce();
+    }
+}
+
+pub struct ScriptFile {
+    pub path: PathBuf,
+    pub name: String,
+}
+
+impl ScriptFile {
+    pub fn new(path: &LitStr) -> syn::Result<Self> {
+        let root = std::env::var("CARGO_MANIFEST_DIR")
+            .map_err(|_| syn::Error::new(path.span(), "`CARGO_MANIFEST_DIR` is not set"))?;
+        let relative = path.value();
+
+        Ok(Self {
+            path: Path::new(&root).join(&relative),
+            name: relative,
+        })
+    }
+
+    pub fn module_name(&self, span: Span) -> syn::Result<syn::Ident> {
+        self.path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| syn::parse_str(stem).ok())
+            .ok_or_else(|| {
+                syn::Error::new(
+                    span,
+                    format!("`{}` is not a valid module name", self.path.display()),
+                )
+            })
+    }
+
+    // Expands to the items of the module, including a dependency on the file so that cargo
+    // rebuilds the crate when the script changes.
+    pub fn expand(&self, span: Span) -> syn::Result<TokenStream> {
+        let source = std::fs::read_to_string(&self.path).map_err(|err| {
+            syn::Error::new(
+                span,
+                format!("couldn't read `{}`: {}", self.path.display(), err),
+            )
+        })?;
+
+        let items = {
+            let _fallback = ForcedFallback::new();
+            build::compile(&self.name, &source).map(|items| items.to_string())
+        };
+
+        let items: TokenStream = items
+            .map_err(|diagnostics| {
+                diagnostics
+                    .into_iter()
+                    .map(|diagnostic| syn::Error::new(span, diagnostic))
+                    .reduce(|mut error, next| {
+                        error.combine(next);
+                        error
+                    })
+                    .unwrap()
+            })?
+            .parse()?;
+
+        let path = self.path.display().to_string();
+        Ok(quote! {
+            #[allow(unused_imports)]
+            use super::*;
+
+            const _: &[u8] = include_bytes!(#path);
+
+            #items
+        })
+    }
+}
@@ -0,0 +1,992 @@
+use rsscript::types::{
+    error::{Error, RangeError, TypeError},
+    number::Number,
+    string::JsString,
+};
+use rsscript_macros::{include_rss, module, rsscript};
+
+rsscript! {
+    class Point {
+        x: Number;
+        y: Number;
+    }
+}
+
+#[test]
+fn items() {
+    let point = Point::new();
+
+    assert_eq!(point.x, Number::default());
+    assert_eq!(point.y, Number::default());
+}
+
+include_rss!("tests/scripts/shapes.rss");
+
+#[module("tests/scripts/shapes.rss")]
+mod scripts {
+    pub fn square() -> super::shapes::Square {
+        super::shapes::Square::new()
+    }
+}
+
+#[test]
+fn script_files() {
+    assert_eq!(
+        <shapes::Square as shapes::Shape>::Unit::default(),
+        shapes::Square::new().size
+    );
+    assert_eq!(scripts::Square::new().size, scripts::square().size);
+}
+
+#[test]
+fn expressions() {
+    let value = rsscript! {
+        let a = 1;
+        let b = 2;
+        a + b * 3 ** 2 - (a + b)
+    };
+
+    assert_eq!(value, Number::from(16.0));
+}
+
+#[test]
+fn operators() {
+    let value = rsscript! {
+        let i = 1;
+        let old = i++;
+        let new = ++i;
+        i **= 2;
+        i -= old + new;
+        -i + ~old
+    };
+
+    assert_eq!(value, Number::from(-7.0));
+}
+
+include_rss!("tests/scripts/greet.rss");
+
+#[test]
+fn templates() {
+    let name = JsString::new(String::from("Ann"));
+    let value = rsscript! {
+        $"${name}: ${1 + 2}"
+    };
+
+    assert_eq!(*value, "Ann: 3");
+    assert_eq!(
+        *greet::greet(name, Number::from(1.0)),
+        "Hello, Ann! You have 2 new \"messages\"."
+    );
+    assert_eq!(greet::tagged(), Number::from(4.0));
+}
+
+rsscript! {
+    class Pair {
+        first: Number;
+        second: Option<Number>;
+    }
+
+    function sum(
+        { first, second = 10 }: Pair,
+        [x, ...rest]: Vec<Number>,
+        pairs: Vec<Vec<Number>>,
+    ): Number {
+        let total = first + second + x + (rest[1] ?? 0);
+        for (const [a, b, c = 1] in pairs) {
+            total += a * b + c;
+        }
+        total
+    }
+
+    function leftover({ first, ...rest }: Pair): Number {
+        first + (rest.first ?? 100)
+    }
+
+    function second_of([first, second]: Vec<Number>): Number {
+        second
+    }
+}
+
+rsscript! {
+    function element(values: Vec<Number>, index: Number): Option<Number> {
+        values[index]
+    }
+
+    function bump(values: Vec<Number>): Vec<Number> {
+        let bumped = values;
+        bumped[0] += 1;
+        bumped
+    }
+}
+
+#[test]
+fn checked_elements() {
+    let values = vec![Number::from(2.0), Number::from(3.0)];
+
+    assert_eq!(
+        element(values.clone(), Number::from(1.0)),
+        Some(Number::from(3.0))
+    );
+    assert_eq!(element(values.clone(), Number::from(5.0)), None);
+    assert_eq!(element(values.clone(), Number::from(-1.0)), None);
+    assert_eq!(element(values.clone(), Number::from(0.5)), None);
+    assert_eq!(element(values.clone(), Number::from(f64::NAN)), None);
+    assert_eq!(bump(values)[0], Number::from(3.0));
+}
+
+#[test]
+fn destructuring() {
+    let mut pair = Pair::new();
+    pair.first = Number::from(1.0);
+    let values = vec![Number::from(2.0), Number::from(3.0), Number::from(4.0)];
+    let pairs = vec![vec![Number::from(2.0), Number::from(3.0)]];
+
+    assert_eq!(sum(pair, values, pairs), Number::from(24.0));
+
+    let mut pair = Pair::new();
+    pair.first = Number::from(1.0);
+    assert_eq!(leftover(pair), Number::from(101.0));
+}
+
+#[test]
+#[should_panic(expected = "Uncaught TypeError: missing element 1 to destructure")]
+fn destructuring_missing_element() {
+    second_of(vec![Number::from(1.0)]);
+}
+
+rsscript! {
+    class Animal {
+        name: JsString = "animal";
+
+        speak(): JsString {
+            $"${this.name} makes a sound"
+        }
+
+        legs(): Number {
+            4
+        }
+    }
+
+    class Dog extends Animal {
+        speak(): JsString {
+            $"${this.name} barks"
+        }
+    }
+
+    class Puppy extends Dog {
+        legs(): Number {
+            3
+        }
+    }
+
+    function introduce(animal: impl AnimalMethods): JsString {
+        let pet = animal;
+        $"${pet.speak()} on ${pet.legs()} legs"
+    }
+}
+
+#[test]
+fn overridden_methods() {
+    let mut puppy = Puppy::new();
+    puppy.name = JsString::new(String::from("Rex"));
+    assert_eq!(*introduce(puppy), "Rex barks on 3 legs");
+    assert_eq!(*introduce(Animal::new()), "animal makes a sound on 4 legs");
+
+    let mut animals: Vec<Box<dyn AnimalMethods>> = vec![
+        Box::new(Animal::new()),
+        Box::new(Dog::new()),
+        Box::new(Puppy::new()),
+    ];
+    let sounds: Vec<_> = animals
+        .iter_mut()
+        .map(|animal| format!("{} {}", animal.speak(), animal.legs()))
+        .collect();
+    assert_eq!(
+        sounds,
+        ["animal makes a sound 4", "animal barks 4", "animal barks 3"]
+    );
+}
+
+rsscript! {
+    function total(base: Number, ...values: Number[]): Number {
+        let sum = base;
+        for (const value in values) {
+            sum += value;
+        }
+        sum
+    }
+
+    function spread(): Number {
+        const values = [1, 2];
+        const more = [...values, 3, ...values];
+        total(10, ...more, 4) + total(0)
+    }
+}
+
+#[test]
+fn spread_and_rest() {
+    assert_eq!(spread(), Number::from(23.0));
+}
+
+rsscript! {
+    function check(value: Number): Number {
+        if (value < 0) {
+            throw RangeError("negative value");
+        }
+        value
+    }
+
+    function double(value: Number): Number {
+        check(value) * 2
+    }
+
+    function attempt(value: Number): JsString {
+        let log = "try";
+        try {
+            double(value);
+            log += " done";
+        } catch (e) {
+            log += $" caught ${e.name}: ${e.message}";
+        } finally {
+            log += " finally";
+        }
+        log
+    }
+
+    function rethrow(): Number {
+        try {
+            throw TypeError("not a number");
+        } catch {
+            throw Error("failed");
+        }
+        1
+    }
+}
+
+#[test]
+fn exceptions() {
+    assert_eq!(*attempt(Number::from(1.0)), "try done finally");
+    assert_eq!(
+        *attempt(Number::from(-1.0)),
+        "try caught RangeError: negative value finally"
+    );
+
+    let error = double(Number::from(-1.0)).unwrap_err();
+    assert_eq!(error.to_string(), "RangeError: negative value");
+    assert_eq!(error.stack, ["check", "double"]);
+    assert_eq!(*rethrow().unwrap_err().message, "failed");
+}
+
+rsscript! {
+    function early_exit(value: Number): Number {
+        try {
+            if (value > 10) {
+                return value;
+            }
+        } finally {
+            if (value > 100) {
+                throw RangeError("too big");
+            }
+        }
+        0
+    }
+
+    function visit(stop: Number): JsString {
+        let log = "";
+        for (let i = 0; i < 5; i++) {
+            try {
+                if (i == stop) {
+                    break;
+                }
+                if (i % 2 == 1) {
+                    continue;
+                }
+                log += $"${i}";
+            } finally {
+                log += ".";
+            }
+        }
+        log
+    }
+
+    function unwind(value: Number): Number {
+        let steps = 0;
+        outer: while (true) {
+            try {
+                try {
+                    if (value > 1) {
+                        return value;
+                    }
+                    break outer;
+                } finally {
+                    steps += 1;
+                }
+            } finally {
+                steps += 10;
+            }
+        }
+        steps
+    }
+}
+
+#[test]
+fn finally_on_jumps() {
+    assert_eq!(early_exit(Number::from(5.0)), Ok(Number::from(0.0)));
+    assert_eq!(early_exit(Number::from(20.0)), Ok(Number::from(20.0)));
+    assert!(early_exit(Number::from(200.0)).is_err());
+    assert_eq!(*visit(Number::from(3.0)), "0..2..");
+    assert_eq!(*visit(Number::from(9.0)), "0..2..4.");
+    assert_eq!(unwind(Number::from(0.0)), Number::from(11.0));
+    assert_eq!(unwind(Number::from(2.0)), Number::from(2.0));
+}
+
+rsscript! {
+    class Wallet {
+        balance: Number;
+
+        constructor(balance: Number) {
+            if (balance < 0) {
+                throw RangeError("negative balance");
+            }
+            this.balance = balance;
+        }
+
+        static open(balance: Number): Wallet {
+            new Wallet(balance)
+        }
+
+        spend(amount: Number): Number {
+            if (amount > this.balance) {
+                throw RangeError("insufficient funds");
+            }
+            this.balance -= amount;
+            this.balance
+        }
+
+        spend_twice(amount: Number): Number {
+            this.spend(amount);
+            this.spend(amount)
+        }
+    }
+
+    function try_spend(amount: Number): JsString {
+        let log = "spent";
+        try {
+            let wallet = Wallet::open(10);
+            wallet.spend_twice(amount);
+        } catch (e) {
+            log = e.message;
+        }
+        log
+    }
+}
+
+#[test]
+fn throwing_methods() {
+    assert_eq!(*try_spend(Number::from(4.0)), "spent");
+    assert_eq!(*try_spend(Number::from(6.0)), "insufficient funds");
+
+    let error = Wallet::open(Number::from(-1.0)).err().unwrap();
+    assert_eq!(error.to_string(), "RangeError: negative balance");
+    assert_eq!(error.stack, ["Wallet.constructor", "Wallet.open"]);
+
+    let mut wallet = Wallet::new(Number::from(3.0)).unwrap();
+    assert_eq!(wallet.spend(Number::from(1.0)), Ok(Number::from(2.0)));
+}
+
+rsscript! {
+    function check_all(limit: Number): JsString {
+        let check = (value) => {
+            if (value > limit) {
+                throw RangeError("over the limit");
+            }
+            value
+        };
+        let twice = function (value: Number): Number {
+            check(value) + check(value)
+        };
+        let log = "ok";
+        try {
+            twice(limit);
+            check(limit + 1);
+        } catch (e) {
+            log = e.message;
+        }
+        log
+    }
+}
+
+#[test]
+fn throwing_closures() {
+    assert_eq!(*check_all(Number::from(1.0)), "over the limit");
+}
+
+rsscript! {
+    function positive(value: Number): Number {
+        if (value <= 0) {
+            throw RangeError("not positive");
+        }
+        value
+    }
+
+    function find(values: Vec<Number>, target: Number): Number {
+        let i = 0;
+        for (const value in values) {
+            if (value == target) {
+                return i;
+            }
+            i += 1;
+        }
+        -1
+    }
+
+    function pairs(limit: Number): Number {
+        let count = 0;
+        outer: for (let i = 0; i < limit; i++) {
+            for (let j = 0; j < limit; j++) {
+                if (j > i) {
+                    continue outer;
+                }
+                if (i + j > 4) {
+                    break outer;
+                }
+                count += 1;
+            }
+        }
+        count
+    }
+
+    function skip_odd(limit: Number): Number {
+        let sum = 0;
+        let i = 0;
+        while (i < limit) {
+            i += 1;
+            try {
+                if (i % 2 == 1) {
+                    continue;
+                }
+                if (i > 6) {
+                    break;
+                }
+                positive(-i);
+            } catch {
+                sum += i;
+            }
+        }
+        sum
+    }
+
+    function checked(value: Number): Number {
+        if (value == 0) {
+            return 0;
+        }
+        positive(value) + 1
+    }
+}
+
+#[test]
+fn control_flow() {
+    let values = vec![Number::from(3.0), Number::from(5.0)];
+    assert_eq!(find(values.clone(), Number::from(5.0)), Number::from(1.0));
+    assert_eq!(find(values, Number::from(4.0)), Number::from(-1.0));
+    assert_eq!(pairs(Number::from(4.0)), Number::from(8.0));
+    assert_eq!(skip_odd(Number::from(10.0)), Number::from(12.0));
+    assert_eq!(checked(Number::from(0.0)), Ok(Number::from(0.0)));
+    assert_eq!(checked(Number::from(2.0)), Ok(Number::from(3.0)));
+    assert!(checked(Number::from(-2.0)).is_err());
+}
+
+rsscript! {
+    class Account {
+        deposits: Number = 0;
+
+        constructor(public owner: JsString, private balance: Number) {
+            this.deposits += 1;
+        }
+
+        total(): Number {
+            this.balance
+        }
+    }
+
+    class Branch {
+        backup: Account;
+
+        constructor(public main: Account) {}
+    }
+
+    function open(): Account {
+        const account = new Account("Ann", new Number("12.5"));
+        account
+    }
+}
+
+#[test]
+fn constructors() {
+    let mut account = open();
+
+    assert_eq!(*account.owner, "Ann");
+    assert_eq!(account.deposits, Number::from(1.0));
+    assert_eq!(account.total(), Number::from(12.5));
+
+    let branch = Branch::new(account);
+    assert_eq!(*branch.main.owner, "Ann");
+    assert_eq!(branch.backup.deposits, Number::from(0.0));
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Ticket(pub u32);
+
+rsscript! {
+    class Holder {
+        doubled: Number = 0;
+
+        constructor(public ticket: Ticket, public size: Number) {
+            this.doubled = size * 2;
+        }
+    }
+
+    function range_error(): RangeError {
+        new RangeError("bad")
+    }
+
+    function plain_error(): Error {
+        new Error()
+    }
+}
+
+#[test]
+fn constructed_builtins_and_moved_properties() {
+    let holder = Holder::new(Ticket(7), Number::from(2.0));
+    assert_eq!(holder.ticket, Ticket(7));
+    assert_eq!(holder.doubled, Number::from(4.0));
+
+    assert_eq!(range_error().to_string(), "RangeError: bad");
+    assert_eq!(plain_error().to_string(), "Error");
+}
+
+rsscript! {
+    class Size {
+        width: Number = 1;
+        height: Number = 2;
+
+        get area(): Number {
+            this.width * this.height
+        }
+    }
+
+    function create_objects(): Number {
+        const size = new Size();
+        let wider = { ...size, width: 3 };
+        let taller: Size = { height: 4 };
+        let counter = {
+            label: "clicks",
+            count: 0,
+            get twice(): Number {
+                this.count * 2
+            },
+            set twice(value: Number) {
+                this.count = value / 2;
+            },
+            add(by: Number): Number {
+                this.count += by;
+                this.count
+            },
+        };
+        counter.twice = 4;
+        counter.add(1);
+        wider.area + taller.area + counter.twice
+    }
+}
+
+#[test]
+fn object_literals() {
+    assert_eq!(create_objects(), Number::from(6.0 + 4.0 + 6.0));
+}
+
+rsscript! {
+    class Counter {
+        count: Number = 0;
+        static created: Number = 0;
+
+        static {
+            Counter.created = 100;
+        }
+
+        constructor() {
+            Counter.created += 1;
+        }
+
+        get doubled(): Number {
+            this.count * 2
+        }
+
+        set doubled(value: Number) {
+            this.count = value / 2;
+        }
+
+        bump() {
+            this.doubled += 4;
+            this.doubled++;
+        }
+    }
+
+    function counted(): Number {
+        let counter = new Counter();
+        counter.doubled = 10;
+        counter.bump();
+        counter.doubled
+    }
+}
+
+#[test]
+fn accessors() {
+    assert_eq!(counted(), Number::from(15.0));
+    assert_eq!(Counter::created(), Number::from(101.0));
+}
+
+rsscript! {
+    enum Direction {
+        Up = 1,
+        Down,
+        Left = 10,
+        Right,
+    }
+
+    enum Suit {
+        Hearts = "hearts",
+        Spades = "spades",
+    }
+
+    const enum Limit {
+        Low = -1,
+        High = 2.5,
+    }
+
+    function direction_name(): Option<JsString> {
+        Direction[Direction.Right]
+    }
+
+    function limits(): Number {
+        Limit.High - Limit.Low
+    }
+
+    function opposite(direction: Direction): Direction {
+        if (direction == Direction.Up) {
+            return Direction.Down;
+        } else if (direction == Direction.Down) {
+            return Direction.Up;
+        } else if (direction == Direction.Left) {
+            return Direction.Right;
+        } else {
+            return Direction.Left;
+        }
+    }
+}
+
+#[test]
+fn enums() {
+    assert_eq!(
+        direction_name().as_deref().map(|name| name.as_str()),
+        Some("Right")
+    );
+    assert_eq!(limits(), Number::from(3.5));
+    assert_eq!(opposite(Direction::Left), Direction::Right);
+    assert_eq!(Number::from(Direction::Down), Number::from(2.0));
+    assert_eq!(
+        Direction::try_from(Number::from(11.0)),
+        Ok(Direction::Right)
+    );
+    assert!(Direction::try_from(Number::from(3.0)).is_err());
+    assert_eq!(JsString::from(Suit::Spades).as_str(), "spades");
+    assert_eq!(Suit::Hearts.to_string(), "hearts");
+}
+
+rsscript! {
+    type Shape = { kind: "circle", r: Number } | { kind: "square", side: Number }
+    type Value = Number | JsString
+
+    function area(shape: Shape): Number {
+        switch (shape.kind) {
+            case "circle":
+                return shape.r * shape.r * 3;
+            case "square":
+                return shape.side * shape.side;
+        }
+    }
+
+    function grow(shape: Shape): Shape {
+        if ("r" in shape) {
+            shape.r = shape.r * 2;
+        } else if (shape.kind !== "circle") {
+            shape.side += 1;
+        }
+        shape
+    }
+
+    function numeric(value: Value): Number {
+        if (typeof value === "number") {
+            return value;
+        }
+        return 0;
+    }
+
+    function kind(shape: Shape): JsString {
+        shape.kind
+    }
+
+    function doubled(values: Vec<Value>): Number {
+        let total = 0;
+        for (const value of values) {
+            const v: Value = value;
+            if (typeof v !== "number") {
+                continue;
+            }
+            total += v * 2;
+        }
+        total
+    }
+
+    function guarded(value: Value): Number {
+        if (typeof value !== "number") {
+            return 0;
+        }
+        value + 1
+    }
+}
+
+#[test]
+fn unions() {
+    assert_eq!(
+        area(Shape::Circle {
+            r: Number::from(2.0)
+        }),
+        Number::from(12.0)
+    );
+    assert_eq!(
+        area(Shape::Square {
+            side: Number::from(3.0)
+        }),
+        Number::from(9.0)
+    );
+    assert_eq!(
+        grow(Shape::Circle {
+            r: Number::from(2.0)
+        }),
+        Shape::Circle {
+            r: Number::from(4.0)
+        }
+    );
+    assert_eq!(
+        grow(Shape::Square {
+            side: Number::from(3.0)
+        }),
+        Shape::Square {
+            side: Number::from(4.0)
+        }
+    );
+    assert_eq!(numeric(Value::Number(Number::from(5.0))), Number::from(5.0));
+    assert_eq!(
+        numeric(Value::JsString(JsString::new(String::from("five")))),
+        Number::from(0.0)
+    );
+    assert_eq!(guarded(Value::Number(Number::from(5.0))), Number::from(6.0));
+    assert_eq!(
+        guarded(Value::JsString(JsString::new(String::from("five")))),
+        Number::from(0.0)
+    );
+    assert_eq!(
+        doubled(vec![
+            Value::Number(Number::from(1.0)),
+            Value::JsString(JsString::new(String::from("two"))),
+            Value::Number(Number::from(3.0)),
+        ]),
+        Number::from(8.0)
+    );
+    assert_eq!(
+        kind(Shape::Square {
+            side: Number::from(1.0)
+        })
+        .as_str(),
+        "square"
+    );
+}
+
+rsscript! {
+    class Price {
+        constructor(public amount: Number, public discount: Option<Number>) {}
+
+        total(): Number {
+            this.amount - (this.discount ?? 0)
+        }
+    }
+
+    function discount(price: Option<Price>): Number {
+        price?.discount ?? 0
+    }
+
+    function net_total(price: Option<Price>): Option<Number> {
+        price?.total()
+    }
+
+    function pick(values: Option<Vec<Number>>, index: usize): Number {
+        values?.[index] ?? -1
+    }
+
+    function apply(f: Option<fn(Number) -> Number>, value: Number): Number {
+        f?.(value) ?? value
+    }
+
+    function fallback(a: Option<Number>, b: Option<Number>): Number {
+        a ?? b ?? 0
+    }
+}
+
+#[test]
+fn optional_chaining() {
+    let price = |discount| Some(Price::new(Number::from(10.0), discount));
+
+    assert_eq!(discount(price(Some(Number::from(2.0)))), Number::from(2.0));
+    assert_eq!(discount(price(None)), Number::from(0.0));
+    assert_eq!(discount(None), Number::from(0.0));
+    assert_eq!(
+        net_total(price(Some(Number::from(3.0)))),
+        Some(Number::from(7.0))
+    );
+    assert_eq!(net_total(None), None);
+    assert_eq!(pick(Some(vec![Number::from(4.0)]), 0), Number::from(4.0));
+    assert_eq!(pick(None, 0), Number::from(-1.0));
+    assert_eq!(
+        apply(Some(|value| value * Number::from(2.0)), Number::from(3.0)),
+        Number::from(6.0)
+    );
+    assert_eq!(apply(None, Number::from(3.0)), Number::from(3.0));
+    assert_eq!(fallback(None, Some(Number::from(1.0))), Number::from(1.0));
+    assert_eq!(fallback(None, None), Number::from(0.0));
+}
+
+rsscript! {
+    function greet(name: JsString, greeting?: JsString): JsString {
+        (greeting ?? "Hello") + ", " + name
+    }
+
+    function range(start: Number, end: Number = start + 10, ...steps: Number[]): Number {
+        let sum = end - start;
+        for (const step in steps) {
+            sum += step;
+        }
+        sum
+    }
+
+    class Scale {
+        factor: Number = 1;
+
+        constructor(factor: Number = 2) {
+            this.factor = factor;
+        }
+
+        apply(value: Number, offset?: Number): Number {
+            value * this.factor + (offset ?? 0)
+        }
+    }
+
+    function scaled(offset: Option<Number>): Number {
+        let scale = new Scale();
+        let triple = new Scale(3);
+        scale.apply(1) + triple.apply(1, 1) + scale.apply(0, offset)
+    }
+
+    function greet_twice(): JsString {
+        greet("Ann") + "; " + greet("Bo", "Hi")
+    }
+
+    function ranges(): Number {
+        range(0) + range(0, 5) + range(0, 5, 2, 3)
+    }
+}
+
+#[test]
+fn optional_parameters() {
+    assert_eq!(*greet(JsString::new("Ann".to_string()), None), "Hello, Ann");
+    assert_eq!(
+        greet_twice(),
+        JsString::new("Hello, Ann; Hi, Bo".to_string())
+    );
+    assert_eq!(range(Number::from(1.0), None, vec![]), Number::from(10.0));
+    assert_eq!(ranges(), Number::from(25.0));
+    assert_eq!(scaled(Some(Number::from(5.0))), Number::from(11.0));
+    assert_eq!(scaled(None), Number::from(6.0));
+}
+
+rsscript! {
+    function* count(start: Number, end: Number): Generator<Number> {
+        for (let i = start; i < end; i++) {
+            yield i;
+        }
+    }
+
+    function* evens(limit: Number): Generator<Number> {
+        for (const n of count(0, limit)) {
+            if (n % 2 == 0) {
+                yield n;
+            }
+        }
+        yield* [10, 20];
+    }
+
+    class Bag {
+        items: Vec<Number> = [];
+
+        add(item: Number) {
+            this.items.push(item);
+        }
+
+        *doubled(): Iterable<Number> {
+            for (const item of this.items.clone()) {
+                yield item * 2;
+            }
+        }
+    }
+
+    function sum_generated(): Number {
+        let sum = 0;
+        for (const n of evens(5)) {
+            sum += n;
+        }
+
+        const squares = function*(n: Number) {
+            yield n * n;
+            yield n * n * n;
+        };
+        for (const s of squares(2)) {
+            sum += s;
+        }
+
+        let bag = new Bag();
+        bag.add(1);
+        bag.add(2);
+        for (const item of bag.doubled()) {
+            sum += item;
+        }
+        sum
+    }
+
+    function first_two(): Number {
+        let numbers = count(1, 3);
+        let first = numbers.next();
+        let second = numbers.next();
+        let done = numbers.next().done;
+        done ? first.value.unwrap() + second.value.unwrap() : 0
+    }
+}
+
+#[test]
+fn generators() {
+    // 0 + 2 + 4 + 10 + 20, then 4 + 8, then 2 + 4.
+    assert_eq!(sum_generated(), Number::from(54.0));
+    assert_eq!(first_two(), Number::from(3.0));
+    assert_eq!(
+        count(Number::from(0.0), Number::from(3.0)).collect::<Vec<_>>(),
+        vec![Number::from(0.0), Number::from(1.0), Number::from(2.0)]
+    );
+}
@@ -12,8 +12,16 @@ pub mod string;
 
 pub mod object;
 
+pub mod class;
+
 pub mod convert_op;
 
+pub mod ops;
+
+pub mod error;
+
+pub mod generator;
+
 pub trait JsValue: DynClone + Display {
     #[allow(non_snake_case)]
     fn toString(self) -> JsString;
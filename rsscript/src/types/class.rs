@@ -0,0 +1,29 @@
+use std::ops::DerefMut;
+
+pub trait Extends: DerefMut<Target = <Self as Extends>::Super> {
+    type Super;
+
+    fn into_super(self) -> Self::Super
+    where
+        Self: Sized;
+}
+
+// What is left of an object destructured with a rest pattern, `const { a, ...rest } = value`. The
+// rest holds every field of the object as an `Option`, and the ones taken out are `None`.
+pub trait Rest {
+    type Rest;
+
+    fn rest(&self) -> Self::Rest;
+
+    // Sets the fields that are `Some` in `rest`, which is how another object of the class is
+    // spread into an object literal, `{ ...value, a: 1 }`.
+    fn update(&mut self, rest: Self::Rest);
+}
+
+// A copy of `value` made field by field, which an object literal spreading it, `{ ...value }`,
+// starts from before setting its own fields.
+pub fn spread<T: Rest + Default>(value: &T) -> T {
+    let mut object = T::default();
+    object.update(value.rest());
+    object
+}
@@ -0,0 +1,176 @@
+use std::{
+    cell::RefCell,
+    fmt::Display,
+    ops::{Deref, DerefMut},
+};
+
+use super::{
+    class::Extends,
+    string::{JsString, String},
+    Constructor, JsValue,
+};
+
+thread_local! {
+    static STACK: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+// Generated functions enter a frame for as long as they run, so that errors can record which
+// script functions they were created in.
+pub struct Frame {
+    _private: (),
+}
+
+impl Frame {
+    pub fn enter(name: &'static str) -> Self {
+        STACK.with(|stack| stack.borrow_mut().push(name));
+        Self { _private: () }
+    }
+}
+
+impl Drop for Frame {
+    fn drop(&mut self) {
+        STACK.with(|stack| stack.borrow_mut().pop());
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+    pub name: JsString,
+    pub message: JsString,
+    pub cause: Option<Box<Error>>,
+    // The innermost function comes first, like in a JavaScript stack trace.
+    pub stack: Vec<&'static str>,
+}
+
+pub fn Error(message: impl JsValue) -> Error {
+    Error::new(String(message), None)
+}
+
+impl Error {
+    pub fn new(message: JsString, cause: Option<Error>) -> Self {
+        Self::named("Error", message, cause)
+    }
+
+    fn named(name: &str, message: JsString, cause: Option<Error>) -> Self {
+        Self {
+            name: JsString::new(name.to_string()),
+            message,
+            cause: cause.map(Box::new),
+            stack: STACK.with(|stack| stack.borrow().iter().rev().copied().collect()),
+        }
+    }
+}
+
+// `new Error(message)` and `new Error()` make the same errors as the calls.
+impl<T: JsValue> Constructor<fn(T) -> Self> for Error {
+    const constructor: fn(T) -> Self = |message| Error::new(String(message), None);
+}
+
+impl Constructor<fn() -> Self> for Error {
+    const constructor: fn() -> Self = || Error::new(JsString::default(), None);
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.message.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{}: {}", self.name, self.message)
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.cause
+            .as_deref()
+            .map(|cause| cause as &(dyn std::error::Error + 'static))
+    }
+}
+
+// How a `try` statement with a `finally` block was left, which is carried on with once `finally`
+// has run.
+pub enum Completion<T> {
+    Normal,
+    Return(T),
+    Throw(Error),
+    // A `break` or `continue` out of the statement, numbered by the compiler.
+    Jump(usize),
+}
+
+macro_rules! impl_error {
+    ($($name: ident);* $(;)?) => {
+        $(
+            #[derive(Clone, Debug, PartialEq)]
+            pub struct $name {
+                error: Error,
+            }
+
+            pub fn $name(message: impl JsValue) -> $name {
+                $name::new(String(message), None)
+            }
+
+            impl $name {
+                pub fn new(message: JsString, cause: Option<Error>) -> Self {
+                    Self {
+                        error: Error::named(stringify!($name), message, cause),
+                    }
+                }
+            }
+
+            impl<T: JsValue> Constructor<fn(T) -> Self> for $name {
+                const constructor: fn(T) -> Self = |message| $name::new(String(message), None);
+            }
+
+            impl Constructor<fn() -> Self> for $name {
+                const constructor: fn() -> Self = || $name::new(JsString::default(), None);
+            }
+
+            impl Deref for $name {
+                type Target = Error;
+
+                fn deref(&self) -> &Error {
+                    &self.error
+                }
+            }
+
+            impl DerefMut for $name {
+                fn deref_mut(&mut self) -> &mut Error {
+                    &mut self.error
+                }
+            }
+
+            impl Extends for $name {
+                type Super = Error;
+
+                fn into_super(self) -> Error {
+                    self.error
+                }
+            }
+
+            impl From<$name> for Error {
+                fn from(error: $name) -> Error {
+                    error.error
+                }
+            }
+
+            impl Display for $name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    self.error.fmt(f)
+                }
+            }
+
+            impl std::error::Error for $name {
+                fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                    std::error::Error::source(&self.error)
+                }
+            }
+        )*
+    };
+}
+
+impl_error!(
+    TypeError;
+    RangeError;
+    SyntaxError;
+);
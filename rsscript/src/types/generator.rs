@@ -0,0 +1,94 @@
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+// The body of a generator function runs as a future that is suspended at every `yield`, leaving
+// the yielded value in a slot it shares with the generator. The future only ever waits on `yield`,
+// so it is polled without a waker.
+pub struct Generator<'a, T> {
+    body: Option<Pin<Box<dyn Future<Output = ()> + 'a>>>,
+    slot: Rc<Cell<Option<T>>>,
+}
+
+impl<'a, T: 'a> Generator<'a, T> {
+    pub fn new<F: Future<Output = ()> + 'a>(body: impl FnOnce(Yielder<T>) -> F) -> Self {
+        let slot = Rc::new(Cell::new(None));
+        let body = body(Yielder { slot: slot.clone() });
+        Self {
+            body: Some(Box::pin(body)),
+            slot,
+        }
+    }
+}
+
+impl<T> Generator<'_, T> {
+    fn resume(&mut self) -> Option<T> {
+        let body = self.body.as_mut()?;
+        match body.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Pending => match self.slot.take() {
+                Some(value) => Some(value),
+                None => panic!("a generator can only be suspended by `yield`"),
+            },
+            Poll::Ready(()) => {
+                self.body = None;
+                None
+            }
+        }
+    }
+
+    // The iterator protocol of JavaScript, which `Iterator::next` is the Rust side of.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> IteratorResult<T> {
+        let value = self.resume();
+        IteratorResult {
+            done: value.is_none(),
+            value,
+        }
+    }
+}
+
+impl<T> Iterator for Generator<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.resume()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IteratorResult<T> {
+    pub value: Option<T>,
+    pub done: bool,
+}
+
+pub struct Yielder<T> {
+    slot: Rc<Cell<Option<T>>>,
+}
+
+impl<T> Yielder<T> {
+    pub fn yield_value(&self, value: T) -> Suspend {
+        self.slot.set(Some(value));
+        Suspend { suspended: false }
+    }
+}
+
+pub struct Suspend {
+    suspended: bool,
+}
+
+impl Future for Suspend {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<()> {
+        if self.suspended {
+            Poll::Ready(())
+        } else {
+            self.suspended = true;
+            Poll::Pending
+        }
+    }
+}
@@ -4,7 +4,7 @@ use super::{string::{JsString, String}, Constructor, JsValue};
 
 pub mod number_ops;
 
-#[derive(Clone, Copy, PartialEq, Debug, PartialOrd)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, PartialOrd)]
 pub struct Number {
     value: f64,
 }
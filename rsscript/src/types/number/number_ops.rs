@@ -315,3 +315,49 @@ impl_assign_bit_ops!(
     ops::ShlAssign, shl_assign, <<;
     ops::ShrAssign, shr_assign, >>;
 );
+
+impl Number {
+    pub fn pow(self, rhs: Number) -> Number {
+        Number {
+            value: self.value.powf(rhs.value),
+        }
+    }
+
+    pub fn unsigned_shr(self, rhs: Number) -> Number {
+        Number {
+            value: ((self.as_i64() as u32) >> (rhs.as_i64() as u32 & 31)) as f64,
+        }
+    }
+
+    // The position of an array element this is, if it is a non-negative integer.
+    pub fn as_index(self) -> Option<usize> {
+        (self.value >= 0.0 && self.value.fract() == 0.0).then_some(self.value as usize)
+    }
+}
+
+// Elements that are assigned to or borrowed must exist.
+fn element_index(index: Number, len: usize) -> usize {
+    match index.as_index() {
+        Some(i) if i < len => i,
+        Some(_) => panic!(
+            "index {} is out of bounds for an array of length {}",
+            index, len
+        ),
+        None => panic!("{} is not an array index", index),
+    }
+}
+
+impl<T> ops::Index<Number> for Vec<T> {
+    type Output = T;
+
+    fn index(&self, index: Number) -> &T {
+        &self[element_index(index, self.len())]
+    }
+}
+
+impl<T> ops::IndexMut<Number> for Vec<T> {
+    fn index_mut(&mut self, index: Number) -> &mut T {
+        let index = element_index(index, self.len());
+        &mut self[index]
+    }
+}
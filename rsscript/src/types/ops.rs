@@ -0,0 +1,210 @@
+use std::{
+    collections::HashMap,
+    hash::{BuildHasher, Hash},
+};
+
+use super::{number::Number, string::JsString};
+
+// Whether a value is considered true in a condition, like `Boolean(value)` in JavaScript.
+pub trait Truthy {
+    fn truthy(&self) -> bool;
+}
+
+impl Truthy for bool {
+    fn truthy(&self) -> bool {
+        *self
+    }
+}
+
+impl Truthy for Number {
+    fn truthy(&self) -> bool {
+        **self != 0.0 && !self.is_nan()
+    }
+}
+
+impl Truthy for JsString {
+    fn truthy(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<T: Truthy> Truthy for Option<T> {
+    fn truthy(&self) -> bool {
+        self.as_ref().is_some_and(Truthy::truthy)
+    }
+}
+
+// Objects are always truthy, even when empty.
+impl<T> Truthy for Vec<T> {
+    fn truthy(&self) -> bool {
+        true
+    }
+}
+
+impl<T: Truthy + ?Sized> Truthy for &T {
+    fn truthy(&self) -> bool {
+        (**self).truthy()
+    }
+}
+
+// `null` and `undefined` are both `None`, so only an `Option` is nullish. `?.` goes through this so
+// that a borrowed `Option` can be checked without moving out of it.
+pub trait Nullish {
+    type Value;
+
+    fn into_option(self) -> Option<Self::Value>;
+}
+
+impl<T> Nullish for Option<T> {
+    type Value = T;
+
+    fn into_option(self) -> Option<T> {
+        self
+    }
+}
+
+impl<'a, T> Nullish for &'a Option<T> {
+    type Value = &'a T;
+
+    fn into_option(self) -> Option<&'a T> {
+        self.as_ref()
+    }
+}
+
+impl<'a, T> Nullish for &'a mut Option<T> {
+    type Value = &'a mut T;
+
+    fn into_option(self) -> Option<&'a mut T> {
+        self.as_mut()
+    }
+}
+
+// What follows a `?.` may itself be nullish, in which case the chain is as well, rather than giving
+// an `Option` of an `Option`. Method resolution picks `ChainOption` for an `Option` value and falls
+// back to `ChainValue` through a mutable borrow for anything else.
+pub struct Chained<T>(pub Option<T>);
+
+pub trait ChainOption {
+    type Value;
+
+    fn chained(self) -> Option<Self::Value>;
+}
+
+impl<T> ChainOption for Chained<Option<T>> {
+    type Value = T;
+
+    fn chained(self) -> Option<T> {
+        self.0.flatten()
+    }
+}
+
+pub trait ChainValue {
+    type Value;
+
+    fn chained(self) -> Option<Self::Value>;
+}
+
+impl<T> ChainValue for &mut Chained<T> {
+    type Value = T;
+
+    fn chained(self) -> Option<T> {
+        self.0.take()
+    }
+}
+
+// `a ?? b` is only nullish when `b` may be, so what it gives depends on the type of `b`.
+pub trait Coalesce<R> {
+    type Output;
+
+    fn coalesce(self, right: impl FnOnce() -> R) -> Self::Output;
+}
+
+impl<T> Coalesce<T> for Option<T> {
+    type Output = T;
+
+    fn coalesce(self, right: impl FnOnce() -> T) -> T {
+        self.unwrap_or_else(right)
+    }
+}
+
+impl<T> Coalesce<Option<T>> for Option<T> {
+    type Output = Option<T>;
+
+    fn coalesce(self, right: impl FnOnce() -> Option<T>) -> Option<T> {
+        self.or_else(right)
+    }
+}
+
+// Reading an element that does not exist gives `None`, like `undefined` in JavaScript.
+pub trait Get<K> {
+    type Output;
+
+    fn get(&self, key: K) -> Option<&Self::Output>;
+}
+
+impl<T> Get<Number> for Vec<T> {
+    type Output = T;
+
+    fn get(&self, index: Number) -> Option<&T> {
+        <[T]>::get(self, index.as_index()?)
+    }
+}
+
+impl<T> Get<usize> for Vec<T> {
+    type Output = T;
+
+    fn get(&self, index: usize) -> Option<&T> {
+        <[T]>::get(self, index)
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Get<K> for HashMap<K, V, S> {
+    type Output = V;
+
+    fn get(&self, key: K) -> Option<&V> {
+        HashMap::get(self, &key)
+    }
+}
+
+pub trait TypeOf {
+    fn type_of(&self) -> JsString;
+}
+
+macro_rules! impl_type_of {
+    ($($t: ty, $name: literal);* $(;)?) => {
+        $(
+            impl TypeOf for $t {
+                fn type_of(&self) -> JsString {
+                    JsString::new(String::from($name))
+                }
+            }
+        )*
+    };
+}
+
+impl_type_of!(
+    bool, "boolean";
+    Number, "number";
+    JsString, "string";
+);
+
+impl<T: TypeOf> TypeOf for Option<T> {
+    fn type_of(&self) -> JsString {
+        match self {
+            Some(value) => value.type_of(),
+            None => JsString::new(String::from("undefined")),
+        }
+    }
+}
+
+impl<T> TypeOf for Vec<T> {
+    fn type_of(&self) -> JsString {
+        JsString::new(String::from("object"))
+    }
+}
+
+impl<T: TypeOf + ?Sized> TypeOf for &T {
+    fn type_of(&self) -> JsString {
+        (**self).type_of()
+    }
+}
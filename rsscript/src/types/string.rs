@@ -2,7 +2,7 @@ use std::ops::{Add, AddAssign, Deref, Mul};
 
 use super::{number::Number, Constructor, JsValue};
 
-#[derive(Clone, PartialEq, Eq, Debug, Hash, PartialOrd, Ord)]
+#[derive(Clone, PartialEq, Eq, Debug, Default, Hash, PartialOrd, Ord)]
 pub struct JsString {
     value: String,
 }